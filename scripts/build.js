@@ -18,6 +18,35 @@ try {
     playwright = null;
 }
 
+// ============================================================
+// EVENT STREAM
+// ============================================================
+
+// With --events, progress is also written to stdout as one JSON object per
+// line, so the desktop app never has to parse the human-readable log:
+//   {"event":"stage_started","stage":"parse"}      stages: parse, html, png
+//   {"event":"stage_finished","stage":"parse"}
+//   {"event":"warning","message":"..."}
+//   {"event":"artifact","kind":"html","path":"...","palette":"reds","viewMode":"day"}
+//   {"event":"error","code":"INPUT_NOT_FOUND","message":"..."}
+// In --worker mode there are also:
+//   {"event":"ready"}                              once, when requests are accepted
+//   {"event":"done","id":"1","ok":true}            after each request
+let eventsEnabled = false;
+
+function emitEvent(event, fields = {}) {
+    if (eventsEnabled) {
+        process.stdout.write(JSON.stringify({ event, ...fields }) + '\n');
+    }
+}
+
+// Error with a stable code for the "error" event; the message stays free-form
+function codedError(code, message) {
+    const error = new Error(message);
+    error.code = code;
+    return error;
+}
+
 // ============================================================
 // BRAND COLOR PALETTES
 // ============================================================
@@ -169,6 +198,11 @@ function collectSubtasks(row) {
 
 // Parse Excel file
 async function parseExcel(filePath) {
+    if (path.extname(filePath).toLowerCase() === '.xls') {
+        // exceljs only understands the .xlsx format; the desktop app converts .xls natively
+        throw codedError('UNSUPPORTED_FORMAT', 'Legacy .xls workbooks cannot be read by the CLI. Open the file in the GanttGen app or save it as .xlsx');
+    }
+
     const workbook = new ExcelJS.Workbook();
     await workbook.xlsx.readFile(filePath);
     
@@ -283,7 +317,7 @@ async function parseExcel(filePath) {
             if (!name || !String(name).trim()) return; // Skip empty rows
             
             const milestone = {
-                name: String(name).trim().replace(/\\n/g, '\n'),
+                name: String(name).trim().replace(/\\([\\n])/g, (_, c) => (c === 'n' ? '\n' : '\\')),
                 date: String(row.getCell(2).value || '').trim()
             };
             
@@ -396,7 +430,7 @@ function validateConfig(config) {
     }
     
     if (errors.length > 0) {
-        throw new Error('Validation errors:\n' + errors.map(e => `  - ${e}`).join('\n'));
+        throw codedError('VALIDATION_FAILED', 'Validation errors:\n' + errors.map(e => `  - ${e}`).join('\n'));
     }
 }
 
@@ -539,14 +573,15 @@ async function build(inputPath, outputPath, options = {}) {
     const isJSON = inputExt === '.json';
     
     if (!isExcel && !isJSON) {
-        throw new Error(`Unsupported file format: ${inputExt}. Expected .json or .xlsx`);
+        throw codedError('UNSUPPORTED_FORMAT', `Unsupported file format: ${inputExt}. Expected .json or .xlsx`);
     }
     
     if (!fs.existsSync(inputPath)) {
-        throw new Error(`Input file not found: ${inputPath}`);
+        throw codedError('INPUT_NOT_FOUND', `Input file not found: ${inputPath}`);
     }
     
     console.log(`📖 Reading ${isExcel ? 'Excel' : 'JSON'} file: ${inputPath}`);
+    emitEvent('stage_started', { stage: 'parse' });
     
     // Parse input
     let config;
@@ -606,6 +641,7 @@ async function build(inputPath, outputPath, options = {}) {
     console.log('✓ Validating configuration...');
     validateConfig(config);
     console.log('✓ Validation passed');
+    emitEvent('stage_finished', { stage: 'parse' });
     
     // Generate HTML
     const templatePath = path.join(__dirname, '..', 'templates', 'gantt_template.html');
@@ -618,25 +654,34 @@ async function build(inputPath, outputPath, options = {}) {
     })();
     
     console.log('✓ Generating HTML...');
+    emitEvent('stage_started', { stage: 'html' });
     const dropShadow = options.dropShadow !== false; // Default to true if not specified
     config.viewMode = options.viewMode || 'day'; // Add viewMode to config
     generateHTML(config, resolvedTemplatePath, htmlOutputPath, dropShadow);
     console.log(`✓ Generated HTML at ${htmlOutputPath}`);
+    const variant = { palette: options.palette ? options.palette.toLowerCase() : null, viewMode: config.viewMode };
+    emitEvent('artifact', { kind: 'html', path: htmlOutputPath, ...variant });
+    emitEvent('stage_finished', { stage: 'html' });
     
     // Generate PNG export with transparent background (if requested and Playwright is available)
     const shouldExportPng = options.exportPng !== false; // Default to true for CLI backwards compatibility
     if (shouldExportPng && playwright) {
         const pngOutputPath = htmlOutputPath.replace(/\.html$/, '.png');
         console.log('✓ Exporting PNG...');
+        emitEvent('stage_started', { stage: 'png' });
         try {
             await exportPNG(htmlOutputPath, pngOutputPath, { dropShadow });
             console.log(`✓ Generated PNG at ${pngOutputPath}`);
+            emitEvent('artifact', { kind: 'png', path: pngOutputPath, ...variant });
         } catch (error) {
             console.warn(`⚠️  PNG export failed: ${error.message}`);
             console.warn('   (HTML file was generated successfully)');
+            emitEvent('warning', { message: error.message });
         }
+        emitEvent('stage_finished', { stage: 'png' });
     } else if (shouldExportPng && !playwright) {
         console.log('ℹ️  Skipping PNG export (Playwright not installed)');
+        emitEvent('warning', { message: 'Skipped PNG export: Playwright is not installed' });
         console.log('   Install with: npm install @playwright/test');
         console.log('   PNG export requires Google Chrome or Microsoft Edge');
     } else {
@@ -654,34 +699,130 @@ async function build(inputPath, outputPath, options = {}) {
         const jsonOutputPath = path.join(configDir, 'project.json');
         fs.writeFileSync(jsonOutputPath, JSON.stringify(config, null, 2), 'utf8');
         console.log(`✓ Saved config to ${jsonOutputPath}`);
+        emitEvent('artifact', { kind: 'json', path: jsonOutputPath, ...variant });
     } catch (error) {
         console.warn(`ℹ️  Skipping config save (non-fatal): ${error.message}`);
+        emitEvent('warning', { message: `Skipped config save: ${error.message}` });
     }
     
     console.log(`\n  To preview: open ${htmlOutputPath} in a browser`);
 }
 
-// CLI
-if (require.main === module) {
-    const args = process.argv.slice(2);
+// Add the palette and view mode suffixes to an explicit output path
+function variantOutputPath(outputPath, palette, viewMode) {
+    const paletteSuffix = palette ? `_${palette.toLowerCase()}` : '';
+    const viewModeSuffix = viewMode === 'week' ? '_week' : '';
+    const ext = path.extname(outputPath);
+    return path.join(path.dirname(outputPath), `${path.basename(outputPath, ext)}${paletteSuffix}${viewModeSuffix}${ext}`);
+}
+
+// Build every palette × view mode combination in one process (palettes outer, view modes inner)
+async function buildVariants(inputPath, outputPath, palettes, viewModes, options = {}) {
+    const multiple = palettes.length * viewModes.length > 1;
+    for (const palette of palettes) {
+        for (const viewMode of viewModes) {
+            const variantOutput = outputPath && multiple
+                ? variantOutputPath(outputPath, palette, viewMode)
+                : outputPath;
+            await build(inputPath, variantOutput, { ...options, palette, viewMode });
+        }
+    }
+}
+
+// Parse build arguments; null when --input is missing
+function parseArgs(args) {
     const inputIndex = args.indexOf('--input') !== -1 ? args.indexOf('--input') : args.indexOf('-i');
     const outputIndex = args.indexOf('--output') !== -1 ? args.indexOf('--output') : args.indexOf('-o');
     const paletteIndex = args.indexOf('--palette') !== -1 ? args.indexOf('--palette') : args.indexOf('-p');
     const viewModeIndex = args.indexOf('--view-mode') !== -1 ? args.indexOf('--view-mode') : -1;
-    const pngFlag = args.includes('--png');
     const noPngFlag = args.includes('--no-png');
     const dropShadowFlag = args.includes('--drop-shadow');
-    
+
     if (inputIndex === -1 || !args[inputIndex + 1]) {
+        return null;
+    }
+
+    const inputPath = path.resolve(args[inputIndex + 1]);
+    const outputPath = outputIndex !== -1 && args[outputIndex + 1] 
+        ? path.resolve(args[outputIndex + 1])
+        : null;
+    const palette = paletteIndex !== -1 && args[paletteIndex + 1]
+        ? args[paletteIndex + 1]
+        : null;
+    const viewMode = viewModeIndex !== -1 && args[viewModeIndex + 1]
+        ? args[viewModeIndex + 1]
+        : 'day';
+    // Default to true for CLI (backwards compatible), but respect --no-png flag
+    const exportPng = noPngFlag ? false : true;
+    // Default drop shadow to true (backwards compatible - shadows were always on before)
+    const dropShadow = dropShadowFlag !== false; // If flag present, it's true; if not present, default to true
+    
+    const list = (value) => value.split(',').map(item => item.trim()).filter(Boolean);
+    const palettes = palette ? list(palette) : [null];
+    const viewModes = list(viewMode).length > 0 ? list(viewMode) : ['day'];
+
+    return { inputPath, outputPath, palettes, viewModes, options: { exportPng, dropShadow } };
+}
+
+// Worker mode: one build per stdin line, {"id":"1","args":["--input","..."]},
+// answered with the usual events and a closing "done" event. Modules stay
+// loaded between requests. Exits when stdin closes.
+function runWorker() {
+    eventsEnabled = true;
+    const readline = require('readline');
+    const input = readline.createInterface({ input: process.stdin });
+    let queue = Promise.resolve();
+
+    input.on('line', (line) => {
+        if (!line.trim()) return;
+        queue = queue.then(async () => {
+            let id = null;
+            try {
+                const request = JSON.parse(line);
+                id = request.id;
+                const parsed = parseArgs(request.args || []);
+                if (!parsed) {
+                    throw codedError('BUILD_FAILED', 'Missing --input');
+                }
+                await buildVariants(parsed.inputPath, parsed.outputPath, parsed.palettes, parsed.viewModes, parsed.options);
+                emitEvent('done', { id, ok: true });
+            } catch (error) {
+                emitEvent('error', { code: error.code || 'BUILD_FAILED', message: error.message });
+                console.error('✗ Error:', error.message);
+                emitEvent('done', { id, ok: false });
+            }
+        });
+    });
+    input.on('close', () => {
+        queue.then(() => process.exit(0));
+    });
+    emitEvent('ready');
+}
+
+// CLI
+if (require.main === module) {
+    const args = process.argv.slice(2);
+    eventsEnabled = args.includes('--events');
+    const worker = args.includes('--worker');
+    const parsed = worker ? null : parseArgs(args);
+    
+    if (worker) {
+        runWorker();
+    } else if (!parsed) {
         console.error('Usage: node scripts/build.js --input <file.json|file.xlsx> [options]');
+        console.error('       node scripts/build.js --worker');
         console.error('');
         console.error('Options:');
         console.error('  --input, -i     Input file (JSON or XLSX) [required]');
         console.error('  --output, -o    Output HTML file (defaults to output/<inputname>_gantt_chart.html)');
         console.error('  --palette, -p   Color palette preset: reds, purples, alternating');
+        console.error('                  (comma-separated to build several variants)');
         console.error('  --view-mode     Timeline view mode: day (default) or week');
+        console.error('                  (comma-separated, e.g. day,week)');
         console.error('  --png           Export PNG image (default for CLI)');
         console.error('  --no-png        Skip PNG export');
+        console.error('  --events        Also write JSON-lines progress events to stdout');
+        console.error('  --worker        Keep running and take JSON-lines build requests on stdin');
         console.error('');
         console.error('Palette presets:');
         console.error('  reds          Red gradient (RED 1 → RED 5) - warm, attention-grabbing');
@@ -691,31 +832,20 @@ if (require.main === module) {
         console.error('  alternating   Alternating red/purple - maximum visual distinction [default]');
         console.error('  alternating_b Alternating red/purple + red left border accent');
         process.exit(1);
+    } else {
+        buildVariants(parsed.inputPath, parsed.outputPath, parsed.palettes, parsed.viewModes, parsed.options).catch(error => {
+            emitEvent('error', { code: error.code || 'BUILD_FAILED', message: error.message });
+            console.error('✗ Error:', error.message);
+            process.exit(1);
+        });
     }
-    
-    const inputPath = path.resolve(args[inputIndex + 1]);
-    const outputPath = outputIndex !== -1 && args[outputIndex + 1] 
-        ? path.resolve(args[outputIndex + 1])
-        : null;
-    const palette = paletteIndex !== -1 && args[paletteIndex + 1]
-        ? args[paletteIndex + 1]
-        : null;
-    const viewMode = viewModeIndex !== -1 && args[viewModeIndex + 1]
-        ? args[viewModeIndex + 1]
-        : 'day';
-    // Default to true for CLI (backwards compatible), but respect --no-png flag
-    const exportPng = noPngFlag ? false : true;
-    // Default drop shadow to true (backwards compatible - shadows were always on before)
-    const dropShadow = dropShadowFlag !== false; // If flag present, it's true; if not present, default to true
-    
-    build(inputPath, outputPath, { palette, exportPng, dropShadow, viewMode }).catch(error => {
-        console.error('✗ Error:', error.message);
-        process.exit(1);
-    });
 }
 
 module.exports = { 
     build, 
+    buildVariants,
+    variantOutputPath,
+    parseArgs,
     parseExcel, 
     parseJSON, 
     validateConfig,
@@ -0,0 +1,205 @@
+use serde::Deserialize;
+
+/// One line of the JSON-lines stream build.js writes with `--events`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BuildEvent {
+    StageStarted {
+        stage: Stage,
+    },
+    StageFinished {
+        stage: Stage,
+    },
+    Warning {
+        message: String,
+    },
+    /// A file build.js wrote, tagged with the variant it belongs to
+    Artifact {
+        kind: ArtifactKind,
+        path: String,
+        #[serde(default)]
+        palette: Option<String>,
+        #[serde(default, rename = "viewMode")]
+        view_mode: Option<String>,
+    },
+    Error {
+        code: String,
+        message: String,
+    },
+    /// `--worker` mode is accepting requests
+    Ready,
+    /// `--worker` mode finished the request with this ID
+    Done {
+        id: String,
+        ok: bool,
+    },
+    /// Events added to build.js after this host was built
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Parse,
+    Html,
+    Png,
+    #[serde(other)]
+    Other,
+}
+
+impl Stage {
+    /// Name used in progress and timeout messages
+    pub fn label(self) -> &'static str {
+        match self {
+            Stage::Parse => "parse",
+            Stage::Html => "HTML",
+            Stage::Png => "PNG",
+            Stage::Other => "build",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ArtifactKind {
+    Html,
+    Png,
+    Json,
+    #[serde(other)]
+    Other,
+}
+
+/// The event on a stdout line, or `None` for ordinary log output
+pub fn parse_line(line: &str) -> Option<BuildEvent> {
+    let line = line.trim();
+    if !line.starts_with("{\"event\"") {
+        return None;
+    }
+    serde_json::from_str(line).ok()
+}
+
+/// HTML files from the event stream, looked up by the variant they were built for
+#[derive(Debug, Default)]
+pub struct Artifacts {
+    html: Vec<(String, String, String)>,
+}
+
+impl Artifacts {
+    /// A variant seen again, as when a restarted worker retries the build, replaces the first
+    pub fn record(&mut self, event: &BuildEvent) {
+        if let BuildEvent::Artifact { kind: ArtifactKind::Html, path, palette, view_mode } = event {
+            let palette = palette.clone().unwrap_or_default().to_lowercase();
+            let view_mode = view_mode.clone().unwrap_or_else(|| "day".to_string()).to_lowercase();
+            self.html.retain(|(p, v, _)| *p != palette || *v != view_mode);
+            self.html.push((palette, view_mode, path.clone()));
+        }
+    }
+
+    pub fn html_count(&self) -> usize {
+        self.html.len()
+    }
+
+    /// Every HTML file written so far
+    pub fn html_paths(&self) -> Vec<&str> {
+        self.html.iter().map(|(_, _, path)| path.as_str()).collect()
+    }
+
+    /// HTML written for a palette and view mode; an empty palette is the project's own
+    pub fn html(&self, palette: &str, view_mode: &str) -> Option<&str> {
+        self.html
+            .iter()
+            .find(|(p, v, _)| *p == palette.trim().to_lowercase() && *v == view_mode.trim().to_lowercase())
+            .map(|(_, _, path)| path.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn artifact(kind: &str, palette: &str, view_mode: &str, path: &str) -> BuildEvent {
+        parse_line(&format!(
+            r#"{{"event":"artifact","kind":"{}","path":"{}","palette":{},"viewMode":{}}}"#,
+            kind, path, palette, view_mode
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_stage_warning_artifact_and_error_events() {
+        assert!(matches!(
+            parse_line(r#"{"event":"stage_started","stage":"parse"}"#),
+            Some(BuildEvent::StageStarted { stage: Stage::Parse })
+        ));
+        assert!(matches!(
+            parse_line("  {\"event\":\"stage_finished\",\"stage\":\"png\"}\r"),
+            Some(BuildEvent::StageFinished { stage: Stage::Png })
+        ));
+        assert!(matches!(
+            parse_line(r#"{"event":"stage_started","stage":"deploy"}"#),
+            Some(BuildEvent::StageStarted { stage: Stage::Other })
+        ));
+        assert!(matches!(
+            parse_line(r#"{"event":"warning","message":"Skipped PNG export"}"#),
+            Some(BuildEvent::Warning { message }) if message == "Skipped PNG export"
+        ));
+        assert!(matches!(
+            parse_line(r#"{"event":"error","code":"INPUT_NOT_FOUND","message":"Input file not found: a.json"}"#),
+            Some(BuildEvent::Error { code, .. }) if code == "INPUT_NOT_FOUND"
+        ));
+
+        let BuildEvent::Artifact { kind, path, palette, view_mode } = artifact("html", r#""reds""#, r#""week""#, "/out/a.html")
+        else {
+            panic!("not an artifact");
+        };
+        assert_eq!(kind, ArtifactKind::Html);
+        assert_eq!(path, "/out/a.html");
+        assert_eq!(palette.as_deref(), Some("reds"));
+        assert_eq!(view_mode.as_deref(), Some("week"));
+        assert!(matches!(
+            parse_line(r#"{"event":"artifact","kind":"svg","path":"/out/a.svg"}"#),
+            Some(BuildEvent::Artifact { kind: ArtifactKind::Other, palette: None, view_mode: None, .. })
+        ));
+    }
+
+    #[test]
+    fn unknown_events_are_kept_and_log_lines_skipped() {
+        assert!(matches!(parse_line(r#"{"event":"progress","percent":50}"#), Some(BuildEvent::Unknown)));
+        assert!(parse_line("✓ Generated HTML at /out/a.html").is_none());
+        assert!(parse_line("").is_none());
+        assert!(parse_line(r#"{"title":"not an event"}"#).is_none());
+        assert!(parse_line(r#"{"event":"error","message":"no code"}"#).is_none());
+        assert!(parse_line(r#"{"event":"warning""#).is_none());
+    }
+
+    #[test]
+    fn parses_worker_events() {
+        assert!(matches!(parse_line(r#"{"event":"ready"}"#), Some(BuildEvent::Ready)));
+        assert!(matches!(
+            parse_line(r#"{"event":"done","id":"7","ok":false}"#),
+            Some(BuildEvent::Done { id, ok: false }) if id == "7"
+        ));
+        assert!(parse_line(r#"{"event":"done","ok":true}"#).is_none());
+    }
+
+    #[test]
+    fn artifacts_are_found_by_variant() {
+        let mut artifacts = Artifacts::default();
+        artifacts.record(&artifact("html", "null", "null", "/out/plain.html"));
+        artifacts.record(&artifact("html", r#""Reds""#, r#""week""#, "/out/reds_week.html"));
+        artifacts.record(&artifact("png", r#""reds""#, r#""week""#, "/out/reds_week.png"));
+        artifacts.record(&BuildEvent::Warning { message: "ignored".to_string() });
+
+        assert_eq!(artifacts.html_count(), 2);
+        assert_eq!(artifacts.html("", "day"), Some("/out/plain.html"));
+        assert_eq!(artifacts.html(" REDS ", "Week"), Some("/out/reds_week.html"));
+        assert_eq!(artifacts.html("reds", "day"), None);
+
+        // A retried variant replaces the earlier file
+        artifacts.record(&artifact("html", r#""reds""#, r#""week""#, "/out/retry.html"));
+        assert_eq!(artifacts.html_count(), 2);
+        assert_eq!(artifacts.html("reds", "week"), Some("/out/retry.html"));
+        assert_eq!(artifacts.html_paths(), ["/out/plain.html", "/out/retry.html"]);
+    }
+}
@@ -0,0 +1,1086 @@
+use crate::palette::{self, Accents};
+use crate::project::{parse_date, Project};
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Chart width: 1920px viewport minus body (10px) and container (15px) padding
+pub const CHART_WIDTH: f64 = 1870.0;
+const BORDER: f64 = 1.0;
+const TASK_COLUMN_WIDTH: f64 = 380.0;
+const TASK_COLUMN_PADDING_X: f64 = 16.0;
+const TASK_COLUMN_PADDING_Y: f64 = 12.0;
+const HEADER_HEIGHT: f64 = 72.0;
+const ROW_MIN_HEIGHT: f64 = 58.0;
+const BAR_HEIGHT: f64 = 48.0;
+const ACCENT_BORDER_WIDTH: f64 = 4.0;
+const CORNER_RADIUS: f64 = 8.0;
+const BAR_RADIUS: f64 = 4.0;
+const MILESTONE_GAP: f64 = 15.0;
+const MILESTONE_ROW_HEIGHT: f64 = 65.0;
+/// Milestone connector color when the milestone has no linked task
+const UNLINKED_MILESTONE_COLOR: &str = "#0056b3";
+
+const TASK_NAME_SIZE: f64 = 21.0;
+const TASK_NAME_LINE: f64 = TASK_NAME_SIZE * 1.2;
+const SUBTASK_SIZE: f64 = 16.0;
+const SUBTASK_LINE: f64 = SUBTASK_SIZE * 1.3;
+const SUBTASK_INDENT: f64 = 16.0;
+const BAR_DATE_SIZE: f64 = 17.0;
+const BAR_DATE_LINE: f64 = BAR_DATE_SIZE * 1.1;
+const BAR_HOURS_SIZE: f64 = 15.0;
+const BAR_HOURS_LINE: f64 = BAR_HOURS_SIZE * 1.1;
+const MILESTONE_SIZE: f64 = 16.0;
+const MILESTONE_LINE: f64 = MILESTONE_SIZE * 1.3;
+
+/// RGBA color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: f64,
+}
+
+impl Color {
+    pub const fn rgba(r: u8, g: u8, b: u8, a: f64) -> Self {
+        Color { r, g, b, a }
+    }
+
+    /// Parse `#RGB`, `#RRGGBB`, `#RRGGBBAA`, `rgb()` and `rgba()` colors
+    pub fn parse(value: &str) -> Option<Color> {
+        let value = value.trim();
+        if let Some(hex) = value.strip_prefix('#') {
+            let digits: Vec<u8> = hex
+                .chars()
+                .map(|c| c.to_digit(16).map(|d| d as u8))
+                .collect::<Option<_>>()?;
+            return match digits.len() {
+                3 => Some(Color::rgba(digits[0] * 17, digits[1] * 17, digits[2] * 17, 1.0)),
+                6 | 8 => {
+                    let byte = |i: usize| digits[i] * 16 + digits[i + 1];
+                    let alpha = if digits.len() == 8 { byte(6) as f64 / 255.0 } else { 1.0 };
+                    Some(Color::rgba(byte(0), byte(2), byte(4), alpha))
+                }
+                _ => None,
+            };
+        }
+        let inner = value
+            .strip_prefix("rgba(")
+            .or_else(|| value.strip_prefix("rgb("))?
+            .strip_suffix(')')?;
+        let parts: Vec<f64> = inner
+            .split(',')
+            .map(|p| p.trim().parse().ok())
+            .collect::<Option<_>>()?;
+        match parts.as_slice() {
+            [r, g, b] => Some(Color::rgba(*r as u8, *g as u8, *b as u8, 1.0)),
+            [r, g, b, a] => Some(Color::rgba(*r as u8, *g as u8, *b as u8, a.clamp(0.0, 1.0))),
+            _ => None,
+        }
+    }
+
+    fn hex(value: &str) -> Color {
+        Color::parse(value).unwrap_or(Color::rgba(0, 0, 0, 1.0))
+    }
+
+    fn with_alpha(self, a: f64) -> Color {
+        Color { a, ..self }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineStyle {
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Stroke {
+    pub color: Color,
+    pub width: f64,
+    pub style: LineStyle,
+}
+
+impl Stroke {
+    fn solid(color: Color, width: f64) -> Self {
+        Stroke {
+            color,
+            width,
+            style: LineStyle::Solid,
+        }
+    }
+
+    fn dashed(color: Color) -> Self {
+        Stroke {
+            color,
+            width: 1.0,
+            style: LineStyle::Dashed,
+        }
+    }
+}
+
+/// Corner radii, clockwise from the top left
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Corners {
+    pub top_left: f64,
+    pub top_right: f64,
+    pub bottom_right: f64,
+    pub bottom_left: f64,
+}
+
+impl Corners {
+    pub fn all(radius: f64) -> Self {
+        Corners {
+            top_left: radius,
+            top_right: radius,
+            bottom_right: radius,
+            bottom_left: radius,
+        }
+    }
+
+    fn left(radius: f64) -> Self {
+        Corners {
+            top_left: radius,
+            bottom_left: radius,
+            ..Corners::default()
+        }
+    }
+
+    fn right(radius: f64) -> Self {
+        Corners {
+            top_right: radius,
+            bottom_right: radius,
+            ..Corners::default()
+        }
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.top_left == 0.0 && self.top_right == 0.0 && self.bottom_right == 0.0 && self.bottom_left == 0.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Anchor {
+    Start,
+    Middle,
+}
+
+/// Drawing primitive in chart coordinates (CSS pixels, y down)
+#[derive(Debug, Clone)]
+pub enum Shape {
+    /// Rectangle; a stroke is drawn inside the bounds like a CSS border
+    Rect {
+        x: f64,
+        y: f64,
+        w: f64,
+        h: f64,
+        corners: Corners,
+        fill: Option<Color>,
+        stroke: Option<Stroke>,
+        shadow: bool,
+    },
+    Line {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+        stroke: Stroke,
+    },
+    /// Single line of text; `y` is the baseline
+    Text {
+        x: f64,
+        y: f64,
+        text: String,
+        size: f64,
+        weight: u16,
+        color: Color,
+        anchor: Anchor,
+    },
+    /// Diagonal stripes running top-left to bottom-right, like the template's
+    /// 45deg repeating gradients: `stripe` px of color every `period` px
+    Hatch {
+        x: f64,
+        y: f64,
+        w: f64,
+        h: f64,
+        color: Color,
+        stripe: f64,
+        period: f64,
+    },
+    /// Shapes clipped to a rounded rectangle
+    Clip {
+        x: f64,
+        y: f64,
+        w: f64,
+        h: f64,
+        radius: f64,
+        shapes: Vec<Shape>,
+    },
+}
+
+impl Shape {
+    /// Rough extent for culling; text is measured with the layout's metrics
+    pub fn bounds(&self) -> (f64, f64, f64, f64) {
+        match self {
+            Shape::Rect { x, y, w, h, .. }
+            | Shape::Hatch { x, y, w, h, .. }
+            | Shape::Clip { x, y, w, h, .. } => (*x, *y, x + w, y + h),
+            Shape::Line { x1, y1, x2, y2, stroke } => {
+                let pad = stroke.width / 2.0;
+                (x1.min(*x2) - pad, y1.min(*y2) - pad, x1.max(*x2) + pad, y1.max(*y2) + pad)
+            }
+            Shape::Text {
+                x,
+                y,
+                text,
+                size,
+                weight,
+                anchor,
+                ..
+            } => {
+                let w = text_width(text, *size, *weight);
+                let left = match anchor {
+                    Anchor::Start => *x,
+                    Anchor::Middle => x - w / 2.0,
+                };
+                (left, y - size, left + w, y + size * 0.25)
+            }
+        }
+    }
+}
+
+/// Laid out chart
+#[derive(Debug, Clone)]
+pub struct Chart {
+    pub width: f64,
+    pub height: f64,
+    /// x where the timeline starts, right of the task name column
+    pub timeline_x: f64,
+    /// y where the header ends and the task rows start
+    pub header_bottom: f64,
+    /// x positions right of `timeline_x` where a page may end: week starts, then the right edge
+    pub column_breaks: Vec<f64>,
+    /// y positions below `header_bottom` where a page may end: row boundaries, then the bottom edge
+    pub row_breaks: Vec<f64>,
+    pub drop_shadow: bool,
+    pub shapes: Vec<Shape>,
+}
+
+/// Rendering options, the native counterparts of the build.js flags
+#[derive(Debug, Clone, Default)]
+pub struct ChartOptions {
+    pub week_view: bool,
+    pub drop_shadow: bool,
+    pub accents: Accents,
+}
+
+impl ChartOptions {
+    /// Options for a `--palette` / `--view-mode` pair, applying the preset to the project
+    pub fn from_generate(project: &mut Project, palette_name: &str, view_mode: &str, drop_shadow: bool) -> Self {
+        ChartOptions {
+            week_view: view_mode == "week",
+            drop_shadow,
+            accents: palette::apply_preset(project, palette_name),
+        }
+    }
+}
+
+struct Month {
+    name: String,
+    days: usize,
+}
+
+struct Week {
+    number: u32,
+    days: usize,
+}
+
+/// Visible day range of the chart, padded like `buildTimelineData`
+struct Timeline {
+    start: NaiveDate,
+    days: Vec<NaiveDate>,
+    months: Vec<Month>,
+    weeks: Vec<Week>,
+}
+
+impl Timeline {
+    fn new(timeline_start: NaiveDate, timeline_end: NaiveDate, week_view: bool) -> Self {
+        let (start, end) = if week_view {
+            let from_monday = timeline_start.weekday().num_days_from_monday() as i64;
+            let to_sunday = 6 - timeline_end.weekday().num_days_from_monday() as i64;
+            (
+                timeline_start - Duration::days(from_monday),
+                timeline_end + Duration::days(to_sunday),
+            )
+        } else {
+            (timeline_start - Duration::days(1), timeline_end + Duration::days(1))
+        };
+        let days: Vec<NaiveDate> = start.iter_days().take_while(|d| *d <= end).collect();
+
+        let mut months: Vec<Month> = Vec::new();
+        let mut weeks: Vec<Week> = Vec::new();
+        let mut current_month = None;
+        for (i, day) in days.iter().enumerate() {
+            let key = (day.year(), day.month());
+            if current_month != Some(key) {
+                months.push(Month {
+                    name: format!("{} {}", MONTH_NAMES[day.month0() as usize], day.year()),
+                    days: 0,
+                });
+                current_month = Some(key);
+            }
+            if let Some(month) = months.last_mut() {
+                month.days += 1;
+            }
+            if i == 0 || day.weekday() == Weekday::Mon {
+                weeks.push(Week {
+                    number: day.iso_week().week(),
+                    days: 0,
+                });
+            }
+            if let Some(week) = weeks.last_mut() {
+                week.days += 1;
+            }
+        }
+
+        // Short months at the edges don't have room for their label
+        let min_days = if week_view { 14 } else { 7 };
+        for month in months.iter_mut().filter(|m| m.days < min_days) {
+            month.name.clear();
+        }
+
+        Timeline {
+            start,
+            days,
+            months,
+            weeks,
+        }
+    }
+
+    fn total_days(&self) -> usize {
+        self.days.len()
+    }
+
+    /// 0-based day column of a date, clamped to the visible range
+    fn column(&self, date: NaiveDate) -> f64 {
+        ((date - self.start).num_days() as f64).clamp(0.0, self.total_days() as f64)
+    }
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const DAY_LETTERS: [&str; 7] = ["M", "T", "W", "T", "F", "S", "S"];
+
+/// Everything `layout` needs to know about a task row
+struct TaskRow {
+    y: f64,
+    height: f64,
+    name_lines: Vec<String>,
+    subtask_lines: Vec<Vec<String>>,
+    content_height: f64,
+}
+
+/// Lay out the chart the way templates/gantt_template.html renders it at the
+/// 1920px viewport build.js screenshots, as shapes in CSS pixels. The native
+/// outputs (SVG, PDF, PNG) all draw from this one layout.
+pub fn layout(project: &Project, options: &ChartOptions) -> Result<Chart, String> {
+    let timeline_start = parse_date(&project.timeline_start, "timelineStart")?;
+    let timeline_end = parse_date(&project.timeline_end, "timelineEnd")?;
+    if timeline_end < timeline_start {
+        return Err("timelineStart must be before timelineEnd".to_string());
+    }
+    let timeline = Timeline::new(timeline_start, timeline_end, options.week_view);
+
+    let black = Color::hex(palette::BLACK);
+    let cream = Color::hex(palette::CREAM);
+    let purple_1 = Color::hex(palette::PURPLE_1);
+    let white = Color::rgba(255, 255, 255, 1.0);
+    let accent_color = options.accents.color.as_deref().map(Color::hex).unwrap_or(black);
+    let accent_border = options.accents.border.as_deref().map(Color::hex);
+    let accent_width = if accent_border.is_some() { ACCENT_BORDER_WIDTH } else { 0.0 };
+
+    let left = BORDER;
+    let timeline_x = left + TASK_COLUMN_WIDTH;
+    let timeline_w = CHART_WIDTH - BORDER - timeline_x;
+    let day_w = timeline_w / timeline.total_days() as f64;
+    let day_x = |col: f64| timeline_x + col * day_w;
+    let text_width_limit = TASK_COLUMN_WIDTH - 2.0 * TASK_COLUMN_PADDING_X - accent_width - BORDER;
+
+    // Header height grows if the title wraps
+    let title = if project.title.is_empty() { "PROJECT TIMELINE" } else { &project.title };
+    let title_lines = wrap_text(title, 16.0, 400, text_width_limit);
+    let header_h = HEADER_HEIGHT.max(2.0 * TASK_COLUMN_PADDING_Y + title_lines.len() as f64 * 16.0 * 1.2);
+    let header_bottom = left + header_h + BORDER;
+
+    // Task rows
+    let mut rows = Vec::new();
+    let mut y = header_bottom;
+    for (idx, task) in project.tasks.iter().enumerate() {
+        let name_lines = wrap_text(&task.name, TASK_NAME_SIZE, 600, text_width_limit);
+        let subtask_lines: Vec<Vec<String>> = task
+            .subtasks
+            .iter()
+            .map(|s| wrap_text(s, SUBTASK_SIZE, 400, text_width_limit - SUBTASK_INDENT))
+            .collect();
+        let mut content_height = 2.0 * TASK_COLUMN_PADDING_Y + name_lines.len() as f64 * TASK_NAME_LINE;
+        if !subtask_lines.is_empty() {
+            let lines: usize = subtask_lines.iter().map(Vec::len).sum();
+            content_height += 6.0 + lines as f64 * SUBTASK_LINE + 3.0 * (subtask_lines.len() - 1) as f64;
+        }
+        let height = ROW_MIN_HEIGHT.max(content_height);
+        rows.push(TaskRow {
+            y,
+            height,
+            name_lines,
+            subtask_lines,
+            content_height,
+        });
+        y += height;
+        if idx + 1 < project.tasks.len() {
+            y += BORDER;
+        }
+    }
+    let chart_height = y + BORDER;
+
+    let mut body = Vec::new();
+
+    // Row backgrounds, text, month shading and grid lines
+    for (idx, row) in rows.iter().enumerate() {
+        body.push(fill_rect(left, row.y, CHART_WIDTH - 2.0 * BORDER, row.height, cream));
+
+        let mut month_col = 0.0;
+        for (m_idx, month) in timeline.months.iter().enumerate() {
+            if m_idx % 2 == 0 {
+                body.push(fill_rect(
+                    day_x(month_col),
+                    row.y,
+                    month.days as f64 * day_w,
+                    row.height,
+                    Color::rgba(0, 0, 0, 0.03),
+                ));
+            }
+            month_col += month.days as f64;
+        }
+
+        if options.week_view {
+            let mut col = 0.0;
+            for week in &timeline.weeks[..timeline.weeks.len() - 1] {
+                col += week.days as f64;
+                body.push(vline(day_x(col) - 0.5, row.y, row.height, Stroke::dashed(Color::rgba(0, 0, 0, 0.15))));
+            }
+        } else {
+            let mut month_end = 0;
+            let month_ends: Vec<usize> = timeline
+                .months
+                .iter()
+                .map(|m| {
+                    month_end += m.days;
+                    month_end
+                })
+                .collect();
+            for (i, day) in timeline.days.iter().enumerate() {
+                let right = day_x(i as f64 + 1.0) - 0.5;
+                if day.weekday() == Weekday::Mon {
+                    body.push(vline(day_x(i as f64) + 0.5, row.y, row.height, Stroke::dashed(Color::rgba(0, 0, 0, 0.2))));
+                }
+                if i + 1 == timeline.total_days() {
+                    continue;
+                }
+                if month_ends[..month_ends.len() - 1].contains(&(i + 1)) {
+                    body.push(vline(right, row.y, row.height, Stroke::solid(purple_1, 1.0)));
+                } else {
+                    body.push(vline(right, row.y, row.height, Stroke::dashed(Color::rgba(0, 0, 0, 0.08))));
+                }
+            }
+        }
+
+        // Task name column
+        body.push(vline(timeline_x - 0.5, row.y, row.height, Stroke::solid(purple_1, 1.0)));
+        if let Some(border) = accent_border {
+            body.push(fill_rect(left, row.y, accent_width, row.height, border));
+        }
+        let text_x = left + accent_width + TASK_COLUMN_PADDING_X;
+        let mut text_y = row.y + (row.height - row.content_height) / 2.0 + TASK_COLUMN_PADDING_Y;
+        for line in &row.name_lines {
+            body.push(text(text_x, baseline(text_y, TASK_NAME_LINE, TASK_NAME_SIZE), line, TASK_NAME_SIZE, 600, accent_color, Anchor::Start));
+            text_y += TASK_NAME_LINE;
+        }
+        if !row.subtask_lines.is_empty() {
+            text_y += 6.0;
+        }
+        for lines in &row.subtask_lines {
+            body.push(text(text_x + 4.0, baseline(text_y, SUBTASK_LINE, SUBTASK_SIZE), "•", SUBTASK_SIZE, 400, Color::hex(palette::PURPLE_2), Anchor::Start));
+            for line in lines {
+                body.push(text(text_x + SUBTASK_INDENT, baseline(text_y, SUBTASK_LINE, SUBTASK_SIZE), line, SUBTASK_SIZE, 400, Color::hex(palette::PURPLE_3), Anchor::Start));
+                text_y += SUBTASK_LINE;
+            }
+            text_y += 3.0;
+        }
+
+        if idx + 1 < rows.len() {
+            body.push(hline(left, row.y + row.height + 0.5, CHART_WIDTH - 2.0 * BORDER, Stroke::solid(purple_1, 1.0)));
+        }
+    }
+
+    // Pause overlays sit above the grid but below the bars
+    let pauses = project
+        .pause_periods
+        .iter()
+        .map(|p| Ok((parse_date(&p.start, "pause start")?, parse_date(&p.end, "pause end")?)))
+        .collect::<Result<Vec<_>, String>>()?;
+    for (start, end) in &pauses {
+        let x = day_x(timeline.column(*start));
+        let w = day_x(timeline.column(*end + Duration::days(1))) - x;
+        body.push(Shape::Hatch {
+            x,
+            y: header_bottom,
+            w,
+            h: chart_height - header_bottom,
+            color: Color::rgba(160, 148, 158, 0.25),
+            stripe: 8.0,
+            period: 16.0,
+        });
+    }
+
+    // Task bars; remember where each task's first bar ends for milestone connectors
+    let mut bar_bottoms = Vec::new();
+    for (task, row) in project.tasks.iter().zip(&rows) {
+        let start = parse_date(&task.start, &format!("start of task \"{}\"", task.name))?;
+        let end = parse_date(&task.end, &format!("end of task \"{}\"", task.name))?;
+        let color = task.color.as_deref().and_then(Color::parse).unwrap_or(Color::hex(palette::PURPLE_4));
+        let bar_y = row.y + (row.height - BAR_HEIGHT) / 2.0;
+        bar_bottoms.push(bar_y + BAR_HEIGHT);
+
+        let hours = (task.hours > 0.0).then(|| format!("{} hrs", task.hours));
+        // Bars fill their day columns less a 1px margin on each side
+        let bar = |from: f64, to: f64| Bar {
+            x: day_x(from) + 1.0,
+            y: bar_y,
+            w: (to - from) * day_w - 2.0,
+        };
+
+        // A bar is split around the first pause it overlaps
+        let pause = pauses.iter().find(|(p_start, p_end)| start < *p_end && end > *p_start);
+        if let Some((p_start, p_end)) = pause {
+            let s1 = (timeline.column(start), timeline.column(*p_start));
+            let s2 = (timeline.column(*p_end + Duration::days(1)), timeline.column(end + Duration::days(1)));
+            if s1.1 > s1.0 {
+                push_bar(&mut body, bar(s1.0, s1.1), Corners::left(BAR_RADIUS), color, options.drop_shadow);
+                push_bar_label(&mut body, bar(s1.0, s1.1), &task.start, &task.end, hours.as_deref(), cream);
+            }
+            let connector_x = day_x(timeline.column(*p_start));
+            body.push(Shape::Hatch {
+                x: connector_x,
+                y: bar_y,
+                w: day_x(s2.0) - connector_x,
+                h: BAR_HEIGHT,
+                color: color.with_alpha(0.6),
+                stripe: 1.0,
+                period: 4.0,
+            });
+            if s2.1 > s2.0 {
+                push_bar(&mut body, bar(s2.0, s2.1), Corners::right(BAR_RADIUS), color, options.drop_shadow);
+            }
+        } else {
+            let (from, to) = (timeline.column(start), timeline.column(end + Duration::days(1)));
+            push_bar(&mut body, bar(from, to), Corners::all(BAR_RADIUS), color, options.drop_shadow);
+            push_bar_label(&mut body, bar(from, to), &task.start, &task.end, hours.as_deref(), cream);
+        }
+    }
+
+    // Header row, drawn last so it covers the pause overlays
+    body.push(fill_rect(left, left, CHART_WIDTH - 2.0 * BORDER, header_h + BORDER, black));
+    if let Some(border) = accent_border {
+        body.push(fill_rect(left, left, accent_width, header_h, border));
+    }
+    body.push(vline(timeline_x - 0.5, left, header_h, Stroke::solid(white.with_alpha(0.2), 1.0)));
+    let mut title_y = left + (header_h - title_lines.len() as f64 * 16.0 * 1.2) / 2.0;
+    for line in &title_lines {
+        body.push(text(left + accent_width + TASK_COLUMN_PADDING_X, baseline(title_y, 19.2, 16.0), line, 16.0, 400, cream, Anchor::Start));
+        title_y += 19.2;
+    }
+    body.extend(header_timeline(&timeline, options.week_view, timeline_x, left, day_w, cream, white));
+    body.push(hline(left, left + header_h + 0.5, CHART_WIDTH - 2.0 * BORDER, Stroke::solid(white.with_alpha(0.2), 1.0)));
+
+    let mut shapes = vec![Shape::Clip {
+        x: 0.0,
+        y: 0.0,
+        w: CHART_WIDTH,
+        h: chart_height,
+        radius: CORNER_RADIUS,
+        shapes: body,
+    }];
+    shapes.push(Shape::Rect {
+        x: 0.0,
+        y: 0.0,
+        w: CHART_WIDTH,
+        h: chart_height,
+        corners: Corners::all(CORNER_RADIUS),
+        fill: None,
+        stroke: Some(Stroke::solid(purple_1, BORDER)),
+        shadow: false,
+    });
+
+    // Milestones hang below the chart with dotted connectors up to their task bar
+    let mut height = chart_height;
+    if project.show_milestones && !project.milestones.is_empty() {
+        let row_top = chart_height + MILESTONE_GAP;
+        let timeline_end_x = timeline_x + timeline_w;
+        for milestone in &project.milestones {
+            let date = parse_date(&milestone.date, &format!("date of milestone \"{}\"", milestone.name))?;
+            let center = day_x(timeline.column(date) + 0.5);
+            let linked = milestone.task_index.filter(|i| *i < project.tasks.len());
+            let color = linked
+                .and_then(|i| project.tasks[i].color.as_deref())
+                .and_then(Color::parse)
+                .unwrap_or(Color::hex(UNLINKED_MILESTONE_COLOR));
+
+            let lines: Vec<&str> = milestone.name.split('\n').collect();
+            let label_w = lines
+                .iter()
+                .map(|l| text_width(l, MILESTONE_SIZE, 500))
+                .fold(0.0, f64::max)
+                + 24.0;
+            let label_h = lines.len() as f64 * MILESTONE_LINE + 16.0;
+            let mut label_x = center - label_w / 2.0;
+            if label_x < timeline_x + day_w {
+                label_x = timeline_x + day_w;
+            } else if label_x + label_w > timeline_end_x - day_w {
+                label_x = timeline_end_x - day_w - label_w;
+            }
+            let label_y = row_top + MILESTONE_ROW_HEIGHT - label_h;
+
+            if let Some(bar_bottom) = linked.map(|i| bar_bottoms[i]) {
+                if label_y > bar_bottom - 4.0 {
+                    shapes.push(Shape::Line {
+                        x1: center,
+                        y1: bar_bottom - 4.0,
+                        x2: center,
+                        y2: label_y,
+                        stroke: Stroke {
+                            color,
+                            width: 2.0,
+                            style: LineStyle::Dotted,
+                        },
+                    });
+                }
+            }
+            shapes.push(Shape::Rect {
+                x: label_x,
+                y: label_y,
+                w: label_w,
+                h: label_h,
+                corners: Corners::all(BAR_RADIUS),
+                fill: Some(cream),
+                stroke: Some(Stroke::solid(color, 2.0)),
+                shadow: options.drop_shadow,
+            });
+            for (i, line) in lines.iter().enumerate() {
+                let line_top = label_y + 8.0 + i as f64 * MILESTONE_LINE;
+                shapes.push(text(label_x + label_w / 2.0, baseline(line_top, MILESTONE_LINE, MILESTONE_SIZE), line, MILESTONE_SIZE, 500, black, Anchor::Middle));
+            }
+        }
+        height = row_top + MILESTONE_ROW_HEIGHT;
+    }
+
+    let mut column_breaks = Vec::new();
+    let mut col = 0.0;
+    for week in &timeline.weeks[..timeline.weeks.len() - 1] {
+        col += week.days as f64;
+        column_breaks.push(day_x(col));
+    }
+    column_breaks.push(CHART_WIDTH);
+    let mut row_breaks: Vec<f64> = rows.iter().skip(1).map(|r| r.y).collect();
+    row_breaks.push(height);
+
+    Ok(Chart {
+        width: CHART_WIDTH,
+        height,
+        timeline_x,
+        header_bottom,
+        column_breaks,
+        row_breaks,
+        drop_shadow: options.drop_shadow,
+        shapes,
+    })
+}
+
+/// Month, day-number and day-letter rows (day view) or month and ISO week rows (week view)
+fn header_timeline(timeline: &Timeline, week_view: bool, x0: f64, y0: f64, day_w: f64, cream: Color, white: Color) -> Vec<Shape> {
+    let mut shapes = Vec::new();
+    let month_h = if week_view { 50.0 } else { 36.0 };
+    let divider = Stroke::solid(white.with_alpha(0.2), 1.0);
+
+    let mut col = 0.0;
+    for (i, month) in timeline.months.iter().enumerate() {
+        let x = x0 + col * day_w;
+        let w = month.days as f64 * day_w;
+        if !month.name.is_empty() {
+            shapes.push(text(x + w / 2.0, baseline(y0, month_h, 17.0), &month.name, 17.0, 600, cream, Anchor::Middle));
+        }
+        if i + 1 < timeline.months.len() {
+            shapes.push(vline(x + w - 0.5, y0, month_h, divider));
+        }
+        col += month.days as f64;
+    }
+    shapes.push(hline(x0, y0 + month_h - 0.5, timeline.total_days() as f64 * day_w, divider));
+
+    let ticks_y = y0 + HEADER_HEIGHT - 22.0;
+    if week_view {
+        let mut col = 0.0;
+        for (i, week) in timeline.weeks.iter().enumerate() {
+            let x = x0 + col * day_w;
+            let w = week.days as f64 * day_w;
+            shapes.push(text(x + w / 2.0, baseline(ticks_y, 22.0, 14.0), &week.number.to_string(), 14.0, 400, white, Anchor::Middle));
+            if i + 1 < timeline.weeks.len() {
+                shapes.push(vline(x + w - 0.5, ticks_y, 22.0, Stroke::solid(white.with_alpha(0.15), 1.0)));
+            }
+            col += week.days as f64;
+        }
+        return shapes;
+    }
+
+    let mut month_end = 0;
+    let month_ends: Vec<usize> = timeline
+        .months
+        .iter()
+        .map(|m| {
+            month_end += m.days;
+            month_end
+        })
+        .collect();
+    let numbers_y = y0 + month_h;
+    for (i, day) in timeline.days.iter().enumerate() {
+        let x = x0 + i as f64 * day_w;
+        if day.weekday() == Weekday::Mon {
+            shapes.push(text(x + day_w / 2.0, baseline(numbers_y, 14.0, 15.0), &day.day().to_string(), 15.0, 500, white, Anchor::Middle));
+        }
+        let weekend = matches!(day.weekday(), Weekday::Sat | Weekday::Sun);
+        let weight = if (i + 1) % 5 == 0 { 700 } else { 400 };
+        let color = if weekend { Color::rgba(0x88, 0x88, 0x88, 1.0) } else { white };
+        let letter = DAY_LETTERS[day.weekday().num_days_from_monday() as usize];
+        shapes.push(text(x + day_w / 2.0, baseline(ticks_y, 22.0, 14.0), letter, 14.0, weight, color, Anchor::Middle));
+        if i + 1 < timeline.total_days() {
+            let alpha = if month_ends.contains(&(i + 1)) { 0.3 } else { 0.15 };
+            shapes.push(vline(x + day_w - 0.5, ticks_y, 22.0, Stroke::solid(white.with_alpha(alpha), 1.0)));
+        }
+    }
+    shapes
+}
+
+/// Horizontal extent of a task bar (or bar segment) in a row
+#[derive(Clone, Copy)]
+struct Bar {
+    x: f64,
+    y: f64,
+    w: f64,
+}
+
+fn push_bar(shapes: &mut Vec<Shape>, bar: Bar, corners: Corners, color: Color, shadow: bool) {
+    if bar.w <= 0.0 {
+        return;
+    }
+    shapes.push(Shape::Rect {
+        x: bar.x,
+        y: bar.y,
+        w: bar.w,
+        h: BAR_HEIGHT,
+        corners,
+        fill: Some(color),
+        stroke: Some(Stroke::solid(Color::rgba(255, 255, 255, 0.2), 1.0)),
+        shadow,
+    });
+}
+
+/// Date range and hours centered in a bar, switching to the numeric date
+/// format when the short one does not fit (`adjustTaskLabels`)
+fn push_bar_label(shapes: &mut Vec<Shape>, bar: Bar, start: &str, end: &str, hours: Option<&str>, color: Color) {
+    let Bar { x, y, w } = bar;
+    if w <= 0.0 {
+        return;
+    }
+    let mut label = format_date_range(start, end, false);
+    if text_width(&label, BAR_DATE_SIZE, 600) > w - 40.0 {
+        label = format_date_range(start, end, true);
+    }
+    let label = truncate_to_width(&label, BAR_DATE_SIZE, 600, w - 24.0);
+
+    let block_h = BAR_DATE_LINE + if hours.is_some() { 1.0 + BAR_HOURS_LINE } else { 0.0 };
+    let top = y + (BAR_HEIGHT - block_h) / 2.0;
+    let center = x + w / 2.0;
+    shapes.push(text(center, baseline(top, BAR_DATE_LINE, BAR_DATE_SIZE), &label, BAR_DATE_SIZE, 600, color, Anchor::Middle));
+    if let Some(hours) = hours {
+        let hours = truncate_to_width(hours, BAR_HOURS_SIZE, 500, w - 24.0);
+        let hours_top = top + BAR_DATE_LINE + 1.0;
+        shapes.push(text(center, baseline(hours_top, BAR_HOURS_LINE, BAR_HOURS_SIZE), &hours, BAR_HOURS_SIZE, 500, color.with_alpha(0.95), Anchor::Middle));
+    }
+}
+
+/// Split `start..last break` into spans no longer than `available`, ending each
+/// span at the furthest break in reach. Breaks that pass `clear` win as long as
+/// the span still fills `min_fill` of `available`. A span with no break in
+/// reach is cut at `available`. Empty when not even a sliver fits.
+pub fn tile(start: f64, breaks: &[f64], clear: impl Fn(f64) -> bool, available: f64, min_fill: f64) -> Vec<(f64, f64)> {
+    let Some(&end) = breaks.last() else {
+        return vec![(start, start)];
+    };
+    if available <= 1.0 {
+        return Vec::new();
+    }
+    let mut spans = Vec::new();
+    let mut from = start;
+    while from < end - 0.01 {
+        let in_reach = |b: &f64| *b > from + 0.01 && *b <= from + available + 0.01;
+        let to = breaks
+            .iter()
+            .rev()
+            .copied()
+            .find(|b| in_reach(b) && *b >= from + available * min_fill && (*b == end || clear(*b)))
+            .or_else(|| breaks.iter().rev().copied().find(in_reach))
+            .unwrap_or((from + available).min(end));
+        spans.push((from, to));
+        from = to;
+    }
+    if spans.is_empty() {
+        spans.push((start, end));
+    }
+    spans
+}
+
+/// Horizontal extent of every text below the header, with room for the padding of label boxes
+pub fn text_spans(shapes: &[Shape], top: f64) -> Vec<(f64, f64)> {
+    let mut spans = Vec::new();
+    for shape in shapes {
+        match shape {
+            Shape::Text { y, .. } if *y > top => {
+                let (x0, _, x1, _) = shape.bounds();
+                spans.push((x0 - 12.0, x1 + 12.0));
+            }
+            Shape::Clip { shapes, .. } => spans.extend(text_spans(shapes, top)),
+            _ => {}
+        }
+    }
+    spans
+}
+
+/// `formatDateRange` from the template: "Jan 6–24", "Jan 28–Feb 3" or "1/6–1/24"
+pub fn format_date_range(start: &str, end: &str, numeric: bool) -> String {
+    let (Ok(s), Ok(e)) = (
+        NaiveDate::parse_from_str(start, "%Y-%m-%d"),
+        NaiveDate::parse_from_str(end, "%Y-%m-%d"),
+    ) else {
+        return format!("{}–{}", start, end);
+    };
+    if numeric {
+        return format!("{}/{}–{}/{}", s.month(), s.day(), e.month(), e.day());
+    }
+    if s.month() == e.month() {
+        format!("{} {}–{}", MONTH_NAMES[s.month0() as usize], s.day(), e.day())
+    } else {
+        format!(
+            "{} {}–{} {}",
+            MONTH_NAMES[s.month0() as usize],
+            s.day(),
+            MONTH_NAMES[e.month0() as usize],
+            e.day()
+        )
+    }
+}
+
+/// Baseline that vertically centers a line of `size` px text in a `line_height` box at `top`
+fn baseline(top: f64, line_height: f64, size: f64) -> f64 {
+    top + line_height / 2.0 + size * 0.35
+}
+
+fn text(x: f64, y: f64, value: &str, size: f64, weight: u16, color: Color, anchor: Anchor) -> Shape {
+    Shape::Text {
+        x,
+        y,
+        text: value.to_string(),
+        size,
+        weight,
+        color,
+        anchor,
+    }
+}
+
+fn fill_rect(x: f64, y: f64, w: f64, h: f64, color: Color) -> Shape {
+    Shape::Rect {
+        x,
+        y,
+        w,
+        h,
+        corners: Corners::default(),
+        fill: Some(color),
+        stroke: None,
+        shadow: false,
+    }
+}
+
+fn vline(x: f64, y: f64, h: f64, stroke: Stroke) -> Shape {
+    Shape::Line {
+        x1: x,
+        y1: y,
+        x2: x,
+        y2: y + h,
+        stroke,
+    }
+}
+
+fn hline(x: f64, y: f64, w: f64, stroke: Stroke) -> Shape {
+    Shape::Line {
+        x1: x,
+        y1: y,
+        x2: x + w,
+        y2: y,
+        stroke,
+    }
+}
+
+/// Approximate advance width of text in the template's sans-serif stack,
+/// using Helvetica metrics (1/1000 em) widened slightly for bold weights
+pub fn text_width(text: &str, size: f64, weight: u16) -> f64 {
+    let units: f64 = text.chars().map(char_width).sum();
+    let bold = if weight >= 600 { 1.06 } else { 1.0 };
+    units / 1000.0 * size * bold
+}
+
+fn char_width(c: char) -> f64 {
+    match c {
+        ' ' | '!' | ',' | '.' | '/' | ':' | ';' | 'I' | '[' | ']' | 'f' | 't' | '\\' => 278.0,
+        '\'' | '|' => 191.0,
+        'i' | 'j' | 'l' => 222.0,
+        '(' | ')' | '-' | '`' | 'r' => 333.0,
+        '"' => 355.0,
+        '*' => 389.0,
+        '•' => 350.0,
+        '^' => 469.0,
+        'J' | 'c' | 'k' | 's' | 'v' | 'x' | 'y' | 'z' => 500.0,
+        'L' | '_' => 556.0,
+        '+' | '<' | '=' | '>' | '~' => 584.0,
+        'F' | 'T' | 'Z' => 611.0,
+        '&' | 'A' | 'B' | 'E' | 'K' | 'P' | 'S' | 'V' | 'X' | 'Y' => 667.0,
+        'C' | 'D' | 'H' | 'N' | 'R' | 'U' | 'w' => 722.0,
+        'G' | 'O' | 'Q' => 778.0,
+        'M' | 'm' => 833.0,
+        '%' => 889.0,
+        'W' => 944.0,
+        '@' => 1015.0,
+        '–' => 556.0,
+        '—' => 1000.0,
+        c if c.is_ascii() => 556.0,
+        // CJK and other wide scripts
+        c if c as u32 >= 0x2E80 => 1000.0,
+        _ => 600.0,
+    }
+}
+
+/// Greedy word wrap to a pixel width; words longer than a line get their own line
+pub fn wrap_text(text: &str, size: f64, weight: u16, max_width: f64) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if !line.is_empty() && text_width(&candidate, size, weight) > max_width {
+                lines.push(std::mem::take(&mut line));
+                line = word.to_string();
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Cut text so it fits a width, the way `overflow: hidden` hides the rest
+fn truncate_to_width(text: &str, size: f64, weight: u16, max_width: f64) -> String {
+    let mut out = String::new();
+    for c in text.chars() {
+        out.push(c);
+        if text_width(&out, size, weight) > max_width {
+            out.pop();
+            break;
+        }
+    }
+    out
+}
+
+/// Coordinates and sizes rounded to 1/100 without trailing zeros, as SVG and PDF write them
+pub fn num(value: f64) -> String {
+    let rounded = (value * 100.0).round() / 100.0;
+    let text = format!("{:.2}", rounded);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::Task;
+
+    fn project(start: &str, end: &str) -> Project {
+        Project {
+            timeline_start: start.to_string(),
+            timeline_end: end.to_string(),
+            tasks: vec![Task {
+                name: "Build".to_string(),
+                start: "2025-01-06".to_string(),
+                end: "2025-01-24".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tile_ends_spans_at_the_furthest_break_in_reach() {
+        let breaks = [100.0, 200.0, 300.0];
+        assert_eq!(tile(0.0, &breaks, |_| true, 250.0, 0.5), [(0.0, 200.0), (200.0, 300.0)]);
+        // A clear break wins over a further one while the span stays full enough
+        assert_eq!(tile(0.0, &breaks, |b| b == 100.0, 250.0, 0.3), [(0.0, 100.0), (100.0, 300.0)]);
+        assert_eq!(tile(0.0, &breaks, |b| b == 100.0, 250.0, 0.5), [(0.0, 200.0), (200.0, 300.0)]);
+    }
+
+    #[test]
+    fn tile_cuts_at_the_available_size_without_a_break_in_reach() {
+        assert_eq!(tile(50.0, &[550.0], |_| true, 200.0, 0.5), [(50.0, 250.0), (250.0, 450.0), (450.0, 550.0)]);
+    }
+
+    #[test]
+    fn tile_needs_room() {
+        assert!(tile(0.0, &[100.0], |_| true, 1.0, 0.5).is_empty());
+        assert!(tile(0.0, &[100.0], |_| true, -20.0, 0.5).is_empty());
+        assert_eq!(tile(10.0, &[], |_| true, 100.0, 0.5), [(10.0, 10.0)]);
+    }
+
+    #[test]
+    fn layout_checks_the_timeline() {
+        let options = ChartOptions::default();
+        assert_eq!(
+            layout(&project("2025-02-01", "2025-01-01"), &options).unwrap_err(),
+            "timelineStart must be before timelineEnd"
+        );
+        assert_eq!(
+            layout(&project("2025-01-01", "01/02/2025"), &options).unwrap_err(),
+            "Invalid timelineEnd date: \"01/02/2025\" (expected YYYY-MM-DD)"
+        );
+
+        let chart = layout(&project("2025-01-01", "2025-01-31"), &options).unwrap();
+        assert_eq!(chart.width, CHART_WIDTH);
+        assert_eq!(chart.column_breaks.last(), Some(&chart.width));
+        assert_eq!(chart.row_breaks.last(), Some(&chart.height));
+        assert!(chart.column_breaks.iter().all(|x| *x > chart.timeline_x));
+        assert!(chart.row_breaks.iter().all(|y| *y > chart.header_bottom));
+    }
+}
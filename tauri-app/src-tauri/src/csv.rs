@@ -0,0 +1,485 @@
+use crate::project::{
+    escape_line_breaks, normalize_date, unescape_line_breaks, Milestone, PausePeriod, Project, Task, MAX_SUBTASKS,
+};
+
+/// Sections in the order they are written; palette comes first so colorIndex resolves
+const SECTIONS: [&str; 5] = ["PALETTE", "PROJECT", "TASKS", "MILESTONES", "PAUSE_PERIODS"];
+
+/// Write a project as a single CSV file with `# SECTION` headers.
+///
+/// Follows "Option B" of docs/CSV_EXCEL_IMPORT_SPEC.md, plus a `PALETTE`
+/// section, a `colorIndex` column on tasks and a `label` column on pause
+/// periods so `read_csv` gets back exactly the same project. Tasks get as
+/// many `subtaskN` columns as the longest subtask list needs (at least ten).
+pub fn to_csv(project: &Project) -> String {
+    let mut out = String::new();
+
+    section(&mut out, "PALETTE", &["color"]);
+    for color in &project.palette {
+        row(&mut out, std::slice::from_ref(color));
+    }
+
+    section(&mut out, "PROJECT", &["title", "timelineStart", "timelineEnd", "showMilestones"]);
+    row(&mut out, &[
+        project.title.clone(),
+        project.timeline_start.clone(),
+        project.timeline_end.clone(),
+        project.show_milestones.to_string(),
+    ]);
+
+    let subtask_columns = project
+        .tasks
+        .iter()
+        .map(|t| t.subtasks.len())
+        .max()
+        .unwrap_or(0)
+        .max(MAX_SUBTASKS);
+    let mut header: Vec<String> = ["name", "start", "end", "color", "hours"].map(String::from).to_vec();
+    header.extend((1..=subtask_columns).map(|i| format!("subtask{}", i)));
+    header.push("colorIndex".to_string());
+    section(&mut out, "TASKS", &header.iter().map(String::as_str).collect::<Vec<_>>());
+    for task in &project.tasks {
+        let mut fields = vec![
+            task.name.clone(),
+            task.start.clone(),
+            task.end.clone(),
+            task.color.clone().unwrap_or_default(),
+            task.hours.to_string(),
+        ];
+        fields.extend((0..subtask_columns).map(|i| task.subtasks.get(i).cloned().unwrap_or_default()));
+        fields.push(task.color_index.map(|i| i.to_string()).unwrap_or_default());
+        row(&mut out, &fields);
+    }
+
+    section(&mut out, "MILESTONES", &["name", "date", "taskIndex"]);
+    for milestone in &project.milestones {
+        row(&mut out, &[
+            escape_line_breaks(&milestone.name),
+            milestone.date.clone(),
+            milestone.task_index.map(|i| i.to_string()).unwrap_or_default(),
+        ]);
+    }
+
+    section(&mut out, "PAUSE_PERIODS", &["start", "end", "label"]);
+    for pause in &project.pause_periods {
+        row(&mut out, &[
+            pause.start.clone(),
+            pause.end.clone(),
+            pause.label.clone().unwrap_or_default(),
+        ]);
+    }
+
+    out
+}
+
+/// Read a single-file sectioned CSV into the normalized project model.
+///
+/// Columns are matched by header name, so hand-made files may reorder or omit
+/// optional ones. Milestones link to a task by zero-based `taskIndex` or by
+/// task name, and blank rows and unknown columns are ignored.
+pub fn read_csv(text: &str) -> Result<Project, String> {
+    let mut project = Project::default();
+    let mut section: Option<(String, Header)> = None;
+    let mut pending: Option<String> = None;
+    let mut milestone_links = Vec::new();
+
+    for (line, record) in parse_records(text.trim_start_matches('\u{feff}'))? {
+        if let Some(name) = section_name(&record) {
+            if !SECTIONS.contains(&name.as_str()) {
+                return Err(format!("Unknown CSV section '{}' on line {}", name, line));
+            }
+            pending = Some(name);
+            section = None;
+            continue;
+        }
+        if record.iter().all(|f| f.value.trim().is_empty()) {
+            continue;
+        }
+        if let Some(name) = pending.take() {
+            section = Some((name, Header::new(&record)));
+            continue;
+        }
+        let Some((name, header)) = &section else {
+            return Err(format!("Line {}: data found before any '# SECTION' header", line));
+        };
+        let get = |column: &str| header.get(&record, column);
+
+        match name.as_str() {
+            "PALETTE" => {
+                let color = get("color");
+                if !color.is_empty() {
+                    project.palette.push(color);
+                }
+            }
+            "PROJECT" => {
+                let title = get("title");
+                if !title.is_empty() {
+                    project.title = title;
+                }
+                project.timeline_start = date(&get("timelineStart"));
+                project.timeline_end = date(&get("timelineEnd"));
+                project.show_milestones =
+                    !matches!(get("showMilestones").to_lowercase().as_str(), "false" | "no" | "0" | "n");
+            }
+            "TASKS" => {
+                header.require("name", "TASKS", line)?;
+                let name = get("name");
+                if name.is_empty() {
+                    continue;
+                }
+                let hours = get("hours");
+                let color = get("color");
+                let color_index = get("colorIndex");
+                project.tasks.push(Task {
+                    name,
+                    start: date(&get("start")),
+                    end: date(&get("end")),
+                    hours: if hours.is_empty() {
+                        0.0
+                    } else {
+                        hours
+                            .parse()
+                            .map_err(|_| format!("Invalid hours '{}' in TASKS on line {}", hours, line))?
+                    },
+                    subtasks: header
+                        .subtasks
+                        .iter()
+                        .filter_map(|&col| record.get(col).map(Field::text))
+                        .filter(|s| !s.is_empty())
+                        .collect(),
+                    color: Some(color).filter(|c| !c.is_empty()),
+                    color_index: if color_index.is_empty() {
+                        None
+                    } else {
+                        Some(color_index.parse().map_err(|_| {
+                            format!("Invalid colorIndex '{}' in TASKS on line {}", color_index, line)
+                        })?)
+                    },
+                });
+            }
+            "MILESTONES" => {
+                header.require("name", "MILESTONES", line)?;
+                let name = get("name");
+                if name.is_empty() {
+                    continue;
+                }
+                milestone_links.push(get("taskIndex"));
+                project.milestones.push(Milestone {
+                    name: unescape_line_breaks(&name),
+                    date: date(&get("date")),
+                    task_index: None,
+                });
+            }
+            _ => {
+                let start = date(&get("start"));
+                let end = date(&get("end"));
+                if start.is_empty() || end.is_empty() {
+                    continue;
+                }
+                project.pause_periods.push(PausePeriod {
+                    start,
+                    end,
+                    label: Some(get("label")).filter(|l| !l.is_empty()),
+                });
+            }
+        }
+    }
+
+    // Links are resolved once every task is known, so sections may come in any order
+    for (milestone, link) in project.milestones.iter_mut().zip(milestone_links) {
+        milestone.task_index = match link.parse::<usize>() {
+            Ok(index) => Some(index).filter(|i| *i < project.tasks.len()),
+            Err(_) => project.tasks.iter().position(|t| !link.is_empty() && t.name == link),
+        };
+    }
+
+    project.resolve_colors();
+    Ok(project)
+}
+
+/// Column positions of a section, looked up by header name
+struct Header {
+    columns: Vec<String>,
+    subtasks: Vec<usize>,
+}
+
+impl Header {
+    fn new(record: &[Field]) -> Self {
+        let columns: Vec<String> = record.iter().map(|f| f.value.trim().to_lowercase()).collect();
+        let mut subtasks: Vec<(usize, usize)> = columns
+            .iter()
+            .enumerate()
+            .filter_map(|(col, name)| Some((name.strip_prefix("subtask")?.parse().ok()?, col)))
+            .collect();
+        subtasks.sort();
+        Header {
+            columns,
+            subtasks: subtasks.into_iter().map(|(_, col)| col).collect(),
+        }
+    }
+
+    fn get(&self, record: &[Field], column: &str) -> String {
+        let column = column.to_lowercase();
+        self.columns
+            .iter()
+            .position(|c| *c == column)
+            .and_then(|col| record.get(col))
+            .map(Field::text)
+            .unwrap_or_default()
+    }
+
+    fn require(&self, column: &str, section: &str, line: usize) -> Result<(), String> {
+        if self.columns.iter().any(|c| c == column) {
+            Ok(())
+        } else {
+            Err(format!("Missing required column '{}' in {} section (line {})", column, section, line))
+        }
+    }
+}
+
+/// One parsed CSV field; quoted fields keep their surrounding whitespace
+struct Field {
+    value: String,
+    quoted: bool,
+}
+
+impl Field {
+    fn text(&self) -> String {
+        if self.quoted {
+            self.value.clone()
+        } else {
+            self.value.trim().to_string()
+        }
+    }
+}
+
+/// Dates are normalized like workbook cells, keeping the raw text when unrecognised
+fn date(value: &str) -> String {
+    normalize_date(value).unwrap_or_else(|| value.to_string())
+}
+
+/// The section a `# NAME` record starts, if it is one
+fn section_name(record: &[Field]) -> Option<String> {
+    let (field, rest) = record.split_first()?;
+    if field.quoted || rest.iter().any(|f| !f.value.trim().is_empty()) {
+        return None;
+    }
+    let name = field.value.trim().strip_prefix('#')?.trim();
+    let name = name.to_uppercase().replace([' ', '-'], "_");
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphabetic() || c == '_') {
+        return None;
+    }
+    Some(if name == "PAUSEPERIODS" { "PAUSE_PERIODS".to_string() } else { name })
+}
+
+/// Split CSV text into records of fields, each tagged with its starting line number
+fn parse_records(text: &str) -> Result<Vec<(usize, Vec<Field>)>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = Field { value: String::new(), quoted: false };
+    let mut chars = text.chars().peekable();
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut in_quotes = false;
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.value.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => {
+                    if c == '\n' {
+                        line += 1;
+                    }
+                    field.value.push(c);
+                }
+            }
+            continue;
+        }
+        match c {
+            '"' if field.value.trim().is_empty() => {
+                field.value.clear();
+                field.quoted = true;
+                in_quotes = true;
+            }
+            ',' => record.push(std::mem::replace(&mut field, Field { value: String::new(), quoted: false })),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                record.push(std::mem::replace(&mut field, Field { value: String::new(), quoted: false }));
+                records.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            _ => field.value.push(c),
+        }
+    }
+    if in_quotes {
+        return Err(format!("Unterminated quoted field starting on line {}", record_line));
+    }
+    if !field.value.is_empty() || field.quoted || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+    Ok(records)
+}
+
+/// Write a blank separator line (except at the start), the section marker and its header row
+fn section(out: &mut String, name: &str, columns: &[&str]) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out.push_str(&format!("# {}\n", name));
+    row(out, &columns.iter().map(|c| c.to_string()).collect::<Vec<_>>());
+}
+
+fn row(out: &mut String, fields: &[String]) {
+    let line: Vec<String> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| escape(f, i == 0, fields.len() == 1))
+        .collect();
+    out.push_str(&line.join(","));
+    out.push('\n');
+}
+
+/// Quote a field when a reader would otherwise split, trim or misread it. A
+/// leading `#` is always quoted, since blank trailing fields would make the row
+/// read back as a `# SECTION` header.
+fn escape(value: &str, first: bool, alone: bool) -> String {
+    let needs_quotes = value.contains([',', '"', '\n', '\r'])
+        || value.trim() != value
+        || (alone && value.is_empty())
+        || (first && value.starts_with('#'));
+    if needs_quotes {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Project {
+        Project {
+            title: "Launch, \"phase\" 2".to_string(),
+            timeline_start: "2025-01-06".to_string(),
+            timeline_end: "2025-06-30".to_string(),
+            show_milestones: false,
+            palette: vec!["#0B5394".to_string(), "#E69138".to_string()],
+            tasks: vec![
+                Task {
+                    name: "# TASKS".to_string(),
+                    start: "2025-01-06".to_string(),
+                    end: "2025-02-14".to_string(),
+                    hours: 120.5,
+                    subtasks: vec!["Scope, budget".to_string(), "Say \"hi\"\nthen leave".to_string()],
+                    color: Some("#0B5394".to_string()),
+                    color_index: Some(0),
+                },
+                Task {
+                    name: "  padded  ".to_string(),
+                    start: "2025-02-17".to_string(),
+                    end: "2025-03-28".to_string(),
+                    hours: 0.0,
+                    subtasks: Vec::new(),
+                    color: Some("#123456".to_string()),
+                    color_index: None,
+                },
+            ],
+            milestones: vec![
+                Milestone {
+                    name: "# TASKS".to_string(),
+                    date: String::new(),
+                    task_index: None,
+                },
+                Milestone {
+                    name: "Sign-off\nC:\\new".to_string(),
+                    date: "2025-02-14".to_string(),
+                    task_index: Some(0),
+                },
+            ],
+            pause_periods: vec![PausePeriod {
+                start: "2025-04-18".to_string(),
+                end: "2025-04-21".to_string(),
+                label: Some("#Easter".to_string()),
+            }],
+        }
+    }
+
+    fn json(project: &Project) -> serde_json::Value {
+        serde_json::to_value(project).unwrap()
+    }
+
+    #[test]
+    fn round_trip_keeps_every_field() {
+        let project = sample();
+        let read = read_csv(&to_csv(&project)).unwrap();
+        assert_eq!(json(&read), json(&project));
+    }
+
+    #[test]
+    fn leading_hash_is_quoted_in_any_row() {
+        let csv = to_csv(&sample());
+        assert!(csv.contains("\n\"# TASKS\",,\n"));
+        assert!(csv.contains("\n\"# TASKS\",2025-01-06,"));
+    }
+
+    #[test]
+    fn milestone_line_breaks_are_written_as_backslash_n() {
+        let csv = to_csv(&sample());
+        assert!(csv.contains("\nSign-off\\nC:\\\\new,2025-02-14,0\n"));
+    }
+
+    #[test]
+    fn reads_the_spec_example() {
+        let csv = r#"# PROJECT
+title,timelineStart,timelineEnd,showMilestones
+PROJECT TIMELINE,2025-12-01,2026-02-14,true
+
+# TASKS
+name,start,end,color,hours,subtask1,subtask2,subtask3,subtask4,subtask5,subtask6,subtask7,subtask8,subtask9,subtask10
+Planning & Preparation,2025-12-02,2025-12-14,#E31E26,20,"Define scope: URLs/Domains to audit; discovery and interviews","Determine test personas/scenarios","Baseline environment setup, any special access required",,,,,
+Initial Site Scans – Tracking System,2025-12-15,2026-01-11,#2CABDB,20,"Scan site and mobile app","Capture all tracking artifacts (cookies, storage, third party scripts, beacons/pixels)","Log and classify by purpose/category",,,,,
+
+# MILESTONES
+name,date,taskIndex
+Project\nKickoff,2025-12-02,0
+Testing\nComplete,2026-01-25,2
+
+# PAUSE_PERIODS
+start,end
+2025-12-23,2026-01-04
+"#;
+        let project = read_csv(csv).unwrap();
+        assert_eq!(project.title, "PROJECT TIMELINE");
+        assert_eq!(project.timeline_start, "2025-12-01");
+        assert_eq!(project.timeline_end, "2026-02-14");
+        assert!(project.show_milestones);
+
+        assert_eq!(project.tasks.len(), 2);
+        assert_eq!(project.tasks[0].name, "Planning & Preparation");
+        assert_eq!(project.tasks[0].color.as_deref(), Some("#E31E26"));
+        assert_eq!(project.tasks[0].hours, 20.0);
+        assert_eq!(project.tasks[0].subtasks.len(), 3);
+        assert_eq!(
+            project.tasks[1].subtasks[1],
+            "Capture all tracking artifacts (cookies, storage, third party scripts, beacons/pixels)"
+        );
+
+        let names: Vec<_> = project.milestones.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["Project\nKickoff", "Testing\nComplete"]);
+        assert_eq!(project.milestones[0].task_index, Some(0));
+        assert_eq!(project.milestones[1].date, "2026-01-25");
+        // Only two tasks, so index 2 links to nothing
+        assert_eq!(project.milestones[1].task_index, None);
+
+        assert_eq!(project.pause_periods.len(), 1);
+        assert_eq!(project.pause_periods[0].start, "2025-12-23");
+        assert_eq!(project.pause_periods[0].end, "2026-01-04");
+    }
+}
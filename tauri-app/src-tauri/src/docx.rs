@@ -0,0 +1,326 @@
+use crate::palette;
+use crate::project::Project;
+use crate::report::{format_hours, long_date_str};
+use crate::xml::{escape_xml, relationships, Package, REL, XML_HEADER};
+use std::fmt::Write as _;
+
+/// A4 portrait with one-inch margins, in twentieths of a point
+const PAGE_WIDTH: u32 = 11906;
+const PAGE_HEIGHT: u32 = 16838;
+const PAGE_MARGIN: u32 = 1440;
+const TEXT_WIDTH: u32 = PAGE_WIDTH - 2 * PAGE_MARGIN;
+const TEXT_HEIGHT: u32 = PAGE_HEIGHT - 2 * PAGE_MARGIN;
+const EMU_PER_TWIP: u64 = 635;
+const TYPEFACE: &str = "Arial";
+/// Table grid lines, the same light mauve as the HTML report
+const GRID_COLOR: &str = "E5E0E6";
+
+const NS: &str = r#"xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:pic="http://schemas.openxmlformats.org/drawingml/2006/picture""#;
+
+/// The chart as a PNG and its size in pixels
+pub struct ChartImage<'a> {
+    pub png: &'a [u8],
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Write a Word document for statements of work: the title, the chart at
+/// page width (or page height, if taller), then a task table (dates, hours and
+/// subtasks as bullets) and a milestone table. Header rows are filled with the
+/// project palette and each task name carries its bar color as a left border,
+/// like the Excel template.
+pub fn to_docx(project: &Project, chart: &ChartImage) -> Result<Vec<u8>, String> {
+    if chart.width == 0 || chart.height == 0 {
+        return Err("Chart image is empty".to_string());
+    }
+    let mut body = String::new();
+    paragraph(&mut body, Some("Title"), &run(project.title.trim(), ""));
+
+    let total_hours: f64 = project.tasks.iter().map(|t| t.hours).sum();
+    let mut summary = Vec::new();
+    if !project.timeline_start.is_empty() && !project.timeline_end.is_empty() {
+        summary.push(format!(
+            "{} – {}",
+            long_date_str(&project.timeline_start),
+            long_date_str(&project.timeline_end)
+        ));
+    }
+    summary.push(format!("{} tasks", project.tasks.len()));
+    summary.push(format!("{} hours", format_hours(total_hours)));
+    paragraph(&mut body, Some("Subtitle"), &run(&summary.join(" · "), ""));
+
+    // Scaled to the text width, or for long projects down to the text height so
+    // Word doesn't crop it, keeping the chart's aspect ratio
+    let (max_cx, max_cy) = (TEXT_WIDTH as u64 * EMU_PER_TWIP, TEXT_HEIGHT as u64 * EMU_PER_TWIP);
+    let (mut cx, mut cy) = (max_cx, max_cx * chart.height as u64 / chart.width as u64);
+    if cy > max_cy {
+        cx = max_cx * max_cy / cy;
+        cy = max_cy;
+    }
+    paragraph(&mut body, None, &format!(
+        concat!(
+            r#"<w:r><w:drawing><wp:inline distT="0" distB="0" distL="0" distR="0"><wp:extent cx="{cx}" cy="{cy}"/>"#,
+            r#"<wp:docPr id="1" name="Gantt chart" descr="Gantt chart"/><wp:cNvGraphicFramePr><a:graphicFrameLocks noChangeAspect="1"/></wp:cNvGraphicFramePr>"#,
+            r#"<a:graphic><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/picture"><pic:pic>"#,
+            r#"<pic:nvPicPr><pic:cNvPr id="0" name="chart.png"/><pic:cNvPicPr/></pic:nvPicPr>"#,
+            r#"<pic:blipFill><a:blip r:embed="rIdChart"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill>"#,
+            r#"<pic:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom></pic:spPr>"#,
+            r#"</pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r>"#,
+        ),
+        cx = cx,
+        cy = cy
+    ));
+
+    let header_fill = |idx: usize| {
+        project
+            .palette
+            .get(idx % project.palette.len().max(1))
+            .map(String::as_str)
+            .unwrap_or(palette::PURPLE_4)
+    };
+
+    paragraph(&mut body, Some("Heading1"), &run("Tasks", ""));
+    if project.tasks.is_empty() {
+        paragraph(&mut body, None, &run("No tasks.", "<w:i/>"));
+    } else {
+        let columns = [("#", 450), ("Task", 2300), ("Start", 1250), ("End", 1250), ("Hours", 700)];
+        let subtask_width = TEXT_WIDTH - columns.iter().map(|(_, w)| w).sum::<u32>();
+        let mut table = Table::new(&[&columns[..], &[("Subtasks", subtask_width)]].concat(), header_fill(0));
+        for (idx, task) in project.tasks.iter().enumerate() {
+            let mut name = Cell::text(&task.name);
+            name.border = task.color.as_deref().map(hex);
+            table.row(vec![
+                Cell::number(&(idx + 1).to_string()),
+                name,
+                Cell::text(&long_date_str(&task.start)),
+                Cell::text(&long_date_str(&task.end)),
+                Cell::number(&format_hours(task.hours)),
+                Cell::bullets(&task.subtasks),
+            ]);
+        }
+        body.push_str(&table.finish());
+    }
+
+    paragraph(&mut body, Some("Heading1"), &run("Milestones", ""));
+    if project.milestones.is_empty() {
+        paragraph(&mut body, None, &run("No milestones.", "<w:i/>"));
+    } else {
+        let mut table = Table::new(&[("Milestone", 3800), ("Date", 1500), ("Task", TEXT_WIDTH - 5300)], header_fill(1));
+        for milestone in &project.milestones {
+            let task = milestone.task_index.and_then(|i| project.tasks.get(i));
+            let mut task_cell = Cell::text(task.map(|t| t.name.as_str()).unwrap_or_default());
+            task_cell.border = task.and_then(|t| t.color.as_deref()).map(hex);
+            table.row(vec![
+                Cell::text(&milestone.name),
+                Cell::text(&long_date_str(&milestone.date)),
+                task_cell,
+            ]);
+        }
+        body.push_str(&table.finish());
+    }
+
+    let document = format!(
+        concat!(
+            r#"{header}<w:document {ns}><w:body>{body}"#,
+            r#"<w:sectPr><w:pgSz w:w="{pw}" w:h="{ph}"/><w:pgMar w:top="{m}" w:right="{m}" w:bottom="{m}" w:left="{m}" w:header="708" w:footer="708" w:gutter="0"/></w:sectPr>"#,
+            r#"</w:body></w:document>"#,
+        ),
+        header = XML_HEADER,
+        ns = NS,
+        body = body,
+        pw = PAGE_WIDTH,
+        ph = PAGE_HEIGHT,
+        m = PAGE_MARGIN
+    );
+    write_package(&project.title, document, chart.png)
+}
+
+/// A table with a repeating, palette-filled header row
+struct Table {
+    widths: Vec<u32>,
+    xml: String,
+}
+
+struct Cell {
+    /// One paragraph per entry
+    lines: Vec<String>,
+    bullets: bool,
+    right: bool,
+    /// Hex color of a thick left border
+    border: Option<String>,
+}
+
+impl Cell {
+    fn text(value: &str) -> Self {
+        Cell { lines: vec![value.to_string()], bullets: false, right: false, border: None }
+    }
+
+    fn number(value: &str) -> Self {
+        Cell { right: true, ..Cell::text(value) }
+    }
+
+    fn bullets(items: &[String]) -> Self {
+        Cell { lines: items.to_vec(), bullets: true, right: false, border: None }
+    }
+}
+
+impl Table {
+    fn new(columns: &[(&str, u32)], fill: &str) -> Self {
+        let fill = hex(fill);
+        let text_color = if is_light(&fill) { hex(palette::BLACK) } else { "FFFFFF".to_string() };
+        let mut xml = format!(
+            r#"<w:tbl><w:tblPr><w:tblStyle w:val="TableGrid"/><w:tblW w:w="{}" w:type="dxa"/><w:tblLayout w:type="fixed"/><w:tblLook w:val="04A0" w:firstRow="1" w:lastRow="0" w:firstColumn="0" w:lastColumn="0" w:noHBand="0" w:noVBand="1"/></w:tblPr><w:tblGrid>"#,
+            TEXT_WIDTH
+        );
+        for (_, width) in columns {
+            let _ = write!(xml, r#"<w:gridCol w:w="{}"/>"#, width);
+        }
+        xml.push_str(r#"</w:tblGrid><w:tr><w:trPr><w:tblHeader/></w:trPr>"#);
+        for (name, width) in columns {
+            let _ = write!(
+                xml,
+                r#"<w:tc><w:tcPr><w:tcW w:w="{}" w:type="dxa"/><w:shd w:val="clear" w:color="auto" w:fill="{}"/></w:tcPr>"#,
+                width, fill
+            );
+            paragraph(&mut xml, None, &run(name, &format!(r#"<w:b/><w:color w:val="{}"/>"#, text_color)));
+            xml.push_str("</w:tc>");
+        }
+        xml.push_str("</w:tr>");
+        Table { widths: columns.iter().map(|(_, w)| *w).collect(), xml }
+    }
+
+    fn row(&mut self, cells: Vec<Cell>) {
+        self.xml.push_str(r#"<w:tr><w:trPr><w:cantSplit/></w:trPr>"#);
+        for (cell, width) in cells.into_iter().zip(&self.widths) {
+            let _ = write!(self.xml, r#"<w:tc><w:tcPr><w:tcW w:w="{}" w:type="dxa"/>"#, width);
+            if let Some(color) = &cell.border {
+                let _ = write!(
+                    self.xml,
+                    r#"<w:tcBorders><w:left w:val="single" w:sz="24" w:space="0" w:color="{}"/></w:tcBorders>"#,
+                    color
+                );
+            }
+            self.xml.push_str("</w:tcPr>");
+            let lines: Vec<&String> = cell.lines.iter().filter(|l| !cell.bullets || !l.trim().is_empty()).collect();
+            if lines.is_empty() {
+                // Every cell needs at least one paragraph
+                self.xml.push_str("<w:p/>");
+            }
+            for line in lines {
+                let style = if cell.bullets { Some("ListBullet") } else { None };
+                let align = if cell.right { r#"<w:jc w:val="right"/>"# } else { "" };
+                paragraph_with(&mut self.xml, style, align, &run(line, ""));
+            }
+            self.xml.push_str("</w:tc>");
+        }
+        self.xml.push_str("</w:tr>");
+    }
+
+    fn finish(mut self) -> String {
+        self.xml.push_str("</w:tbl>");
+        // Word merges adjacent tables, so keep a paragraph after each one
+        self.xml.push_str("<w:p/>");
+        self.xml
+    }
+}
+
+fn paragraph(out: &mut String, style: Option<&str>, runs: &str) {
+    paragraph_with(out, style, "", runs);
+}
+
+fn paragraph_with(out: &mut String, style: Option<&str>, properties: &str, runs: &str) {
+    let style = style.map(|s| format!(r#"<w:pStyle w:val="{}"/>"#, s)).unwrap_or_default();
+    if style.is_empty() && properties.is_empty() {
+        let _ = write!(out, "<w:p>{}</w:p>", runs);
+    } else {
+        let _ = write!(out, "<w:p><w:pPr>{}{}</w:pPr>{}</w:p>", style, properties, runs);
+    }
+}
+
+/// A run of text, with line breaks where the value has newlines
+fn run(text: &str, properties: &str) -> String {
+    let properties = if properties.is_empty() { String::new() } else { format!("<w:rPr>{}</w:rPr>", properties) };
+    let lines: Vec<String> = text
+        .lines()
+        .map(|line| format!(r#"<w:t xml:space="preserve">{}</w:t>"#, escape_xml(line)))
+        .collect();
+    format!("<w:r>{}{}</w:r>", properties, lines.join("<w:br/>"))
+}
+
+/// `#RRGGBB` as Word's `RRGGBB`
+fn hex(color: &str) -> String {
+    color.trim().trim_start_matches('#').to_uppercase()
+}
+
+/// Whether dark text reads better than white on this fill
+fn is_light(hex: &str) -> bool {
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2).unwrap_or("00"), 16).unwrap_or(0) as f64;
+    0.299 * channel(0) + 0.587 * channel(2) + 0.114 * channel(4) > 160.0
+}
+
+/// Zip the document with its styles, bullet numbering and the chart image
+fn write_package(title: &str, document: String, png: &[u8]) -> Result<Vec<u8>, String> {
+    const WORD: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml";
+    let mut package = Package::new("DOCX", title, "word/document.xml", &format!("{WORD}.document.main+xml"), "");
+    package.default_type("png", "image/png");
+    package.add("word/document.xml", None, document);
+    package.add(
+        "word/_rels/document.xml.rels",
+        None,
+        relationships(&format!(
+            concat!(
+                r#"<Relationship Id="rId1" Type="{rel}/styles" Target="styles.xml"/>"#,
+                r#"<Relationship Id="rId2" Type="{rel}/numbering" Target="numbering.xml"/>"#,
+                r#"<Relationship Id="rIdChart" Type="{rel}/image" Target="media/chart.png"/>"#,
+            ),
+            rel = REL
+        )),
+    );
+    package.add("word/styles.xml", Some(&format!("{WORD}.styles+xml")), styles());
+    package.add("word/numbering.xml", Some(&format!("{WORD}.numbering+xml")), numbering());
+    package.add("word/media/chart.png", None, png);
+    package.finish()
+}
+
+/// Arial throughout, headings in the brand's dark purple and a light grid for tables
+fn styles() -> String {
+    let dark = hex(palette::PURPLE_4);
+    let black = hex(palette::BLACK);
+    let muted = hex(palette::PURPLE_3);
+    format!(
+        concat!(
+            r#"{header}<w:styles xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#,
+            r#"<w:docDefaults><w:rPrDefault><w:rPr><w:rFonts w:ascii="{font}" w:hAnsi="{font}" w:eastAsia="{font}" w:cs="{font}"/><w:color w:val="{black}"/><w:sz w:val="20"/><w:szCs w:val="20"/><w:lang w:val="en-US"/></w:rPr></w:rPrDefault>"#,
+            r#"<w:pPrDefault><w:pPr><w:spacing w:after="80" w:line="259" w:lineRule="auto"/></w:pPr></w:pPrDefault></w:docDefaults>"#,
+            r#"<w:style w:type="paragraph" w:default="1" w:styleId="Normal"><w:name w:val="Normal"/><w:qFormat/></w:style>"#,
+            r#"<w:style w:type="paragraph" w:styleId="Title"><w:name w:val="Title"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:spacing w:after="60"/></w:pPr><w:rPr><w:b/><w:sz w:val="40"/><w:szCs w:val="40"/></w:rPr></w:style>"#,
+            r#"<w:style w:type="paragraph" w:styleId="Subtitle"><w:name w:val="Subtitle"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:spacing w:after="240"/></w:pPr><w:rPr><w:color w:val="{muted}"/><w:sz w:val="22"/><w:szCs w:val="22"/></w:rPr></w:style>"#,
+            r#"<w:style w:type="paragraph" w:styleId="Heading1"><w:name w:val="heading 1"/><w:basedOn w:val="Normal"/><w:next w:val="Normal"/><w:qFormat/><w:pPr><w:keepNext/><w:spacing w:before="240" w:after="120"/><w:outlineLvl w:val="0"/></w:pPr><w:rPr><w:b/><w:color w:val="{dark}"/><w:sz w:val="28"/><w:szCs w:val="28"/></w:rPr></w:style>"#,
+            r#"<w:style w:type="paragraph" w:styleId="ListBullet"><w:name w:val="List Bullet"/><w:basedOn w:val="Normal"/><w:pPr><w:numPr><w:ilvl w:val="0"/><w:numId w:val="1"/></w:numPr><w:spacing w:after="0"/></w:pPr></w:style>"#,
+            r#"<w:style w:type="table" w:default="1" w:styleId="TableNormal"><w:name w:val="Normal Table"/><w:tblPr><w:tblInd w:w="0" w:type="dxa"/><w:tblCellMar><w:top w:w="0" w:type="dxa"/><w:left w:w="108" w:type="dxa"/><w:bottom w:w="0" w:type="dxa"/><w:right w:w="108" w:type="dxa"/></w:tblCellMar></w:tblPr></w:style>"#,
+            r#"<w:style w:type="table" w:styleId="TableGrid"><w:name w:val="Table Grid"/><w:basedOn w:val="TableNormal"/><w:pPr><w:spacing w:before="40" w:after="40"/></w:pPr>"#,
+            r#"<w:tblPr><w:tblBorders><w:top w:val="single" w:sz="4" w:space="0" w:color="{grid}"/><w:left w:val="single" w:sz="4" w:space="0" w:color="{grid}"/><w:bottom w:val="single" w:sz="4" w:space="0" w:color="{grid}"/><w:right w:val="single" w:sz="4" w:space="0" w:color="{grid}"/><w:insideH w:val="single" w:sz="4" w:space="0" w:color="{grid}"/><w:insideV w:val="single" w:sz="4" w:space="0" w:color="{grid}"/></w:tblBorders>"#,
+            r#"<w:tblCellMar><w:top w:w="40" w:type="dxa"/><w:left w:w="108" w:type="dxa"/><w:bottom w:w="40" w:type="dxa"/><w:right w:w="108" w:type="dxa"/></w:tblCellMar></w:tblPr></w:style>"#,
+            r#"</w:styles>"#,
+        ),
+        header = XML_HEADER,
+        font = TYPEFACE,
+        black = black,
+        muted = muted,
+        dark = dark,
+        grid = GRID_COLOR
+    )
+}
+
+/// A single bullet list definition used by the `ListBullet` style
+fn numbering() -> String {
+    format!(
+        concat!(
+            r#"{}<w:numbering xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">"#,
+            r#"<w:abstractNum w:abstractNumId="0"><w:multiLevelType w:val="singleLevel"/><w:lvl w:ilvl="0"><w:start w:val="1"/><w:numFmt w:val="bullet"/><w:lvlText w:val="•"/><w:lvlJc w:val="left"/><w:pPr><w:ind w:left="284" w:hanging="170"/></w:pPr></w:lvl></w:abstractNum>"#,
+            r#"<w:num w:numId="1"><w:abstractNumId w:val="0"/></w:num>"#,
+            r#"</w:numbering>"#,
+        ),
+        XML_HEADER
+    )
+}
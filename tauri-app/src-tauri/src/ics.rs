@@ -0,0 +1,284 @@
+use crate::project::{parse_date, Project};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use std::collections::HashMap;
+
+/// Maximum line length in octets before folding (RFC 5545 §3.1)
+const MAX_LINE_OCTETS: usize = 75;
+
+/// Write a project as an RFC 5545 iCalendar file.
+///
+/// Tasks become all-day events spanning their range with the subtasks in the
+/// description, milestones become single all-day events and pause periods are
+/// opaque (busy) blocks. UIDs are hashed from the project title plus the
+/// item's kind and name, so re-importing an updated export replaces the
+/// existing events instead of duplicating them. `stamp` is the DTSTAMP (UTC).
+pub fn to_ics(project: &Project, stamp: NaiveDateTime) -> Result<String, String> {
+    let stamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+    let mut uids = UidGenerator::new(&project.title);
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//SEI//GanttGen//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "METHOD:PUBLISH".to_string(),
+        format!("X-WR-CALNAME:{}", escape_text(&project.title)),
+    ];
+
+    for task in &project.tasks {
+        let start = parse_date(&task.start, &format!("start of task \"{}\"", task.name))?;
+        let end = parse_date(&task.end, &format!("end of task \"{}\"", task.name))?;
+        let mut description: Vec<String> = task.subtasks.iter().map(|s| format!("• {}", s)).collect();
+        if task.hours > 0.0 {
+            description.push(format!("{} hrs", task.hours));
+        }
+        lines.extend(event(Event {
+            uid: uids.next("task", &task.name),
+            stamp: &stamp,
+            start,
+            end,
+            summary: &task.name,
+            description: Some(description.join("\n")).filter(|d| !d.is_empty()),
+            busy: false,
+            category: "Task",
+        }));
+    }
+
+    for milestone in &project.milestones {
+        let date = parse_date(&milestone.date, &format!("date of milestone \"{}\"", milestone.name))?;
+        let name = milestone.name.replace('\n', " ");
+        let description = milestone
+            .task_index
+            .and_then(|i| project.tasks.get(i))
+            .map(|t| format!("Milestone for {}", t.name));
+        lines.extend(event(Event {
+            uid: uids.next("milestone", &name),
+            stamp: &stamp,
+            start: date,
+            end: date,
+            summary: &name,
+            description,
+            busy: false,
+            category: "Milestone",
+        }));
+    }
+
+    for pause in &project.pause_periods {
+        let start = parse_date(&pause.start, "pause period start")?;
+        let end = parse_date(&pause.end, "pause period end")?;
+        let label = pause.label.as_deref().unwrap_or("Pause");
+        lines.extend(event(Event {
+            uid: uids.next("pause", label),
+            stamp: &stamp,
+            start,
+            end,
+            summary: label,
+            description: None,
+            busy: true,
+            category: "Pause",
+        }));
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+    for line in lines {
+        out.push_str(&fold_line(&line));
+        out.push_str("\r\n");
+    }
+    Ok(out)
+}
+
+struct Event<'a> {
+    uid: String,
+    stamp: &'a str,
+    start: NaiveDate,
+    end: NaiveDate,
+    summary: &'a str,
+    description: Option<String>,
+    busy: bool,
+    category: &'a str,
+}
+
+fn event(event: Event) -> Vec<String> {
+    // All-day DTEND is exclusive, while chart end dates are inclusive
+    let end = event.end + Duration::days(1);
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event.uid),
+        format!("DTSTAMP:{}", event.stamp),
+        format!("DTSTART;VALUE=DATE:{}", event.start.format("%Y%m%d")),
+        format!("DTEND;VALUE=DATE:{}", end.format("%Y%m%d")),
+        format!("SUMMARY:{}", escape_text(event.summary)),
+    ];
+    if let Some(description) = event.description {
+        lines.push(format!("DESCRIPTION:{}", escape_text(&description)));
+    }
+    lines.push(format!("CATEGORIES:{}", event.category));
+    lines.push(format!("TRANSP:{}", if event.busy { "OPAQUE" } else { "TRANSPARENT" }));
+    lines.push("END:VEVENT".to_string());
+    lines
+}
+
+/// Hands out UIDs that depend only on the project title, the item kind and its
+/// name, numbering repeated names so each keeps its own UID
+struct UidGenerator {
+    project: String,
+    seen: HashMap<(String, String), usize>,
+}
+
+impl UidGenerator {
+    fn new(project: &str) -> Self {
+        UidGenerator {
+            project: project.trim().to_string(),
+            seen: HashMap::new(),
+        }
+    }
+
+    fn next(&mut self, kind: &str, name: &str) -> String {
+        let name = name.trim().to_string();
+        let count = self.seen.entry((kind.to_string(), name.clone())).or_insert(0);
+        *count += 1;
+        let key = format!("{}\u{1f}{}\u{1f}{}\u{1f}{}", self.project, kind, name, count);
+        format!("{}-{:016x}@ganttgen", kind, fnv1a(key.as_bytes()))
+    }
+}
+
+/// 64-bit FNV-1a, stable across platforms and releases unlike `DefaultHasher`
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Escape a TEXT value (RFC 5545 §3.3.11)
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Fold a content line into 75-octet pieces without splitting UTF-8 characters
+fn fold_line(line: &str) -> String {
+    let mut out = String::new();
+    let mut octets = 0;
+    for ch in line.chars() {
+        let len = ch.len_utf8();
+        if octets + len > MAX_LINE_OCTETS {
+            out.push_str("\r\n ");
+            // The leading space counts towards the continuation line
+            octets = 1;
+        }
+        out.push(ch);
+        octets += len;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Milestone, PausePeriod, Task};
+
+    fn sample() -> Project {
+        let task = |name: &str, start: &str, end: &str| Task {
+            name: name.to_string(),
+            start: start.to_string(),
+            end: end.to_string(),
+            ..Default::default()
+        };
+        Project {
+            title: "Launch".to_string(),
+            tasks: vec![
+                Task {
+                    hours: 12.5,
+                    subtasks: vec!["Draft".to_string()],
+                    ..task("Review", "2025-01-06", "2025-01-10")
+                },
+                task("Build", "2025-01-13", "2025-01-31"),
+                task(" Review ", "2025-02-03", "2025-02-07"),
+            ],
+            milestones: vec![Milestone {
+                name: "Go\nlive".to_string(),
+                date: "2025-02-07".to_string(),
+                task_index: Some(2),
+            }],
+            pause_periods: vec![PausePeriod {
+                start: "2025-01-01".to_string(),
+                end: "2025-01-01".to_string(),
+                label: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn stamp(day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 1, day).unwrap().and_hms_opt(9, 30, 0).unwrap()
+    }
+
+    fn values<'a>(ics: &'a str, property: &str) -> Vec<&'a str> {
+        ics.lines().filter_map(|line| line.strip_prefix(property)).collect()
+    }
+
+    #[test]
+    fn uids_are_stable_across_exports() {
+        let first = to_ics(&sample(), stamp(1)).unwrap();
+        let second = to_ics(&sample(), stamp(2)).unwrap();
+        assert_eq!(values(&first, "DTSTAMP:"), ["20250101T093000Z"; 5]);
+        assert_eq!(values(&first, "UID:"), values(&second, "UID:"));
+
+        // Repeated names are numbered, so the second "Review" keeps its own UID
+        let uids = values(&first, "UID:");
+        assert!(uids.iter().all(|uid| uid.ends_with("@ganttgen")));
+        assert_eq!(uids.iter().collect::<std::collections::HashSet<_>>().len(), 5);
+
+        let mut a = UidGenerator::new("Launch");
+        let mut b = UidGenerator::new(" Launch ");
+        assert_eq!(a.next("task", "Review"), b.next("task", "Review "));
+        assert_eq!(a.next("task", "Review"), b.next("task", "Review"));
+        assert_ne!(a.next("task", "Build"), b.next("milestone", "Build"));
+        assert_ne!(
+            UidGenerator::new("Other").next("task", "Review"),
+            UidGenerator::new("Launch").next("task", "Review")
+        );
+    }
+
+    #[test]
+    fn all_day_end_is_exclusive() {
+        let ics = to_ics(&sample(), stamp(1)).unwrap();
+        assert_eq!(
+            values(&ics, "DTSTART;VALUE=DATE:"),
+            ["20250106", "20250113", "20250203", "20250207", "20250101"]
+        );
+        assert_eq!(
+            values(&ics, "DTEND;VALUE=DATE:"),
+            ["20250111", "20250201", "20250208", "20250208", "20250102"]
+        );
+        assert_eq!(values(&ics, "SUMMARY:")[3], "Go live");
+        assert_eq!(values(&ics, "DESCRIPTION:"), ["• Draft\\n12.5 hrs", "Milestone for  Review "]);
+        assert_eq!(values(&ics, "TRANSP:").last(), Some(&"OPAQUE"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn text_escapes_backslashes_separators_and_line_breaks() {
+        assert_eq!(escape_text("C:\\temp; a, b\r\nc\nd"), r"C:\\temp\; a\, b\nc\nd");
+    }
+
+    #[test]
+    fn folding_never_splits_multibyte_characters() {
+        let line = format!("SUMMARY:{}", "é".repeat(40));
+        let folded = fold_line(&line);
+        let pieces: Vec<&str> = folded.split("\r\n").collect();
+        // 8 ASCII octets plus 33 two-octet characters; a 34th would make 76
+        assert_eq!(pieces.iter().map(|p| p.len()).collect::<Vec<_>>(), [74, 15]);
+        assert!(pieces[1].starts_with(' '));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+
+        let ascii = "X".repeat(150);
+        assert!(fold_line(&ascii).split("\r\n").all(|p| p.len() <= MAX_LINE_OCTETS));
+        assert_eq!(fold_line("SHORT"), "SHORT");
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+
+/// Message returned by a generation that was stopped with `cancel_generation`
+pub const CANCELLED: &str = "Generation cancelled";
+
+/// Generation runs that can still be cancelled, by job ID
+#[derive(Default)]
+pub struct Jobs {
+    running: Mutex<HashMap<String, Arc<Job>>>,
+    next_id: AtomicU64,
+}
+
+impl Jobs {
+    /// Register a run under the caller's ID, or a fresh one when none is given
+    pub fn start(&self, id: Option<String>) -> Result<Arc<Job>, String> {
+        let id = id
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .unwrap_or_else(|| format!("job-{}", self.next_id.fetch_add(1, Ordering::Relaxed) + 1));
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        if running.contains_key(&id) {
+            return Err(format!("A generation with job ID \"{}\" is already running", id));
+        }
+        let job = Arc::new(Job {
+            id: id.clone(),
+            cancelled: AtomicBool::new(false),
+            notify: Notify::new(),
+            pid: Mutex::new(None),
+        });
+        running.insert(id, job.clone());
+        Ok(job)
+    }
+
+    pub fn finish(&self, id: &str) {
+        self.running.lock().unwrap_or_else(|e| e.into_inner()).remove(id);
+    }
+
+    /// Cancel a running job; false when no job has that ID (it may have just finished)
+    pub fn cancel(&self, id: &str) -> bool {
+        let job = self.running.lock().unwrap_or_else(|e| e.into_inner()).get(id).cloned();
+        match job {
+            Some(job) => {
+                job.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+pub struct Job {
+    pub id: String,
+    cancelled: AtomicBool,
+    notify: Notify,
+    /// Node process currently running for this job
+    pid: Mutex<Option<u32>>,
+}
+
+impl Job {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// `Err(CANCELLED)` once the job has been cancelled, for `?` at checkpoints
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Resolves when the job is cancelled, for racing against long steps
+    pub async fn cancelled(&self) {
+        loop {
+            // Created before the check so a cancel in between still wakes it
+            let notified = self.notify.notified();
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Remember the process to kill on cancel; started in its own process group
+    pub fn set_process(&self, pid: Option<u32>) {
+        *self.pid.lock().unwrap_or_else(|e| e.into_inner()) = pid;
+        if self.is_cancelled() {
+            if let Some(pid) = pid {
+                kill_process_tree(pid);
+            }
+        }
+    }
+
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        if let Some(pid) = *self.pid.lock().unwrap_or_else(|e| e.into_inner()) {
+            kill_process_tree(pid);
+        }
+        self.notify.notify_waiters();
+    }
+}
+
+/// Kill a process and everything it started, such as Chromium launched by
+/// Playwright. Returns without waiting, so it is safe to call on the async runtime.
+pub fn kill_process_tree(pid: u32) {
+    #[cfg(not(target_os = "windows"))]
+    {
+        // The process leads its own group, so a negative PID reaches the whole tree
+        if let Ok(pid) = libc::pid_t::try_from(pid) {
+            // SAFETY: kill() only sends a signal; a stale group ID at worst fails with ESRCH
+            unsafe {
+                libc::kill(-pid, libc::SIGKILL);
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        // taskkill reports back only once the tree is gone; don't wait for it
+        let _ = std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .spawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn generates_ids_and_rejects_running_duplicates() {
+        let jobs = Jobs::default();
+        assert_eq!(jobs.start(None).unwrap().id, "job-1");
+        assert_eq!(jobs.start(Some("  ".to_string())).unwrap().id, "job-2");
+        assert_eq!(jobs.start(Some(" export ".to_string())).unwrap().id, "export");
+
+        let err = jobs.start(Some("export".to_string())).err().unwrap();
+        assert_eq!(err, "A generation with job ID \"export\" is already running");
+
+        jobs.finish("export");
+        assert!(jobs.start(Some("export".to_string())).is_ok());
+        assert_eq!(jobs.start(None).unwrap().id, "job-3");
+    }
+
+    #[test]
+    fn cancel_reaches_checkpoints() {
+        let jobs = Jobs::default();
+        let job = jobs.start(None).unwrap();
+        assert_eq!(job.check(), Ok(()));
+        assert!(!jobs.cancel("job-9"));
+
+        assert!(jobs.cancel("job-1"));
+        assert!(job.is_cancelled());
+        assert_eq!(job.check(), Err(CANCELLED.to_string()));
+
+        // Finished jobs can no longer be cancelled
+        jobs.finish("job-1");
+        assert!(!jobs.cancel("job-1"));
+    }
+
+    #[tokio::test]
+    async fn cancelled_wakes_a_waiting_step() {
+        let jobs = Arc::new(Jobs::default());
+        let job = jobs.start(Some("slow".to_string())).unwrap();
+        let canceller = jobs.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            canceller.cancel("slow");
+        });
+
+        tokio::time::timeout(Duration::from_secs(5), job.cancelled())
+            .await
+            .expect("cancelled() did not resolve");
+        // Already cancelled jobs resolve straight away
+        tokio::time::timeout(Duration::from_millis(10), job.cancelled()).await.unwrap();
+    }
+}
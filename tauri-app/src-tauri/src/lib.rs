@@ -10,6 +10,32 @@ use tauri::{Emitter, Manager};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 use tempfile::tempdir;
+use build_events::{ArtifactKind, BuildEvent, Stage};
+use transcript::{Stream, TranscriptLine};
+
+mod build_events;
+mod chart;
+mod csv;
+mod docx;
+mod ics;
+mod jobs;
+mod mermaid;
+mod mspdi;
+mod offline;
+mod palette;
+mod paste;
+mod pdf;
+mod pptx;
+mod project;
+mod raster;
+mod renderer;
+mod report;
+mod svg;
+mod transcript;
+mod watch;
+mod watchdog;
+mod workbook;
+mod xml;
 
 // Include generated build info
 include!(concat!(env!("OUT_DIR"), "/build_info.rs"));
@@ -34,6 +60,102 @@ pub struct GenerateOptions {
     pub export_png: bool,
     pub png_drop_shadow: bool,
     pub view_mode: String,
+    /// PNG resolution; 96 is one pixel per CSS pixel
+    #[serde(default = "default_png_dpi")]
+    pub png_dpi: f64,
+    /// Device pixels per CSS pixel, like a browser's `deviceScaleFactor`; overrides `png_dpi`
+    #[serde(default)]
+    pub png_scale: Option<f64>,
+    /// Exact PNG width in pixels, padding included; overrides `png_dpi` and `png_scale`
+    #[serde(default)]
+    pub png_width: Option<u32>,
+    /// `transparent`, `white`, `cream` or a CSS color; transparent when unset
+    #[serde(default)]
+    pub png_background: Option<String>,
+    /// Margin around the cropped chart in CSS pixels, filled with the background
+    #[serde(default)]
+    pub png_padding: f64,
+    /// Also write a JPEG next to the HTML output, flattened onto white unless a background is set
+    #[serde(default)]
+    pub export_jpeg: bool,
+    /// 1-100
+    #[serde(default = "default_image_quality")]
+    pub jpeg_quality: u8,
+    /// Also write a lossy WebP next to the HTML output
+    #[serde(default)]
+    pub export_webp: bool,
+    /// 1-100
+    #[serde(default = "default_image_quality")]
+    pub webp_quality: u8,
+    /// Also write a small `<name>_thumb.png` preview
+    #[serde(default)]
+    pub export_thumbnail: bool,
+    #[serde(default = "default_thumbnail_width")]
+    pub thumbnail_width: u32,
+    /// Inline every stylesheet, script, image and font the HTML references
+    #[serde(default)]
+    pub self_contained_html: bool,
+    /// Also render the chart natively to SVG next to the HTML output
+    #[serde(default)]
+    pub export_svg: bool,
+    /// Also render a print-ready PDF next to the HTML output
+    #[serde(default)]
+    pub export_pdf: bool,
+    /// A4, Letter, A3 or Tabloid
+    #[serde(default = "default_page_size")]
+    pub pdf_page_size: String,
+    /// portrait or landscape
+    #[serde(default = "default_orientation")]
+    pub pdf_orientation: String,
+    #[serde(default = "default_margin_mm")]
+    pub pdf_margin_mm: f64,
+    /// Also write a PowerPoint deck of native shapes next to the HTML output
+    #[serde(default)]
+    pub export_pptx: bool,
+    /// Split the deck into one slide per this many weeks; one slide when unset or 0
+    #[serde(default)]
+    pub pptx_weeks_per_slide: Option<u32>,
+    /// ID to pass to `cancel_generation`; one is assigned when unset and reported in progress events
+    #[serde(default)]
+    pub job_id: Option<String>,
+    /// Limit for the whole generation in seconds; 0 for none
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Limit for any one stage (parse, HTML, PNG) in seconds; 0 for none
+    #[serde(default = "default_stage_timeout_secs")]
+    pub stage_timeout_secs: u64,
+}
+
+fn default_png_dpi() -> f64 {
+    raster::DEFAULT_DPI
+}
+
+fn default_image_quality() -> u8 {
+    90
+}
+
+fn default_thumbnail_width() -> u32 {
+    400
+}
+
+fn default_timeout_secs() -> u64 {
+    600
+}
+
+fn default_stage_timeout_secs() -> u64 {
+    180
+}
+
+fn default_page_size() -> String {
+    "a4".to_string()
+}
+
+fn default_orientation() -> String {
+    "landscape".to_string()
+}
+
+fn default_margin_mm() -> f64 {
+    10.0
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,11 +163,23 @@ pub struct GenerateResult {
     pub success: bool,
     pub html_path: Option<String>,
     pub png_path: Option<String>,
+    /// Pixel size of the PNG, JPEG and WebP outputs
+    pub png_width: Option<u32>,
+    pub png_height: Option<u32>,
+    pub jpeg_path: Option<String>,
+    pub webp_path: Option<String>,
+    pub thumbnail_path: Option<String>,
+    pub svg_path: Option<String>,
+    pub pdf_path: Option<String>,
+    pub pptx_path: Option<String>,
     pub message: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ProgressUpdate {
+    pub job_id: String,
+    /// "running", "complete", "failed" or "cancelled"
+    pub state: String,
     pub step: String,
     pub progress: u8,
 }
@@ -438,47 +572,388 @@ fn get_node_path(app_handle: &tauri::AppHandle) -> Result<String, String> {
     }
 }
 
+/// Whether a path is a mermaid gantt file by extension
+fn is_mermaid_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| matches!(e.to_lowercase().as_str(), "mmd" | "mermaid"))
+        .unwrap_or(false)
+}
+
+/// Whether a path is a sectioned CSV file by extension
+fn is_csv_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.eq_ignore_ascii_case("csv"))
+        .unwrap_or(false)
+}
+
+/// Load any supported input file (.json, .xlsx, .xls, .mmd, .csv) into the normalized project model
+fn load_project(path: &Path) -> Result<project::Project, String> {
+    if is_mermaid_file(path) {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        return mermaid::parse_mermaid(&text);
+    }
+    if is_csv_file(path) {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        return csv::read_csv(&text);
+    }
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if extension == "json" {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let mut project: project::Project = serde_json::from_str(&text)
+            .map_err(|e| format!("Invalid project JSON in {}: {}", path.display(), e))?;
+        project.resolve_colors();
+        return Ok(project);
+    }
+    workbook::read_workbook(path)
+}
+
+/// Lay out an input file the way build.js renders it for a palette and view mode
+fn layout_chart(
+    path: &Path,
+    palette_name: &str,
+    view_mode: &str,
+    drop_shadow: bool,
+) -> Result<(project::Project, chart::Chart), String> {
+    let mut project = load_project(path)?;
+    let chart_options = chart::ChartOptions::from_generate(&mut project, palette_name, view_mode, drop_shadow);
+    let chart = chart::layout(&project, &chart_options)?;
+    Ok((project, chart))
+}
+
 #[tauri::command]
 async fn generate_gantt(
     app_handle: tauri::AppHandle,
     options: GenerateOptions,
     window: tauri::Window,
 ) -> Result<GenerateResult, String> {
-    emit_log(&window, "info", "rust", "Starting Gantt chart generation...");
-    emit_log(&window, "debug", "rust", &format!("Options: input={}, palette={}, export_png={}, view_mode={}",
-        options.input_path, options.palette, options.export_png, options.view_mode));
+    let palettes = [options.palette.clone()];
+    let view_modes = [options.view_mode.clone()];
+    generate(&app_handle, &options, &palettes, &view_modes, &window)
+        .await?
+        .pop()
+        .ok_or_else(|| "Generation produced no result".to_string())
+}
 
-    let scripts_dir = get_scripts_dir(&app_handle)?;
-    emit_log(&window, "debug", "rust", &format!("Scripts directory: {}", scripts_dir.display()));
+/// Render every palette × view mode combination in one build.js run, returning
+/// a result per variant (palettes outer, view modes inner). Outputs get the
+/// `_<palette>` and `_week` suffixes build.js uses; empty lists fall back to
+/// the palette and view mode in `options`.
+#[tauri::command]
+async fn generate_variants(
+    app_handle: tauri::AppHandle,
+    options: GenerateOptions,
+    palettes: Vec<String>,
+    view_modes: Vec<String>,
+    window: tauri::Window,
+) -> Result<Vec<GenerateResult>, String> {
+    let unique = |values: Vec<String>, fallback: &str| {
+        let mut seen = Vec::new();
+        for value in values.into_iter().map(|v| v.trim().to_lowercase()) {
+            if !value.is_empty() && !seen.contains(&value) {
+                seen.push(value);
+            }
+        }
+        if seen.is_empty() {
+            seen.push(fallback.to_string());
+        }
+        seen
+    };
+    let palettes = unique(palettes, &options.palette);
+    let view_modes = unique(view_modes, &options.view_mode);
+    generate(&app_handle, &options, &palettes, &view_modes, &window).await
+}
 
-    let build_script = scripts_dir.join("build.js");
+/// Outcome of one regeneration in watch mode, sent on `watch-result`
+#[derive(Debug, Serialize, Clone)]
+pub struct WatchResult {
+    pub path: String,
+    pub job_id: String,
+    pub result: Option<GenerateResult>,
+    pub error: Option<String>,
+}
 
-    if !build_script.exists() {
-        let err = format!("Build script not found at: {}", build_script.display());
-        emit_log(&window, "error", "rust", &err);
-        return Err(err);
-    }
-    emit_log(&window, "debug", "rust", &format!("Build script found: {}", build_script.display()));
+/// Regenerate with `options` each time the input file at `path` is saved, until
+/// `unwatch`. Runs report progress on `generation-progress` under `watch-` job
+/// IDs and their outcome on `watch-result`; a failed run keeps the watch going.
+#[tauri::command]
+async fn watch_input(
+    app_handle: tauri::AppHandle,
+    path: String,
+    mut options: GenerateOptions,
+    window: tauri::Window,
+) -> Result<(), String> {
+    let mut changes = app_handle
+        .state::<watch::Watches>()
+        .watch(Path::new(&path))
+        .inspect_err(|e| emit_log(&window, "error", "rust", e))?;
+    emit_log(&window, "info", "rust", &format!("Watching {} for changes", path));
+    options.input_path = path.clone();
+
+    tauri::async_runtime::spawn(async move {
+        let palettes = [options.palette.clone()];
+        let view_modes = [options.view_mode.clone()];
+        while watch::next_change(&mut changes).await {
+            // Some editors delete and recreate the file; the save that recreates it triggers again
+            if !Path::new(&path).exists() {
+                emit_log(&window, "warn", "rust", &format!("{} is missing; waiting for the next save", path));
+                continue;
+            }
+            let job_id = app_handle.state::<watch::Watches>().next_job_id();
+            options.job_id = Some(job_id.clone());
+            emit_log(&window, "info", "rust", &format!("{} changed; regenerating", path));
 
-    // Emit progress update
+            let outcome = generate(&app_handle, &options, &palettes, &view_modes, &window)
+                .await
+                .and_then(|mut results| results.pop().ok_or_else(|| "Generation produced no result".to_string()));
+            let (result, error) = match outcome {
+                Ok(result) => (Some(result), None),
+                Err(e) => {
+                    emit_log(&window, "error", "rust", &format!("Regenerating {} failed: {}", path, e));
+                    (None, Some(e))
+                }
+            };
+            let _ = window.emit(
+                "watch-result",
+                WatchResult {
+                    path: path.clone(),
+                    job_id,
+                    result,
+                    error,
+                },
+            );
+        }
+        emit_log(&window, "info", "rust", &format!("Stopped watching {}", path));
+    });
+    Ok(())
+}
+
+/// Stop watching an input file; false when it wasn't being watched
+#[tauri::command]
+fn unwatch(path: String, watches: tauri::State<'_, watch::Watches>) -> Result<bool, String> {
+    Ok(watches.unwatch(Path::new(&path)))
+}
+
+/// Report a generation job's progress on `generation-progress`
+fn emit_progress(window: &tauri::Window, job: &jobs::Job, state: &str, step: &str, progress: u8) {
     let _ = window.emit(
         "generation-progress",
         ProgressUpdate {
-            step: "Starting generation...".to_string(),
-            progress: 10,
+            job_id: job.id.clone(),
+            state: state.to_string(),
+            step: step.to_string(),
+            progress,
         },
     );
+}
+
+/// Report a generation that ran past a deadline, with whatever HTML it got out
+fn timeout_error(
+    window: &tauri::Window,
+    job: &jobs::Job,
+    timeout: watchdog::Timeout,
+    artifacts: &build_events::Artifacts,
+    tail: &watchdog::Tail,
+) -> String {
+    let err = timeout.report(&artifacts.html_paths(), tail);
+    emit_log(window, "error", "rust", &err);
+    emit_progress(window, job, "failed", "Timed out", 100);
+    err
+}
 
-    let node = get_node_path(&app_handle)?;
-    emit_log(&window, "info", "rust", &format!("Using Node.js: {}", node));
+/// Stop a running generation: kill its Node process tree and remove the files it wrote
+#[tauri::command]
+async fn cancel_generation(
+    job_id: String,
+    jobs: tauri::State<'_, jobs::Jobs>,
+    window: tauri::Window,
+) -> Result<bool, String> {
+    let found = jobs.cancel(&job_id);
+    if found {
+        emit_log(&window, "info", "rust", &format!("Cancelling generation {}", job_id));
+    } else {
+        emit_log(&window, "warn", "rust", &format!("No running generation with job ID {}", job_id));
+    }
+    Ok(found)
+}
+
+/// Interleaved stdout/stderr of a recent generation, or of `install-dependencies`
+#[tauri::command]
+fn get_transcript(
+    job_id: String,
+    transcripts: tauri::State<'_, transcript::Transcripts>,
+) -> Result<Vec<TranscriptLine>, String> {
+    transcripts
+        .get(&job_id)
+        .ok_or_else(|| format!("No transcript for job ID {}", job_id))
+}
+
+/// What a generation run left behind, kept even when it stops part way
+#[derive(Default)]
+struct RunRecord {
+    /// Every output file, for cleanup on cancel
+    written: Vec<PathBuf>,
+    /// Everything build.js printed, saved for `get_transcript`
+    transcript: Vec<TranscriptLine>,
+}
+
+/// Run a generation as a cancellable job. A cancelled run deletes every
+/// output it already wrote and reports the "cancelled" state.
+async fn generate(
+    app_handle: &tauri::AppHandle,
+    options: &GenerateOptions,
+    palettes: &[String],
+    view_modes: &[String],
+    window: &tauri::Window,
+) -> Result<Vec<GenerateResult>, String> {
+    let jobs = app_handle.state::<jobs::Jobs>();
+    let job = jobs
+        .start(options.job_id.clone())
+        .inspect_err(|e| emit_log(window, "error", "rust", e))?;
+    let mut record = RunRecord::default();
+    let result = run_generation(app_handle, options, palettes, view_modes, &job, &mut record, window).await;
+    jobs.finish(&job.id);
+    let RunRecord { written, transcript } = record;
+    app_handle.state::<transcript::Transcripts>().save(&job.id, transcript);
+
+    // A cancel that lands after the last step leaves the finished outputs alone
+    if result.is_err() && job.is_cancelled() {
+        for path in &written {
+            let _ = tokio::fs::remove_file(path).await;
+        }
+        emit_log(window, "warn", "rust", &format!(
+            "Generation {} cancelled; removed {} partial output(s)",
+            job.id,
+            written.len()
+        ));
+        emit_progress(window, &job, "cancelled", "Cancelled", 100);
+        return Err(jobs::CANCELLED.to_string());
+    }
+    result
+}
+
+/// Run build.js once for all palette/view combinations, then post-process each
+/// variant's HTML. Outputs and build.js output are collected in `record`.
+async fn run_generation(
+    app_handle: &tauri::AppHandle,
+    options: &GenerateOptions,
+    palettes: &[String],
+    view_modes: &[String],
+    job: &jobs::Job,
+    record: &mut RunRecord,
+    window: &tauri::Window,
+) -> Result<Vec<GenerateResult>, String> {
+    emit_log(window, "info", "rust", "Starting Gantt chart generation...");
+    let watchdog = watchdog::Watchdog::new(options.timeout_secs, options.stage_timeout_secs, Stage::Parse);
+    emit_log(window, "debug", "rust", &format!("Options: input={}, palette={}, export_png={}, view_mode={}",
+        options.input_path, palettes.join(","), options.export_png, view_modes.join(",")));
+
+    // Check the image and page settings before spending time on the build
+    let raster_options = if options.export_png || options.export_jpeg || options.export_webp || options.export_thumbnail {
+        Some(
+            raster::RasterOptions::new(
+                options.png_dpi,
+                options.png_scale,
+                options.png_width,
+                options.png_background.as_deref(),
+                options.png_padding,
+            )
+            .inspect_err(|e| emit_log(window, "error", "rust", e))?,
+        )
+    } else {
+        None
+    };
+    for (enabled, format, quality) in [
+        (options.export_jpeg, "JPEG", options.jpeg_quality),
+        (options.export_webp, "WebP", options.webp_quality),
+    ] {
+        if enabled {
+            raster::check_quality(format, quality).inspect_err(|e| emit_log(window, "error", "rust", e))?;
+        }
+    }
+    let thumbnail_options = match raster_options {
+        Some(raster) if options.export_thumbnail => Some(
+            raster
+                .thumbnail(options.thumbnail_width)
+                .inspect_err(|e| emit_log(window, "error", "rust", e))?,
+        ),
+        _ => None,
+    };
+    let page_setup = if options.export_pdf {
+        Some(
+            pdf::PageSetup::new(&options.pdf_page_size, &options.pdf_orientation, options.pdf_margin_mm)
+                .inspect_err(|e| emit_log(window, "error", "rust", e))?,
+        )
+    } else {
+        None
+    };
+
+    let scripts_dir = get_scripts_dir(app_handle)?;
+    emit_log(window, "debug", "rust", &format!("Scripts directory: {}", scripts_dir.display()));
+
+    let build_script = scripts_dir.join("build.js");
+
+    if !build_script.exists() {
+        let err = format!("Build script not found at: {}", build_script.display());
+        emit_log(window, "error", "rust", &err);
+        return Err(err);
+    }
+    emit_log(window, "debug", "rust", &format!("Build script found: {}", build_script.display()));
+
+    // Emit progress update
+    emit_progress(window, job, "running", "Starting generation...", 10);
+
+    // build.js only reads JSON and .xlsx, so legacy .xls workbooks, mermaid
+    // and CSV files are handed over as natively parsed JSON. The temp dir lives until
+    // generation ends.
+    let mut input_path = options.input_path.clone();
+    let mut _converted_dir = None;
+    let source = PathBuf::from(&input_path);
+    if workbook::is_legacy_workbook(&source) || is_mermaid_file(&source) || is_csv_file(&source) {
+        emit_log(window, "info", "rust", "Converting input file for the build script...");
+        let stem = source
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "project".to_string());
+        watchdog.enter(Stage::Parse);
+        let parse = tokio::task::spawn_blocking(move || load_project(&source));
+        // Parsing can't be interrupted, so a cancelled or timed-out job just stops waiting for it
+        let project = tokio::select! {
+            parsed = parse => parsed.map_err(|e| format!("Parse task failed: {}", e))?,
+            _ = job.cancelled() => return Err(jobs::CANCELLED.to_string()),
+            timeout = watchdog.expired() => {
+                return Err(timeout_error(window, job, timeout, &Default::default(), &Default::default()));
+            }
+        }
+        .inspect_err(|e| emit_log(window, "error", "rust", e))?;
+        let dir = tempdir().map_err(|e| format!("Failed to create temp dir: {}", e))?;
+        // Keep the workbook's file name so default output names stay the same
+        let json_path = dir.path().join(format!("{}.json", stem));
+        let json = serde_json::to_string_pretty(&project)
+            .map_err(|e| format!("Failed to serialize parsed project: {}", e))?;
+        fs::write(&json_path, json)
+            .map_err(|e| format!("Failed to write {}: {}", json_path.display(), e))?;
+        input_path = json_path.to_string_lossy().to_string();
+        _converted_dir = Some(dir);
+    }
+
+    let node = get_node_path(app_handle)?;
+    emit_log(window, "info", "rust", &format!("Using Node.js: {}", node));
     let mut args = vec![
-        build_script.to_string_lossy().to_string(),
         "--input".to_string(),
-        options.input_path.clone(),
+        input_path,
         "--palette".to_string(),
-        options.palette.clone(),
+        palettes.join(","),
         "--view-mode".to_string(),
-        options.view_mode.clone(),
+        view_modes.join(","),
     ];
 
     if let Some(output) = &options.output_path {
@@ -486,175 +961,309 @@ async fn generate_gantt(
         args.push(output.clone());
     }
 
-    // Add PNG export flag
-    if options.export_png {
-        args.push("--png".to_string());
-    } else {
-        args.push("--no-png".to_string());
-    }
-    
+    // PNG is rasterized natively below, so build.js never needs the browser
+    args.push("--no-png".to_string());
+
     // Add drop shadow flag if enabled
     if options.png_drop_shadow {
         args.push("--drop-shadow".to_string());
     }
 
-    let _ = window.emit(
-        "generation-progress",
-        ProgressUpdate {
-            step: "Running build script...".to_string(),
-            progress: 30,
-        },
-    );
-
-    // Set up environment for the node process
-    let mut cmd = Command::new(&node);
-    cmd.args(&args)
-        .current_dir(scripts_dir.parent().unwrap_or(&scripts_dir))
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+    emit_progress(window, job, "running", "Running build script...", 30);
 
     // Set NODE_PATH to include user-installed dependencies if available
     // NODE_PATH should point directly to the node_modules directory for require() to work
-    if let Ok(node_modules) = get_node_modules_dir(&app_handle) {
-        cmd.env("NODE_PATH", &node_modules);
-    }
-
-    if let Some(browser_dir) = get_browser_install_dir(&app_handle) {
-        cmd.env("PLAYWRIGHT_BROWSERS_PATH", browser_dir);
-    }
-
-    emit_log(&window, "debug", "rust", &format!("Running: {} {}", node, args.join(" ")));
-
-    let mut child = cmd
-        .spawn()
-        .map_err(|e| {
-            let err = format!("Failed to start node process: {}", e);
-            emit_log(&window, "error", "rust", &err);
-            err
-        })?;
+    let node_modules = get_node_modules_dir(app_handle).ok();
+    let browser_dir = get_browser_install_dir(app_handle);
+    // The worker is restarted whenever any of these change
+    let worker_key = format!("{}|{}|{:?}|{:?}", node, build_script.display(), node_modules, browser_dir);
+    let worker_command = || {
+        let mut cmd = Command::new(&node);
+        cmd.arg(&build_script)
+            .arg("--worker")
+            .current_dir(scripts_dir.parent().unwrap_or(&scripts_dir));
+
+        // Its own process group, so cancelling also reaches Chromium started by Playwright
+        #[cfg(not(target_os = "windows"))]
+        cmd.process_group(0);
+
+        if let Some(node_modules) = &node_modules {
+            cmd.env("NODE_PATH", node_modules);
+        }
+        if let Some(browser_dir) = &browser_dir {
+            cmd.env("PLAYWRIGHT_BROWSERS_PATH", browser_dir);
+        }
+        cmd
+    };
 
-    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
-    let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+    emit_log(window, "debug", "rust", &format!("Building with the renderer worker: {}", args.join(" ")));
 
-    let mut stdout_reader = BufReader::new(stdout).lines();
-    let mut stderr_reader = BufReader::new(stderr).lines();
+    job.check()?;
+    // Another generation (a watch run, say) may hold the worker; queueing counts against no limit
+    let queued = std::time::Instant::now();
+    let renderer = app_handle.state::<renderer::Renderer>();
+    let mut renderer = tokio::select! {
+        renderer = renderer.lock() => renderer,
+        _ = job.cancelled() => return Err(jobs::CANCELLED.to_string()),
+    };
+    watchdog.exclude(queued.elapsed());
+    // build.js reports its own stages; until the first, the worker is starting up
+    watchdog.enter(Stage::Parse);
 
     let mut output_lines = Vec::new();
     let mut error_lines = Vec::new();
-    let mut html_path: Option<String> = None;
-    let mut png_path: Option<String> = None;
-
-    // Read stdout
-    while let Ok(Some(line)) = stdout_reader.next_line().await {
-        output_lines.push(line.clone());
-        emit_log(&window, "debug", "node", &line);
-
-        // Parse output to find generated file paths
-        if line.contains("Generated:") || line.contains("Output:") || line.contains("Generated PNG") || line.contains("Generated HTML") {
-            if line.ends_with(".html") {
-                html_path = Some(line.split_whitespace().last().unwrap_or("").to_string());
-                emit_log(&window, "info", "node", &format!("HTML generated: {}", html_path.as_ref().unwrap_or(&String::new())));
-            } else if line.ends_with(".png") {
-                png_path = Some(line.split_whitespace().last().unwrap_or("").to_string());
-                emit_log(&window, "info", "node", &format!("PNG generated: {}", png_path.as_ref().unwrap_or(&String::new())));
+    let mut artifacts = build_events::Artifacts::default();
+    let mut build_error = None;
+    let mut tail = watchdog::Tail::default();
+
+    // Progress and output paths come from the JSON-lines event stream, not the log text
+    let run = renderer.render(
+        &worker_key,
+        worker_command,
+        &args,
+        |pid| job.set_process(pid),
+        &mut record.transcript,
+        |stream, line| {
+            if stream == Stream::Stderr {
+                tail.push(&line);
+                if !line.trim().is_empty() {
+                    emit_log(window, "warn", "node", &line);
+                }
+                error_lines.push(line);
+                return;
             }
+            let Some(event) = build_events::parse_line(&line) else {
+                tail.push(&line);
+                emit_log(window, "debug", "node", &line);
+                output_lines.push(line);
+                return;
+            };
+            artifacts.record(&event);
+            match event {
+                BuildEvent::StageStarted { stage } => {
+                    watchdog.enter(stage);
+                    match stage {
+                        Stage::Parse => emit_progress(window, job, "running", "Parsing input file...", 40),
+                        Stage::Html => emit_progress(window, job, "running", "Generating HTML...", 60),
+                        _ => {}
+                    }
+                }
+                BuildEvent::Artifact { kind, path, .. } if kind != ArtifactKind::Json => {
+                    if kind == ArtifactKind::Html {
+                        emit_log(window, "info", "node", &format!("HTML generated: {}", path));
+                    }
+                    record.written.push(PathBuf::from(path));
+                }
+                BuildEvent::StageFinished { stage } => {
+                    emit_log(window, "debug", "node", &format!("Finished {:?} stage", stage));
+                }
+                BuildEvent::Warning { message } => {
+                    tail.push(&message);
+                    emit_log(window, "warn", "node", &message);
+                }
+                BuildEvent::Error { code, message } => {
+                    tail.push(&message);
+                    emit_log(window, "error", "node", &format!("[{}] {}", code, message));
+                    build_error = Some((code, message));
+                }
+                _ => {}
+            }
+        },
+    );
+    // Err(None) when cancelled, Err(Some) when a deadline passed
+    let succeeded = tokio::select! {
+        succeeded = run => Ok(succeeded),
+        _ = job.cancelled() => Err(None),
+        timeout = watchdog.expired() => Err(Some(timeout)),
+    };
+    job.set_process(None);
+    let succeeded = match succeeded {
+        Ok(succeeded) => succeeded.inspect_err(|e| emit_log(window, "error", "rust", e))?,
+        Err(timeout) => {
+            // The worker may still be mid-build; a fresh one starts with the next generation
+            renderer.stop();
+            return Err(match timeout {
+                Some(timeout) => timeout_error(window, job, timeout, &artifacts, &tail),
+                None => jobs::CANCELLED.to_string(),
+            });
         }
+    };
+    // The rest of the run is Rust-side; let a queued generation have the worker
+    drop(renderer);
 
-        // Update progress based on output
-        if line.contains("Parsing") {
-            let _ = window.emit(
-                "generation-progress",
-                ProgressUpdate {
-                    step: "Parsing input file...".to_string(),
-                    progress: 40,
-                },
-            );
-        } else if line.contains("Generating HTML") {
-            let _ = window.emit(
-                "generation-progress",
-                ProgressUpdate {
-                    step: "Generating HTML...".to_string(),
-                    progress: 60,
-                },
-            );
-        } else if line.contains("Exporting PNG") {
-            let _ = window.emit(
-                "generation-progress",
-                ProgressUpdate {
-                    step: "Exporting PNG...".to_string(),
-                    progress: 80,
-                },
-            );
-        }
-    }
+    emit_log(window, "info", "rust", &format!("Build {}", if succeeded { "finished" } else { "failed" }));
 
-    // Read stderr
-    while let Ok(Some(line)) = stderr_reader.next_line().await {
-        error_lines.push(line.clone());
-        if !line.trim().is_empty() {
-            emit_log(&window, "warn", "node", &line);
-        }
+    if !succeeded {
+        emit_progress(window, job, "failed", "Failed", 100);
     }
 
-    let status = child
-        .wait()
-        .await
-        .map_err(|e| {
-            let err = format!("Failed to wait for process: {}", e);
-            emit_log(&window, "error", "rust", &err);
-            err
-        })?;
-
-    emit_log(&window, "info", "rust", &format!("Build process exited with status: {:?}", status));
+    if succeeded {
+        let variants: Vec<(&String, &String)> = palettes
+            .iter()
+            .flat_map(|palette| view_modes.iter().map(move |view_mode| (palette, view_mode)))
+            .collect();
+        if variants.len() > 1 && artifacts.html_count() != variants.len() {
+            let err = format!(
+                "Expected {} variants from the build script but found {} HTML files",
+                variants.len(),
+                artifacts.html_count()
+            );
+            emit_log(window, "error", "rust", &err);
+            return Err(err);
+        }
 
-    let _ = window.emit(
-        "generation-progress",
-        ProgressUpdate {
-            step: if status.success() {
-                "Complete!"
-            } else {
-                "Failed"
-            }
-            .to_string(),
-            progress: 100,
-        },
-    );
+        let mut results = Vec::new();
+        for (index, (palette, view_mode)) in variants.into_iter().enumerate() {
+            job.check()?;
+            let html_path = artifacts.html(palette, view_mode).map(String::from);
+            let step = |name: &str| {
+                if palettes.len() * view_modes.len() > 1 {
+                    format!("{} ({} {}, {}/{})", name, palette, view_mode, index + 1, palettes.len() * view_modes.len())
+                } else {
+                    name.to_string()
+                }
+            };
 
-    if status.success() {
-        // Try to extract paths from output if not already found
-        if html_path.is_none() {
-            for line in &output_lines {
-                if line.ends_with(".html") && (line.contains("output") || line.contains("Output") || line.contains("Generated HTML") || line.contains("Generated:")) {
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    if let Some(path) = parts.last() {
-                        html_path = Some(path.to_string());
+            if options.self_contained_html {
+                if let Some(html) = &html_path {
+                    emit_progress(window, job, "running", &step("Inlining resources..."), 75);
+                    watchdog.enter(Stage::Html);
+                    // Template references resolve like they do next to the template in the repo
+                    let project_dir = scripts_dir.parent().map(Path::to_path_buf).unwrap_or_default();
+                    let search_dirs = [
+                        Path::new(html).parent().map(Path::to_path_buf).unwrap_or_default(),
+                        project_dir.join("templates"),
+                        project_dir,
+                    ];
+                    let contents = tokio::fs::read_to_string(html)
+                        .await
+                        .map_err(|e| format!("Failed to read {}: {}", html, e))?;
+                    let inlined = tokio::select! {
+                        inlined = offline::inline_resources(&contents, &search_dirs) => inlined,
+                        _ = job.cancelled() => Err(jobs::CANCELLED.to_string()),
+                        timeout = watchdog.expired() => return Err(timeout_error(window, job, timeout, &artifacts, &tail)),
                     }
+                    .inspect_err(|e| emit_log(window, "error", "rust", e))?;
+                    job.check()?;
+                    tokio::fs::write(html, inlined)
+                        .await
+                        .map_err(|e| format!("Failed to write {}: {}", html, e))?;
+                    emit_log(window, "info", "rust", &format!("Self-contained HTML: {}", html));
                 }
             }
-        }
 
-        // Try to extract PNG path from output if not already found
-        if png_path.is_none() && options.export_png {
-            for line in &output_lines {
-                if line.ends_with(".png") && (line.contains("Generated PNG") || line.contains("Generated:") || line.contains("PNG")) {
-                    let parts: Vec<&str> = line.split_whitespace().collect();
-                    // Look for the path (usually the last word that ends with .png)
-                    for part in parts.iter().rev() {
-                        if part.ends_with(".png") {
-                            png_path = Some(part.to_string());
-                            break;
-                        }
+            let mut png_path = None;
+            let mut png_size = None;
+            let mut jpeg_path = None;
+            let mut webp_path = None;
+            let mut thumbnail_path = None;
+            let mut svg_path = None;
+            let mut pdf_path = None;
+            let mut pptx_path = None;
+            let export_png = options.export_png;
+            let jpeg_quality = options.export_jpeg.then_some(options.jpeg_quality);
+            let webp_quality = options.export_webp.then_some(options.webp_quality);
+            let export_svg = options.export_svg;
+            let pptx_weeks = options.export_pptx.then_some(options.pptx_weeks_per_slide);
+            if raster_options.is_some() || export_svg || page_setup.is_some() || pptx_weeks.is_some() {
+                if let Some(html) = &html_path {
+                    emit_progress(window, job, "running", &step("Rendering chart..."), 80);
+                    watchdog.enter(Stage::Png);
+                    let source = PathBuf::from(&options.input_path);
+                    let palette_name = palette.clone();
+                    let view_mode = view_mode.clone();
+                    let drop_shadow = options.png_drop_shadow;
+                    let render = tokio::task::spawn_blocking(move || {
+                        let (project, chart) = layout_chart(&source, &palette_name, &view_mode, drop_shadow)?;
+                        // PNG, JPEG and WebP share one rendering; only the encoding differs
+                        let image = raster_options
+                            .filter(|_| export_png || jpeg_quality.is_some() || webp_quality.is_some())
+                            .map(|raster| raster::render(&chart, &raster))
+                            .transpose()?;
+                        let size = image.as_ref().map(|image| (image.width(), image.height()));
+                        let png = image
+                            .as_ref()
+                            .filter(|_| export_png)
+                            .map(raster::Image::to_png)
+                            .transpose()?;
+                        let jpeg = image
+                            .as_ref()
+                            .zip(jpeg_quality)
+                            .map(|(image, quality)| image.to_jpeg(quality))
+                            .transpose()?;
+                        let webp = image
+                            .as_ref()
+                            .zip(webp_quality)
+                            .map(|(image, quality)| image.to_webp(quality))
+                            .transpose()?;
+                        let thumbnail = thumbnail_options
+                            .map(|thumbnail| raster::render(&chart, &thumbnail)?.to_png())
+                            .transpose()?;
+                        let svg = export_svg.then(|| svg::to_svg(&chart));
+                        let pdf = page_setup
+                            .map(|setup| pdf::to_pdf(&chart, &project.title, &setup))
+                            .transpose()?;
+                        let pptx = pptx_weeks
+                            .map(|weeks| pptx::to_pptx(&chart, &project.title, weeks))
+                            .transpose()?;
+                        Ok::<_, String>((size, png, jpeg, webp, thumbnail, svg, pdf, pptx))
+                    });
+                    // Rendering can't be interrupted, so a cancelled or timed-out job just stops waiting for it
+                    let (size, png, jpeg, webp, thumbnail, svg, pdf, pptx) = tokio::select! {
+                        rendered = render => rendered.map_err(|e| format!("Render task failed: {}", e))?,
+                        _ = job.cancelled() => return Err(jobs::CANCELLED.to_string()),
+                        timeout = watchdog.expired() => return Err(timeout_error(window, job, timeout, &artifacts, &tail)),
+                    }
+                    .inspect_err(|e| emit_log(window, "error", "rust", e))?;
+                    if let Some((width, height)) = size {
+                        png_size = size;
+                        emit_log(window, "debug", "rust", &format!("Image size: {}x{} pixels", width, height));
+                    }
+                    let html = PathBuf::from(html);
+                    let stem = html.file_stem().unwrap_or_default().to_string_lossy().to_string();
+                    for (bytes, label, path, slot) in [
+                        (png, "PNG", html.with_extension("png"), &mut png_path),
+                        (jpeg, "JPEG", html.with_extension("jpg"), &mut jpeg_path),
+                        (webp, "WebP", html.with_extension("webp"), &mut webp_path),
+                        (thumbnail, "Thumbnail", html.with_file_name(format!("{}_thumb.png", stem)), &mut thumbnail_path),
+                        (svg.map(String::into_bytes), "SVG", html.with_extension("svg"), &mut svg_path),
+                        (pdf, "PDF", html.with_extension("pdf"), &mut pdf_path),
+                        (pptx, "PPTX", html.with_extension("pptx"), &mut pptx_path),
+                    ] {
+                        let Some(bytes) = bytes else { continue };
+                        job.check()?;
+                        record.written.push(path.clone());
+                        tokio::fs::write(&path, bytes)
+                            .await
+                            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+                        emit_log(window, "info", "rust", &format!("{} generated: {}", label, path.display()));
+                        *slot = Some(path.to_string_lossy().to_string());
                     }
+                } else {
+                    emit_log(window, "warn", "rust", "No HTML output path found, skipping image/SVG/PDF/PPTX export");
                 }
             }
+
+            results.push(GenerateResult {
+                success: true,
+                html_path,
+                png_path,
+                png_width: png_size.map(|(width, _)| width),
+                png_height: png_size.map(|(_, height)| height),
+                jpeg_path,
+                webp_path,
+                thumbnail_path,
+                svg_path,
+                pdf_path,
+                pptx_path,
+                message: output_lines.join("\n"),
+            });
         }
 
-        Ok(GenerateResult {
-            success: true,
-            html_path,
-            png_path,
-            message: output_lines.join("\n"),
-        })
+        emit_progress(window, job, "complete", "Complete!", 100);
+        Ok(results)
+    } else if let Some((code, message)) = build_error {
+        Err(format!("Generation failed ({}): {}", code, message))
     } else {
         Err(format!(
             "Generation failed:\n{}",
@@ -695,12 +1304,22 @@ async fn validate_input_file(path: String, window: tauri::Window) -> Result<bool
         .to_lowercase();
 
     match extension.as_str() {
-        "json" | "xlsx" => {
+        "json" => {
+            emit_log(&window, "debug", "rust", &format!("File validated: {}", path.display()));
+            Ok(true)
+        }
+        "xlsx" | "xls" | "mmd" | "mermaid" | "csv" => {
+            // Make sure the file can actually be read before accepting it
+            let input_path = path.clone();
+            tokio::task::spawn_blocking(move || load_project(&input_path))
+                .await
+                .map_err(|e| format!("Validation task failed: {}", e))?
+                .inspect_err(|e| emit_log(&window, "error", "rust", e))?;
             emit_log(&window, "debug", "rust", &format!("File validated: {}", path.display()));
             Ok(true)
         }
         _ => {
-            let err = format!("Invalid file type: .{}. Expected .json or .xlsx", extension);
+            let err = format!("Invalid file type: .{}. Expected .json, .xlsx, .xls, .mmd or .csv", extension);
             emit_log(&window, "error", "rust", &err);
             Err(err)
         }
@@ -708,7 +1327,7 @@ async fn validate_input_file(path: String, window: tauri::Window) -> Result<bool
 }
 
 #[tauri::command]
-async fn parse_file(path: String, app_handle: tauri::AppHandle, window: tauri::Window) -> Result<String, String> {
+async fn parse_file(path: String, window: tauri::Window) -> Result<String, String> {
     let file_path = PathBuf::from(&path);
     
     if !file_path.exists() {
@@ -735,50 +1354,36 @@ async fn parse_file(path: String, app_handle: tauri::AppHandle, window: tauri::W
                 })
         }
         "xlsx" | "xls" => {
-            // For Excel files, use Node.js to parse
-            let scripts_dir = get_scripts_dir(&app_handle)?;
-            let parse_script = scripts_dir.join("parse-file.js");
-            
-            if !parse_script.exists() {
-                let err = format!("Parse script not found at: {}", parse_script.display());
-                emit_log(&window, "error", "rust", &err);
-                return Err(err);
-            }
-
-            let node = get_node_path(&app_handle)?;
-            
-            let mut cmd = Command::new(&node);
-            cmd.arg(parse_script.to_string_lossy().to_string())
-                .arg(&path)
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped());
-
-            // Set NODE_PATH to include user-installed dependencies if available
-            // NODE_PATH should point directly to the node_modules directory for require() to work
-            if let Ok(node_modules) = get_node_modules_dir(&app_handle) {
-                cmd.env("NODE_PATH", &node_modules);
-            }
-
+            // Excel workbooks are read natively so previews work before Node is installed
             emit_log(&window, "debug", "rust", &format!("Parsing Excel file: {}", path));
 
-            let output = cmd
-                .output()
+            let project = tokio::task::spawn_blocking(move || workbook::read_workbook(&file_path))
                 .await
+                .map_err(|e| format!("Parse task failed: {}", e))?
                 .map_err(|e| {
-                    let err = format!("Failed to run parse script: {}", e);
+                    let err = format!("Failed to parse Excel file: {}", e);
                     emit_log(&window, "error", "rust", &err);
                     err
                 })?;
 
-            if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
-                let err = format!("Failed to parse Excel file: {}", stderr);
-                emit_log(&window, "error", "rust", &err);
-                return Err(err);
-            }
+            serde_json::to_string_pretty(&project)
+                .map_err(|e| format!("Failed to serialize parsed project: {}", e))
+        }
+        "mmd" | "mermaid" | "csv" => {
+            let kind = if extension == "csv" { "CSV" } else { "mermaid" };
+            emit_log(&window, "debug", "rust", &format!("Parsing {} file: {}", kind, path));
 
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            Ok(stdout.to_string())
+            let project = tokio::task::spawn_blocking(move || load_project(&file_path))
+                .await
+                .map_err(|e| format!("Parse task failed: {}", e))?
+                .map_err(|e| {
+                    let err = format!("Failed to parse {} file: {}", kind, e);
+                    emit_log(&window, "error", "rust", &err);
+                    err
+                })?;
+
+            serde_json::to_string_pretty(&project)
+                .map_err(|e| format!("Failed to serialize parsed project: {}", e))
         }
         _ => {
             let err = format!("Unsupported file type for parsing: {}", extension);
@@ -788,65 +1393,230 @@ async fn parse_file(path: String, app_handle: tauri::AppHandle, window: tauri::W
     }
 }
 
-/// Export JSON data to Excel format using the json_to_excel.js script
+/// Parse a task block pasted as TSV or an HTML table into tasks plus per-row diagnostics
+#[tauri::command]
+fn parse_pasted_tasks(text: String) -> paste::PasteResult {
+    paste::parse_pasted_tasks(&text)
+}
+
+/// Save a project from the manual-entry screen as canonical JSON, stable across saves
+#[tauri::command]
+async fn save_project_json(
+    project: project::Project,
+    output_path: String,
+    window: tauri::Window,
+) -> Result<bool, String> {
+    let json = project::to_canonical_json(&project).inspect_err(|e| emit_log(&window, "error", "rust", e))?;
+    tokio::fs::write(&output_path, json)
+        .await
+        .map_err(|e| {
+            let err = format!("Failed to write {}: {}", output_path, e);
+            emit_log(&window, "error", "rust", &err);
+            err
+        })?;
+    emit_log(&window, "info", "rust", &format!("Saved project to: {}", output_path));
+    Ok(true)
+}
+
+/// Write a project from the manual-entry screen as an Excel workbook in the template layout
 #[tauri::command]
 async fn export_to_excel(
-    json_path: String,
+    project: project::Project,
     excel_path: String,
-    app_handle: tauri::AppHandle,
     window: tauri::Window,
 ) -> Result<bool, String> {
     emit_log(&window, "info", "rust", &format!("Exporting to Excel: {}", excel_path));
 
-    let scripts_dir = get_scripts_dir(&app_handle)?;
-    let export_script = scripts_dir.join("json_to_excel.js");
+    let contents = tokio::task::spawn_blocking(move || workbook::write_workbook(&project))
+        .await
+        .map_err(|e| format!("Export task failed: {}", e))?
+        .map_err(|e| {
+            let err = format!("Failed to export to Excel: {}", e);
+            emit_log(&window, "error", "rust", &err);
+            err
+        })?;
 
-    if !export_script.exists() {
-        let err = format!("Export script not found at: {}", export_script.display());
-        emit_log(&window, "error", "rust", &err);
-        return Err(err);
-    }
+    tokio::fs::write(&excel_path, contents)
+        .await
+        .map_err(|e| {
+            let err = format!("Failed to write {}: {}", excel_path, e);
+            emit_log(&window, "error", "rust", &err);
+            err
+        })?;
 
-    let node = get_node_path(&app_handle)?;
+    emit_log(&window, "info", "rust", &format!("Successfully exported to: {}", excel_path));
+    Ok(true)
+}
 
-    let mut cmd = Command::new(&node);
-    cmd.arg(export_script.to_string_lossy().to_string())
-        .arg("--input")
-        .arg(&json_path)
-        .arg("--output")
-        .arg(&excel_path)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped());
+/// Write a project from the manual-entry screen as a Word document: the chart
+/// at page width followed by task and milestone tables
+#[tauri::command]
+async fn export_docx(
+    project: project::Project,
+    output_path: String,
+    palette: Option<String>,
+    view_mode: Option<String>,
+    window: tauri::Window,
+) -> Result<bool, String> {
+    emit_log(&window, "info", "rust", &format!("Exporting to Word: {}", output_path));
+
+    let contents = tokio::task::spawn_blocking(move || {
+        let mut project = project;
+        let chart_options = chart::ChartOptions::from_generate(
+            &mut project,
+            palette.as_deref().unwrap_or(""),
+            view_mode.as_deref().unwrap_or("day"),
+            false,
+        );
+        let chart = chart::layout(&project, &chart_options)?;
+        let raster = raster::RasterOptions::new(raster::DEFAULT_DPI, None, None, None, 0.0)?;
+        let image = raster::render(&chart, &raster)?;
+        let png = image.to_png()?;
+        docx::to_docx(&project, &docx::ChartImage { png: &png, width: image.width(), height: image.height() })
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?
+    .map_err(|e| {
+        let err = format!("Failed to export to Word: {}", e);
+        emit_log(&window, "error", "rust", &err);
+        err
+    })?;
 
-    // Set NODE_PATH to include user-installed dependencies if available
-    // NODE_PATH should point directly to the node_modules directory for require() to work
-    if let Ok(node_modules) = get_node_modules_dir(&app_handle) {
-        cmd.env("NODE_PATH", &node_modules);
-    }
+    tokio::fs::write(&output_path, contents)
+        .await
+        .map_err(|e| {
+            let err = format!("Failed to write {}: {}", output_path, e);
+            emit_log(&window, "error", "rust", &err);
+            err
+        })?;
 
-    emit_log(&window, "debug", "rust", &format!("Running: {} {} --input {} --output {}",
-        node, export_script.display(), json_path, excel_path));
+    emit_log(&window, "info", "rust", &format!("Successfully exported to: {}", output_path));
+    Ok(true)
+}
 
-    let output = cmd
-        .output()
+/// Load a project file, render it with `render` and write the result to `output_path`
+async fn export_project<F>(
+    input_path: String,
+    output_path: String,
+    format_name: &str,
+    window: &tauri::Window,
+    render: F,
+) -> Result<bool, String>
+where
+    F: FnOnce(&project::Project) -> Result<Vec<u8>, String> + Send + 'static,
+{
+    emit_log(window, "info", "rust", &format!("Exporting to {}: {}", format_name, output_path));
+
+    let source = PathBuf::from(&input_path);
+    let contents = tokio::task::spawn_blocking(move || render(&load_project(&source)?))
         .await
+        .map_err(|e| format!("Export task failed: {}", e))?
         .map_err(|e| {
-            let err = format!("Failed to run export script: {}", e);
-            emit_log(&window, "error", "rust", &err);
+            let err = format!("Failed to export to {}: {}", format_name, e);
+            emit_log(window, "error", "rust", &err);
             err
         })?;
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let err = format!("Failed to export to Excel: {}", stderr);
-        emit_log(&window, "error", "rust", &err);
-        return Err(err);
-    }
+    tokio::fs::write(&output_path, contents)
+        .await
+        .map_err(|e| {
+            let err = format!("Failed to write {}: {}", output_path, e);
+            emit_log(window, "error", "rust", &err);
+            err
+        })?;
 
-    emit_log(&window, "info", "rust", &format!("Successfully exported to: {}", excel_path));
+    emit_log(window, "info", "rust", &format!("Successfully exported to: {}", output_path));
     Ok(true)
 }
 
+/// Export a project file as a single sectioned CSV that parse_file can read back
+#[tauri::command]
+async fn export_csv(
+    input_path: String,
+    output_path: String,
+    window: tauri::Window,
+) -> Result<bool, String> {
+    export_project(input_path, output_path, "CSV", &window, |project| {
+        Ok(csv::to_csv(project).into_bytes())
+    })
+    .await
+}
+
+/// Export a Markdown status report, or an HTML one when `output_path` ends in .html.
+/// `png_path` is embedded as a link relative to the report.
+#[tauri::command]
+async fn export_report(
+    input_path: String,
+    output_path: String,
+    png_path: Option<String>,
+    upcoming_days: Option<i64>,
+    window: tauri::Window,
+) -> Result<bool, String> {
+    let output = PathBuf::from(&output_path);
+    let html = output
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| matches!(e.to_lowercase().as_str(), "html" | "htm"))
+        .unwrap_or(false);
+    let options = report::ReportOptions {
+        today: chrono::Local::now().date_naive(),
+        upcoming_days: upcoming_days.unwrap_or(report::DEFAULT_UPCOMING_DAYS),
+        image_link: png_path.map(|png| {
+            report::relative_link(output.parent().unwrap_or(Path::new("")), Path::new(&png))
+        }),
+    };
+    let format_name = if html { "HTML report" } else { "Markdown report" };
+    export_project(input_path, output_path, format_name, &window, move |project| {
+        let report = if html {
+            report::to_html(project, &options)
+        } else {
+            report::to_markdown(project, &options)
+        };
+        report.map(String::into_bytes)
+    })
+    .await
+}
+
+/// Export a project file as a mermaid gantt block that parse_file can read back
+#[tauri::command]
+async fn export_mermaid(
+    input_path: String,
+    output_path: String,
+    window: tauri::Window,
+) -> Result<bool, String> {
+    export_project(input_path, output_path, "mermaid", &window, |project| {
+        Ok(mermaid::to_mermaid(project).into_bytes())
+    })
+    .await
+}
+
+/// Export a project file as Microsoft Project XML (MSPDI)
+#[tauri::command]
+async fn export_mspdi(
+    input_path: String,
+    output_path: String,
+    window: tauri::Window,
+) -> Result<bool, String> {
+    export_project(input_path, output_path, "MS Project XML", &window, |project| {
+        mspdi::to_mspdi(project).map(String::into_bytes)
+    })
+    .await
+}
+
+/// Export a project file as an iCalendar (.ics) feed of tasks, milestones and pauses
+#[tauri::command]
+async fn export_ics(
+    input_path: String,
+    output_path: String,
+    window: tauri::Window,
+) -> Result<bool, String> {
+    let stamp = chrono::Utc::now().naive_utc();
+    export_project(input_path, output_path, "iCalendar", &window, move |project| {
+        ics::to_ics(project, stamp).map(String::into_bytes)
+    })
+    .await
+}
+
 #[tauri::command]
 fn get_palette_info() -> Vec<PaletteInfo> {
     vec![
@@ -1020,10 +1790,17 @@ pub struct InstallProgress {
     pub error: Option<String>,
 }
 
+/// How long `npm install` may run before it is killed
+const NPM_INSTALL_TIMEOUT_SECS: u64 = 900;
+
+/// Job ID the last `npm install` transcript is saved under
+const INSTALL_TRANSCRIPT_ID: &str = "install-dependencies";
+
 /// Install dependencies by running npm install
 #[tauri::command]
 async fn install_dependencies(
     app_handle: tauri::AppHandle,
+    timeout_secs: Option<u64>,
     window: tauri::Window,
 ) -> Result<bool, String> {
     // Emit initial progress
@@ -1095,6 +1872,9 @@ async fn install_dependencies(
         },
     );
 
+    // The renderer worker has the old modules loaded; the next generation starts a new one
+    app_handle.state::<renderer::Renderer>().stop().await;
+
     // Run npm install
     let mut npm_cmd_builder = Command::new(&npm_cmd);
     npm_cmd_builder
@@ -1103,6 +1883,10 @@ async fn install_dependencies(
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
+    // Its own process group, so a timeout also reaches the install scripts npm runs
+    #[cfg(not(target_os = "windows"))]
+    npm_cmd_builder.process_group(0);
+
     if let Some(node_dir) = node_dir {
         let separator = if cfg!(target_os = "windows") { ";" } else { ":" };
         let existing_path = std::env::var("PATH").unwrap_or_default();
@@ -1126,40 +1910,67 @@ async fn install_dependencies(
     let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
     let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
 
-    let mut stdout_reader = BufReader::new(stdout).lines();
     let mut progress = 20u8;
+    let mut tail = watchdog::Tail::default();
+    let mut error_lines = Vec::new();
+    let mut transcript = Vec::new();
+
+    let run = async {
+        transcript::drain(stdout, stderr, &mut transcript, |stream, line| {
+            tail.push(&line);
+            if stream == Stream::Stderr {
+                // Kept for the error message only
+                if !line.contains("WARN") && !line.is_empty() {
+                    error_lines.push(line);
+                }
+                return;
+            }
+            // Update progress based on npm output
+            if line.contains("added") || line.contains("packages") {
+                progress = progress.saturating_add(10).min(90);
+            }
+            let _ = window.emit(
+                "install-progress",
+                InstallProgress {
+                    stage: "Installing".to_string(),
+                    message: line,
+                    progress,
+                    complete: false,
+                    error: None,
+                },
+            );
+        })
+        .await;
 
-    // Read stdout and update progress
-    while let Ok(Some(line)) = stdout_reader.next_line().await {
-        // Update progress based on npm output
-        if line.contains("added") || line.contains("packages") {
-            progress = progress.saturating_add(10).min(90);
+        child.wait().await
+    };
+    let limit = timeout_secs.unwrap_or(NPM_INSTALL_TIMEOUT_SECS);
+    let status = tokio::select! {
+        status = run => Some(status),
+        _ = tokio::time::sleep(std::time::Duration::from_secs(limit)), if limit > 0 => None,
+    };
+    app_handle
+        .state::<transcript::Transcripts>()
+        .save(INSTALL_TRANSCRIPT_ID, transcript);
+    let Some(status) = status else {
+        if let Some(pid) = child.id() {
+            jobs::kill_process_tree(pid);
         }
+        let _ = child.kill().await;
+        let error_msg = format!("npm install timed out after {}s{}", limit, tail.report());
         let _ = window.emit(
             "install-progress",
             InstallProgress {
-                stage: "Installing".to_string(),
-                message: line,
-                progress,
-                complete: false,
-                error: None,
+                stage: "Error".to_string(),
+                message: "Installation timed out".to_string(),
+                progress: 100,
+                complete: true,
+                error: Some(error_msg.clone()),
             },
         );
-    }
-
-    let status = child
-        .wait()
-        .await
-        .map_err(|e| format!("Failed to wait for npm: {}", e))?;
-
-    // Read stderr for error messages
-    let mut error_lines = Vec::new();
-    let mut stderr_reader = BufReader::new(stderr).lines();
-    while let Ok(Some(line)) = stderr_reader.next_line().await {
-        if !line.contains("WARN") && !line.is_empty() {
-            error_lines.push(line);
-        }
-    }
+        return Err(error_msg);
+    };
+    let status = status.map_err(|e| format!("Failed to wait for npm: {}", e))?;
 
     if status.success() {
         let _ = window.emit(
@@ -1644,12 +2455,29 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
+        .manage(jobs::Jobs::default())
+        .manage(transcript::Transcripts::default())
+        .manage(renderer::Renderer::default())
+        .manage(watch::Watches::default())
         .invoke_handler(tauri::generate_handler![
             generate_gantt,
+            generate_variants,
+            cancel_generation,
+            get_transcript,
+            watch_input,
+            unwatch,
             read_json_file,
             validate_input_file,
             parse_file,
+            parse_pasted_tasks,
+            save_project_json,
             export_to_excel,
+            export_docx,
+            export_csv,
+            export_mermaid,
+            export_mspdi,
+            export_ics,
+            export_report,
             get_palette_info,
             check_dependencies,
             install_dependencies,
@@ -0,0 +1,430 @@
+use crate::project::{normalize_date, Milestone, PausePeriod, Project, Task};
+
+/// Prefix for the comment lines that carry fields mermaid has no syntax for
+const META_PREFIX: &str = "%% ganttgen:";
+
+/// Section that holds milestones not linked to a task
+const MILESTONES_SECTION: &str = "Milestones";
+
+/// Section that holds pause periods
+const PAUSES_SECTION: &str = "Pause Periods";
+
+/// Appended to a task's section when its name is one of the sections above
+const TASK_SECTION_SUFFIX: &str = " (task)";
+
+/// Write a project as a mermaid `gantt` block.
+///
+/// Every task becomes its own section holding the task bar and the milestones
+/// linked to it. Unlinked milestones and pause periods get sections of their
+/// own. Dates are written verbatim with `inclusiveEndDates`, so mermaid draws
+/// the same ranges as the HTML chart and `parse_mermaid` reads them back as-is.
+/// Hours, subtasks, colors and the timeline bounds ride along as `%% ganttgen:`
+/// comments, which mermaid ignores, as do the original names of anything whose
+/// label had to lose line breaks or `:;#`.
+pub fn to_mermaid(project: &Project) -> String {
+    let mut out = vec![
+        "gantt".to_string(),
+        format!("    title {}", single_line(&project.title)),
+        "    dateFormat YYYY-MM-DD".to_string(),
+        "    axisFormat %b %d".to_string(),
+        "    inclusiveEndDates".to_string(),
+    ];
+    if single_line(&project.title) != project.title {
+        out.push(format!("    {}title {}", META_PREFIX, meta_text(&project.title)));
+    }
+    if !project.timeline_start.is_empty() || !project.timeline_end.is_empty() {
+        out.push(format!(
+            "    {}timeline {} {}",
+            META_PREFIX, project.timeline_start, project.timeline_end
+        ));
+    }
+    if !project.show_milestones {
+        out.push(format!("    {}hideMilestones", META_PREFIX));
+    }
+    if !project.palette.is_empty() {
+        out.push(format!("    {}palette {}", META_PREFIX, project.palette.join(" ")));
+    }
+
+    for (idx, task) in project.tasks.iter().enumerate() {
+        out.push(String::new());
+        out.push(format!("    section {}", section_label(&task.name)));
+        if task.hours != 0.0 {
+            out.push(format!("    {}hours {}", META_PREFIX, task.hours));
+        }
+        if let Some(index) = task.color_index {
+            out.push(format!("    {}colorIndex {}", META_PREFIX, index));
+        } else if let Some(color) = &task.color {
+            out.push(format!("    {}color {}", META_PREFIX, color));
+        }
+        for subtask in &task.subtasks {
+            out.push(format!("    {}subtask {}", META_PREFIX, meta_text(subtask)));
+        }
+        push_name(&mut out, &task.name, false);
+        out.push(format!(
+            "    {} :t{}, {}, {}",
+            task_label(&task.name),
+            idx + 1,
+            task.start,
+            task.end
+        ));
+        for (m_idx, milestone) in project.milestones.iter().enumerate() {
+            if milestone.task_index == Some(idx) {
+                push_milestone(&mut out, milestone, m_idx);
+            }
+        }
+    }
+
+    let unlinked: Vec<(usize, &Milestone)> = project
+        .milestones
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| m.task_index.is_none_or(|i| i >= project.tasks.len()))
+        .collect();
+    if !unlinked.is_empty() {
+        out.push(String::new());
+        out.push(format!("    section {}", MILESTONES_SECTION));
+        for (m_idx, milestone) in unlinked {
+            push_milestone(&mut out, milestone, m_idx);
+        }
+    }
+
+    if !project.pause_periods.is_empty() {
+        out.push(String::new());
+        out.push(format!("    section {}", PAUSES_SECTION));
+        for (idx, pause) in project.pause_periods.iter().enumerate() {
+            let label = pause.label.as_deref().unwrap_or("Pause");
+            // A label that reads "Pause" would otherwise come back as no label
+            if let Some(label) = &pause.label {
+                push_name(&mut out, label, label == "Pause");
+            }
+            out.push(format!(
+                "    {} :done, p{}, {}, {}",
+                task_label(label),
+                idx + 1,
+                pause.start,
+                pause.end
+            ));
+        }
+    }
+
+    out.push(String::new());
+    out.join("\n")
+}
+
+/// Read a mermaid `gantt` block back into the project model.
+///
+/// Understands the layout written by `to_mermaid`, and also plain mermaid
+/// charts where each `id, start, end` or `start, end` task line becomes a task.
+/// Duration-only items (`after a1, 3d`) cannot be placed on dates and are
+/// rejected rather than guessed.
+pub fn parse_mermaid(text: &str) -> Result<Project, String> {
+    let mut project = Project::default();
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .enumerate()
+        .filter(|(_, l)| !l.is_empty() && !l.starts_with("```"));
+
+    match lines.next() {
+        Some((_, "gantt")) => {}
+        _ => return Err("Not a mermaid gantt chart (expected a `gantt` header)".to_string()),
+    }
+
+    let mut section = String::new();
+    // Task that the current section's milestones and metadata belong to
+    let mut section_task: Option<usize> = None;
+    let mut pending_hours = 0.0;
+    let mut pending_subtasks: Vec<String> = Vec::new();
+    let mut pending_color: Option<String> = None;
+    let mut pending_color_index: Option<usize> = None;
+    // Original name of the next item, when its label had to drop characters
+    let mut pending_name: Option<String> = None;
+    let mut title: Option<String> = None;
+
+    for (line_no, line) in lines {
+        if let Some(meta) = line.strip_prefix(META_PREFIX) {
+            let (key, value) = meta.split_once(' ').unwrap_or((meta, ""));
+            match key {
+                "timeline" => {
+                    let mut bounds = value.split_whitespace();
+                    project.timeline_start = bounds.next().unwrap_or("").to_string();
+                    project.timeline_end = bounds.next().unwrap_or("").to_string();
+                }
+                "title" => title = Some(read_meta_text(value)),
+                "hideMilestones" => project.show_milestones = false,
+                "palette" => project.palette = value.split_whitespace().map(String::from).collect(),
+                "hours" => pending_hours = value.trim().parse().unwrap_or(0.0),
+                "colorIndex" => pending_color_index = value.trim().parse().ok(),
+                "color" => pending_color = Some(value.trim().to_string()),
+                "subtask" => pending_subtasks.push(read_meta_text(value)),
+                "name" => pending_name = Some(read_meta_text(value)),
+                _ => {}
+            }
+            continue;
+        }
+        if line.starts_with("%%") {
+            continue;
+        }
+        if let Some(title) = line.strip_prefix("title ") {
+            project.title = title.trim().to_string();
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("section ") {
+            section = name.trim().to_string();
+            section_task = None;
+            continue;
+        }
+
+        let Some((label, spec)) = line.split_once(':') else {
+            // dateFormat, axisFormat, excludes and the other directives
+            continue;
+        };
+        let label = label.trim().to_string();
+        let item = parse_item(spec)
+            .ok_or_else(|| format!("Line {}: cannot read dates from \"{}\"", line_no + 1, line))?;
+        let original = pending_name.take();
+
+        if item.milestone {
+            project.milestones.push(Milestone {
+                name: original.unwrap_or(label),
+                date: item.start,
+                task_index: section_task,
+            });
+        } else if section == PAUSES_SECTION {
+            project.pause_periods.push(PausePeriod {
+                start: item.start,
+                end: item.end,
+                label: original.or((label != "Pause").then_some(label)),
+            });
+        } else {
+            // Sections in hand-edited charts are often named after their task, and
+            // keep characters the bar label had to drop
+            let section = section.strip_suffix(TASK_SECTION_SUFFIX).unwrap_or(&section);
+            let name = match original {
+                Some(name) => name,
+                None if label == task_label(section) => section.to_string(),
+                None => label,
+            };
+            project.tasks.push(Task {
+                name,
+                start: item.start,
+                end: item.end,
+                hours: std::mem::take(&mut pending_hours),
+                subtasks: std::mem::take(&mut pending_subtasks),
+                color: pending_color.take(),
+                color_index: pending_color_index.take(),
+            });
+            section_task = Some(project.tasks.len() - 1);
+        }
+    }
+
+    if let Some(title) = title {
+        project.title = title;
+    }
+    if project.timeline_start.is_empty() {
+        project.timeline_start = project.tasks.iter().map(|t| t.start.clone()).min().unwrap_or_default();
+    }
+    if project.timeline_end.is_empty() {
+        project.timeline_end = project.tasks.iter().map(|t| t.end.clone()).max().unwrap_or_default();
+    }
+    project.resolve_colors();
+    Ok(project)
+}
+
+struct Item {
+    start: String,
+    end: String,
+    milestone: bool,
+}
+
+/// Parse the part of a task line after the colon: tags, an optional id, then dates
+fn parse_item(spec: &str) -> Option<Item> {
+    let mut milestone = false;
+    let mut dates = Vec::new();
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match part {
+            "milestone" => milestone = true,
+            "done" | "active" | "crit" => {}
+            _ => {
+                if let Some(date) = normalize_date(part) {
+                    dates.push(date);
+                } else if is_duration(part) && milestone {
+                    // Milestones are written with a 0d duration
+                } else if dates.is_empty() && !is_duration(part) && !part.starts_with("after ") {
+                    // Task id
+                } else {
+                    return None;
+                }
+            }
+        }
+    }
+
+    let start = dates.first()?.clone();
+    let end = if milestone { start.clone() } else { dates.get(1)?.clone() };
+    Some(Item {
+        start,
+        end,
+        milestone,
+    })
+}
+
+fn is_duration(part: &str) -> bool {
+    let digits = part.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+    !digits.is_empty() && digits.len() < part.len() && digits.parse::<f64>().is_ok()
+}
+
+fn push_milestone(out: &mut Vec<String>, milestone: &Milestone, idx: usize) {
+    push_name(out, &milestone.name, false);
+    out.push(format!(
+        "    {} :milestone, m{}, {}, 0d",
+        task_label(&milestone.name),
+        idx + 1,
+        milestone.date
+    ));
+}
+
+/// Keep a name whose label loses characters (or when `always`) for the next item line
+fn push_name(out: &mut Vec<String>, name: &str, always: bool) {
+    if always || task_label(name) != name {
+        out.push(format!("    {}name {}", META_PREFIX, meta_text(name)));
+    }
+}
+
+/// A task's section, renamed when it would read back as a milestone or pause section
+fn section_label(name: &str) -> String {
+    let label = single_line(name);
+    if label == MILESTONES_SECTION || label == PAUSES_SECTION {
+        label + TASK_SECTION_SUFFIX
+    } else {
+        label
+    }
+}
+
+/// Metadata text, as a JSON string when line breaks or edge spaces must survive
+fn meta_text(text: &str) -> String {
+    if text.contains(['\n', '\r']) || text.trim() != text || text.starts_with('"') {
+        serde_json::to_string(text).unwrap_or_default()
+    } else {
+        text.to_string()
+    }
+}
+
+fn read_meta_text(value: &str) -> String {
+    let value = value.trim();
+    if value.starts_with('"') {
+        if let Ok(text) = serde_json::from_str(value) {
+            return text;
+        }
+    }
+    value.to_string()
+}
+
+/// Collapse line breaks, which end a statement in mermaid
+fn single_line(text: &str) -> String {
+    text.split(['\n', '\r'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Task labels end at the first colon, and `#`/`;` start comments and entities
+fn task_label(text: &str) -> String {
+    single_line(text)
+        .replace([':', ';', '#'], " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(project: &Project) -> serde_json::Value {
+        serde_json::to_value(project).unwrap()
+    }
+
+    #[test]
+    fn round_trip_keeps_names_labels_cannot_hold() {
+        let mut project = Project {
+            title: "Roll-out\nphase 2".to_string(),
+            timeline_start: "2025-01-06".to_string(),
+            timeline_end: "2025-06-30".to_string(),
+            show_milestones: false,
+            palette: vec!["#0B5394".to_string(), "#E69138".to_string()],
+            tasks: vec![
+                Task {
+                    name: "Pause Periods".to_string(),
+                    start: "2025-01-06".to_string(),
+                    end: "2025-02-14".to_string(),
+                    hours: 12.5,
+                    subtasks: vec!["Plan: scope; #1".to_string(), " two\nlines ".to_string()],
+                    color: None,
+                    color_index: Some(1),
+                },
+                Task {
+                    name: "Milestones".to_string(),
+                    start: "2025-02-17".to_string(),
+                    end: "2025-03-28".to_string(),
+                    hours: 0.0,
+                    subtasks: Vec::new(),
+                    color: Some("#123456".to_string()),
+                    color_index: None,
+                },
+                Task {
+                    name: "Build: API #2; UI".to_string(),
+                    start: "2025-03-31".to_string(),
+                    end: "2025-05-30".to_string(),
+                    ..Task::default()
+                },
+            ],
+            milestones: vec![
+                Milestone {
+                    name: "Sign-off\nby client".to_string(),
+                    date: "2025-02-14".to_string(),
+                    task_index: Some(0),
+                },
+                Milestone {
+                    name: "Go live: v1".to_string(),
+                    date: "2025-06-02".to_string(),
+                    task_index: None,
+                },
+            ],
+            pause_periods: vec![
+                PausePeriod {
+                    start: "2025-04-18".to_string(),
+                    end: "2025-04-21".to_string(),
+                    label: Some("Pause".to_string()),
+                },
+                PausePeriod {
+                    start: "2025-05-05".to_string(),
+                    end: "2025-05-06".to_string(),
+                    label: None,
+                },
+            ],
+        };
+        project.resolve_colors();
+
+        let read = parse_mermaid(&to_mermaid(&project)).unwrap();
+        assert_eq!(json(&read), json(&project));
+    }
+
+    #[test]
+    fn task_sections_never_read_back_as_pauses() {
+        let project = Project {
+            tasks: vec![Task {
+                name: "Pause Periods".to_string(),
+                start: "2025-01-06".to_string(),
+                end: "2025-01-10".to_string(),
+                ..Task::default()
+            }],
+            ..Project::default()
+        };
+        let text = to_mermaid(&project);
+        assert!(text.contains("section Pause Periods (task)"));
+        let read = parse_mermaid(&text).unwrap();
+        assert_eq!(read.tasks.len(), 1);
+        assert!(read.pause_periods.is_empty());
+    }
+}
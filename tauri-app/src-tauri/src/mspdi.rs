@@ -0,0 +1,448 @@
+use crate::project::{parse_date, Project};
+use crate::xml::escape_xml;
+use chrono::{Datelike, NaiveDate, Weekday};
+
+/// Working day used for durations and calendar times (8:00–12:00, 13:00–17:00)
+const HOURS_PER_DAY: i64 = 8;
+
+/// UID of the single resource that carries task work
+const TEAM_RESOURCE_UID: usize = 1;
+
+/// Write a project as Microsoft Project XML (MSPDI).
+///
+/// Tasks are manually scheduled so Project keeps the chart's dates instead of
+/// re-levelling them. Subtasks become child tasks spanning their parent's
+/// range, milestones follow the task they are linked to, and pause periods are
+/// non-working exceptions on the standard calendar. Hours are booked as work
+/// on a single "Team" resource, since Project derives task work from
+/// assignments.
+pub fn to_mspdi(project: &Project) -> Result<String, String> {
+    let pauses = project
+        .pause_periods
+        .iter()
+        .map(|p| {
+            let start = parse_date(&p.start, "pause period start")?;
+            let end = parse_date(&p.end, "pause period end")?;
+            Ok((start, end, p.label.clone().unwrap_or_else(|| "Pause".to_string())))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    let calendar = WorkCalendar {
+        pauses: pauses.iter().map(|(s, e, _)| (*s, *e)).collect(),
+    };
+
+    let mut rows: Vec<Row> = Vec::new();
+    let mut top_level = 0;
+    for (idx, task) in project.tasks.iter().enumerate() {
+        let start = parse_date(&task.start, &format!("start of task \"{}\"", task.name))?;
+        let end = parse_date(&task.end, &format!("end of task \"{}\"", task.name))?;
+        top_level += 1;
+        rows.push(Row {
+            name: task.name.clone(),
+            outline: vec![top_level],
+            start,
+            end,
+            work_hours: task.hours,
+            milestone: false,
+            summary: !task.subtasks.is_empty(),
+        });
+
+        // Child tasks share the parent's range and split its hours evenly
+        let child_hours = task.hours / task.subtasks.len().max(1) as f64;
+        for (s_idx, subtask) in task.subtasks.iter().enumerate() {
+            rows.push(Row {
+                name: subtask.clone(),
+                outline: vec![top_level, s_idx + 1],
+                start,
+                end,
+                work_hours: child_hours,
+                milestone: false,
+                summary: false,
+            });
+        }
+
+        for milestone in project.milestones.iter().filter(|m| m.task_index == Some(idx)) {
+            top_level += 1;
+            rows.push(milestone_row(&milestone.name, &milestone.date, top_level)?);
+        }
+    }
+    for milestone in project
+        .milestones
+        .iter()
+        .filter(|m| m.task_index.is_none_or(|i| i >= project.tasks.len()))
+    {
+        top_level += 1;
+        rows.push(milestone_row(&milestone.name, &milestone.date, top_level)?);
+    }
+
+    let project_start = rows
+        .iter()
+        .map(|r| r.start)
+        .chain(parse_date(&project.timeline_start, "timeline start").ok())
+        .min();
+    let project_finish = rows
+        .iter()
+        .map(|r| r.end)
+        .chain(parse_date(&project.timeline_end, "timeline end").ok())
+        .max();
+    let (Some(project_start), Some(project_finish)) = (project_start, project_finish) else {
+        return Err("Project has no dated tasks or milestones to export".to_string());
+    };
+
+    let mut xml = String::new();
+    xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n");
+    xml.push_str("<Project xmlns=\"http://schemas.microsoft.com/project\">\n");
+    push_element(&mut xml, 1, "SaveVersion", "14");
+    push_element(&mut xml, 1, "Name", &format!("{}.xml", project.title));
+    push_element(&mut xml, 1, "Title", &project.title);
+    push_element(&mut xml, 1, "ScheduleFromStart", "1");
+    push_element(&mut xml, 1, "StartDate", &day_start(project_start));
+    push_element(&mut xml, 1, "FinishDate", &day_finish(project_finish));
+    push_element(&mut xml, 1, "CalendarUID", "1");
+    push_element(&mut xml, 1, "DefaultStartTime", "08:00:00");
+    push_element(&mut xml, 1, "DefaultFinishTime", "17:00:00");
+    push_element(&mut xml, 1, "MinutesPerDay", &(HOURS_PER_DAY * 60).to_string());
+    push_element(&mut xml, 1, "MinutesPerWeek", &(HOURS_PER_DAY * 60 * 5).to_string());
+    push_element(&mut xml, 1, "DaysPerMonth", "20");
+    push_element(&mut xml, 1, "NewTasksAreManual", "1");
+
+    write_calendar(&mut xml, &pauses);
+
+    xml.push_str("  <Tasks>\n");
+    // Task 0 is the project summary task Project expects at the top of the outline
+    xml.push_str("    <Task>\n");
+    push_element(&mut xml, 3, "UID", "0");
+    push_element(&mut xml, 3, "ID", "0");
+    push_element(&mut xml, 3, "Name", &project.title);
+    push_element(&mut xml, 3, "OutlineNumber", "0");
+    push_element(&mut xml, 3, "OutlineLevel", "0");
+    push_element(&mut xml, 3, "Start", &day_start(project_start));
+    push_element(&mut xml, 3, "Finish", &day_finish(project_finish));
+    push_element(&mut xml, 3, "Summary", "1");
+    xml.push_str("    </Task>\n");
+    for (idx, row) in rows.iter().enumerate() {
+        write_task(&mut xml, idx + 1, row, &calendar);
+    }
+    xml.push_str("  </Tasks>\n");
+
+    xml.push_str("  <Resources>\n    <Resource>\n");
+    push_element(&mut xml, 3, "UID", &TEAM_RESOURCE_UID.to_string());
+    push_element(&mut xml, 3, "ID", "1");
+    push_element(&mut xml, 3, "Name", "Team");
+    push_element(&mut xml, 3, "Type", "1");
+    push_element(&mut xml, 3, "CalendarUID", "1");
+    xml.push_str("    </Resource>\n  </Resources>\n");
+
+    xml.push_str("  <Assignments>\n");
+    let mut assignment_uid = 0;
+    for (idx, row) in rows.iter().enumerate() {
+        if row.summary || row.milestone || row.work_hours <= 0.0 {
+            continue;
+        }
+        assignment_uid += 1;
+        let duration_hours = calendar.working_hours(row.start, row.end);
+        let units = if duration_hours > 0 {
+            row.work_hours / duration_hours as f64
+        } else {
+            1.0
+        };
+        xml.push_str("    <Assignment>\n");
+        push_element(&mut xml, 3, "UID", &assignment_uid.to_string());
+        push_element(&mut xml, 3, "TaskUID", &(idx + 1).to_string());
+        push_element(&mut xml, 3, "ResourceUID", &TEAM_RESOURCE_UID.to_string());
+        push_element(&mut xml, 3, "Finish", &day_finish(row.end));
+        push_element(&mut xml, 3, "Start", &day_start(row.start));
+        push_element(&mut xml, 3, "Units", &format!("{:.4}", units));
+        push_element(&mut xml, 3, "Work", &work_duration(row.work_hours));
+        xml.push_str("    </Assignment>\n");
+    }
+    xml.push_str("  </Assignments>\n");
+
+    xml.push_str("</Project>\n");
+    Ok(xml)
+}
+
+/// One line of the Project outline
+struct Row {
+    name: String,
+    outline: Vec<usize>,
+    start: NaiveDate,
+    end: NaiveDate,
+    work_hours: f64,
+    milestone: bool,
+    summary: bool,
+}
+
+fn milestone_row(name: &str, date: &str, top_level: usize) -> Result<Row, String> {
+    let name = name.replace('\n', " ");
+    let date = parse_date(date, &format!("date of milestone \"{}\"", name))?;
+    Ok(Row {
+        name,
+        outline: vec![top_level],
+        start: date,
+        end: date,
+        work_hours: 0.0,
+        milestone: true,
+        summary: false,
+    })
+}
+
+/// Weekdays are working days unless they fall in a pause period
+struct WorkCalendar {
+    pauses: Vec<(NaiveDate, NaiveDate)>,
+}
+
+impl WorkCalendar {
+    fn is_working(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+            && !self.pauses.iter().any(|(s, e)| *s <= date && date <= *e)
+    }
+
+    fn working_hours(&self, start: NaiveDate, end: NaiveDate) -> i64 {
+        start
+            .iter_days()
+            .take_while(|d| *d <= end)
+            .filter(|d| self.is_working(*d))
+            .count() as i64
+            * HOURS_PER_DAY
+    }
+}
+
+fn write_calendar(xml: &mut String, pauses: &[(NaiveDate, NaiveDate, String)]) {
+    xml.push_str("  <Calendars>\n    <Calendar>\n");
+    push_element(xml, 3, "UID", "1");
+    push_element(xml, 3, "Name", "Standard");
+    push_element(xml, 3, "IsBaseCalendar", "1");
+    push_element(xml, 3, "BaseCalendarUID", "-1");
+
+    xml.push_str("      <WeekDays>\n");
+    // DayType 1 = Sunday ... 7 = Saturday
+    for day_type in 1..=7 {
+        let working = (2..=6).contains(&day_type);
+        xml.push_str("        <WeekDay>\n");
+        push_element(xml, 5, "DayType", &day_type.to_string());
+        push_element(xml, 5, "DayWorking", if working { "1" } else { "0" });
+        if working {
+            xml.push_str("          <WorkingTimes>\n");
+            for (from, to) in [("08:00:00", "12:00:00"), ("13:00:00", "17:00:00")] {
+                xml.push_str("            <WorkingTime>\n");
+                push_element(xml, 7, "FromTime", from);
+                push_element(xml, 7, "ToTime", to);
+                xml.push_str("            </WorkingTime>\n");
+            }
+            xml.push_str("          </WorkingTimes>\n");
+        }
+        xml.push_str("        </WeekDay>\n");
+    }
+    // Project 2003 and most other tools read exceptions as DayType 0 entries
+    for (start, end, _) in pauses {
+        xml.push_str("        <WeekDay>\n");
+        push_element(xml, 5, "DayType", "0");
+        push_element(xml, 5, "DayWorking", "0");
+        xml.push_str("          <TimePeriod>\n");
+        push_element(xml, 6, "FromDate", &format!("{}T00:00:00", start.format("%Y-%m-%d")));
+        push_element(xml, 6, "ToDate", &format!("{}T23:59:00", end.format("%Y-%m-%d")));
+        xml.push_str("          </TimePeriod>\n");
+        xml.push_str("        </WeekDay>\n");
+    }
+    xml.push_str("      </WeekDays>\n");
+
+    // Project 2007 and later read the dedicated Exceptions element
+    if !pauses.is_empty() {
+        xml.push_str("      <Exceptions>\n");
+        for (start, end, label) in pauses {
+            xml.push_str("        <Exception>\n");
+            push_element(xml, 5, "EnteredByOccurrences", "0");
+            xml.push_str("          <TimePeriod>\n");
+            push_element(xml, 6, "FromDate", &format!("{}T00:00:00", start.format("%Y-%m-%d")));
+            push_element(xml, 6, "ToDate", &format!("{}T23:59:00", end.format("%Y-%m-%d")));
+            xml.push_str("          </TimePeriod>\n");
+            push_element(xml, 5, "Occurrences", "1");
+            push_element(xml, 5, "Name", label);
+            push_element(xml, 5, "Type", "1");
+            push_element(xml, 5, "DayWorking", "0");
+            xml.push_str("        </Exception>\n");
+        }
+        xml.push_str("      </Exceptions>\n");
+    }
+    xml.push_str("    </Calendar>\n  </Calendars>\n");
+}
+
+fn write_task(xml: &mut String, uid: usize, row: &Row, calendar: &WorkCalendar) {
+    let outline_number = row
+        .outline
+        .iter()
+        .map(|n| n.to_string())
+        .collect::<Vec<_>>()
+        .join(".");
+    let duration_hours = if row.milestone {
+        0
+    } else {
+        calendar.working_hours(row.start, row.end)
+    };
+
+    let (start, finish) = if row.milestone {
+        // Milestones sit at the end of their day, like the chart's connector
+        (day_finish(row.start), day_finish(row.end))
+    } else {
+        (day_start(row.start), day_finish(row.end))
+    };
+    let duration = work_duration(duration_hours as f64);
+
+    // Elements follow the order of the MSPDI schema sequence
+    xml.push_str("    <Task>\n");
+    push_element(xml, 3, "UID", &uid.to_string());
+    push_element(xml, 3, "ID", &uid.to_string());
+    push_element(xml, 3, "Name", &row.name);
+    push_element(xml, 3, "Manual", "1");
+    // Type 1 = fixed duration
+    push_element(xml, 3, "Type", "1");
+    push_element(xml, 3, "IsNull", "0");
+    push_element(xml, 3, "WBS", &outline_number);
+    push_element(xml, 3, "OutlineNumber", &outline_number);
+    push_element(xml, 3, "OutlineLevel", &row.outline.len().to_string());
+    push_element(xml, 3, "Start", &start);
+    push_element(xml, 3, "Finish", &finish);
+    push_element(xml, 3, "Duration", &duration);
+    push_element(xml, 3, "ManualStart", &start);
+    push_element(xml, 3, "ManualFinish", &finish);
+    push_element(xml, 3, "ManualDuration", &duration);
+    // DurationFormat 7 = days
+    push_element(xml, 3, "DurationFormat", "7");
+    push_element(xml, 3, "Work", &work_duration(row.work_hours));
+    push_element(xml, 3, "Milestone", if row.milestone { "1" } else { "0" });
+    push_element(xml, 3, "Summary", if row.summary { "1" } else { "0" });
+    push_element(xml, 3, "Critical", "0");
+    // ConstraintType 4 = Start No Earlier Than, so switching to auto-schedule keeps dates
+    push_element(xml, 3, "ConstraintType", "4");
+    push_element(xml, 3, "CalendarUID", "-1");
+    push_element(xml, 3, "ConstraintDate", &day_start(row.start));
+    xml.push_str("    </Task>\n");
+}
+
+fn day_start(date: NaiveDate) -> String {
+    format!("{}T08:00:00", date.format("%Y-%m-%d"))
+}
+
+fn day_finish(date: NaiveDate) -> String {
+    format!("{}T17:00:00", date.format("%Y-%m-%d"))
+}
+
+/// ISO 8601 duration in the PTnHnMnS form Project writes
+fn work_duration(hours: f64) -> String {
+    let minutes = (hours * 60.0).round() as i64;
+    format!("PT{}H{}M0S", minutes / 60, minutes % 60)
+}
+
+fn push_element(xml: &mut String, depth: usize, name: &str, value: &str) {
+    xml.push_str(&"  ".repeat(depth));
+    xml.push_str(&format!("<{}>{}</{}>\n", name, escape_xml(value), name));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::{Milestone, PausePeriod, Task};
+
+    fn sample() -> Project {
+        Project {
+            title: "Audit & Review".to_string(),
+            timeline_start: "2025-01-06".to_string(),
+            timeline_end: "2025-01-17".to_string(),
+            tasks: vec![
+                Task {
+                    name: "Planning".to_string(),
+                    start: "2025-01-06".to_string(),
+                    end: "2025-01-10".to_string(),
+                    hours: 40.0,
+                    subtasks: vec!["Scope".to_string(), "Interviews".to_string()],
+                    ..Default::default()
+                },
+                Task {
+                    name: "Report".to_string(),
+                    start: "2025-01-13".to_string(),
+                    end: "2025-01-17".to_string(),
+                    hours: 10.0,
+                    ..Default::default()
+                },
+            ],
+            milestones: vec![Milestone {
+                name: "Plan\nagreed".to_string(),
+                date: "2025-01-10".to_string(),
+                task_index: Some(0),
+            }],
+            pause_periods: vec![PausePeriod {
+                start: "2025-01-08".to_string(),
+                end: "2025-01-08".to_string(),
+                label: Some("Offsite".to_string()),
+            }],
+            ..Default::default()
+        }
+    }
+
+    /// Contents of every `<name>` block in document order
+    fn blocks<'a>(xml: &'a str, name: &str) -> Vec<&'a str> {
+        let open = format!("<{}>", name);
+        let close = format!("</{}>", name);
+        xml.split(open.as_str())
+            .skip(1)
+            .map(|rest| &rest[..rest.find(close.as_str()).unwrap_or(rest.len())])
+            .collect()
+    }
+
+    fn value<'a>(block: &'a str, name: &str) -> &'a str {
+        blocks(block, name).first().copied().unwrap_or_default()
+    }
+
+    #[test]
+    fn writes_outline_milestones_work_and_pauses() {
+        let xml = to_mspdi(&sample()).unwrap();
+        assert!(xml.contains("<Title>Audit &amp; Review</Title>"));
+
+        let tasks = blocks(&xml, "Task");
+        let names: Vec<_> = tasks.iter().map(|t| value(t, "Name")).collect();
+        assert_eq!(names, ["Audit &amp; Review", "Planning", "Scope", "Interviews", "Plan agreed", "Report"]);
+        let outline: Vec<_> = tasks.iter().map(|t| (value(t, "OutlineNumber"), value(t, "OutlineLevel"))).collect();
+        assert_eq!(outline, [("0", "0"), ("1", "1"), ("1.1", "2"), ("1.2", "2"), ("2", "1"), ("3", "1")]);
+        assert_eq!(value(tasks[1], "Summary"), "1");
+
+        // Five weekdays minus the paused Wednesday
+        assert_eq!(value(tasks[1], "Duration"), "PT32H0M0S");
+        assert_eq!(value(tasks[1], "Work"), "PT40H0M0S");
+        assert_eq!(value(tasks[2], "Work"), "PT20H0M0S");
+        assert_eq!(value(tasks[5], "Work"), "PT10H0M0S");
+
+        let milestone = tasks[4];
+        assert_eq!(value(milestone, "Milestone"), "1");
+        assert_eq!(value(milestone, "Duration"), "PT0H0M0S");
+        assert_eq!(value(milestone, "Work"), "PT0H0M0S");
+        assert_eq!(value(milestone, "Start"), "2025-01-10T17:00:00");
+
+        // Work is booked on the leaves only
+        let assignments = blocks(&xml, "Assignment");
+        let booked: Vec<_> = assignments
+            .iter()
+            .map(|a| (value(a, "TaskUID"), value(a, "Work"), value(a, "Units")))
+            .collect();
+        assert_eq!(
+            booked,
+            [("2", "PT20H0M0S", "0.6250"), ("3", "PT20H0M0S", "0.6250"), ("5", "PT10H0M0S", "0.2500")]
+        );
+
+        let exceptions = blocks(&xml, "Exception");
+        assert_eq!(exceptions.len(), 1);
+        assert_eq!(value(exceptions[0], "FromDate"), "2025-01-08T00:00:00");
+        assert_eq!(value(exceptions[0], "ToDate"), "2025-01-08T23:59:00");
+        assert_eq!(value(exceptions[0], "Name"), "Offsite");
+        assert_eq!(value(exceptions[0], "DayWorking"), "0");
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        let mut project = sample();
+        project.tasks[1].end = "17/01/2025".to_string();
+        assert_eq!(
+            to_mspdi(&project).unwrap_err(),
+            "Invalid end of task \"Report\" date: \"17/01/2025\" (expected YYYY-MM-DD)"
+        );
+    }
+}
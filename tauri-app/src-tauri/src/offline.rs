@@ -0,0 +1,730 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine as _;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::time::Duration;
+
+/// How deep stylesheets may `@import` each other before it is treated as a cycle
+const MAX_IMPORT_DEPTH: usize = 8;
+/// Longest a single remote reference may take to download
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// What a reference is inlined as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Kind {
+    /// Image, font or other file, inlined as a data URI
+    Resource,
+    /// `<link rel="stylesheet">` or `@import`, inlined as CSS text
+    Stylesheet,
+    /// `<script src>`, inlined as the script body
+    Script,
+}
+
+/// Where relative references are looked up
+#[derive(Debug, Clone)]
+enum Base {
+    /// The first directory holding the file wins
+    Dirs(Vec<PathBuf>),
+    Remote(reqwest::Url),
+}
+
+enum Location {
+    File(PathBuf),
+    Remote(reqwest::Url),
+}
+
+/// Make an HTML document self-contained: stylesheets and scripts are inlined
+/// as text, images and fonts (including those referenced from CSS) as data
+/// URIs. Relative references are looked up in `search_dirs` in order; remote
+/// ones are downloaded. Fails if a reference cannot be loaded or if anything
+/// but a hyperlink still points outside the document afterwards.
+pub async fn inline_resources(html: &str, search_dirs: &[PathBuf]) -> Result<String, String> {
+    let client = reqwest::Client::builder()
+        .timeout(DOWNLOAD_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let base = Base::Dirs(search_dirs.to_vec());
+
+    let mut references = Vec::new();
+    rewrite_html(html, &mut |url, kind| {
+        references.push((url.to_string(), kind));
+        None
+    });
+    let inlined = inline_all(&client, references, &base, 0).await?;
+    let output = rewrite_html(html, &mut |url, kind| inlined.get(&(url.to_string(), kind)).cloned());
+
+    let remaining = external_references(&output);
+    if !remaining.is_empty() {
+        return Err(format!(
+            "Self-contained HTML still references external files: {}",
+            remaining.join(", ")
+        ));
+    }
+    Ok(output)
+}
+
+/// Every reference in an HTML document that would load something from outside it
+pub fn external_references(html: &str) -> Vec<String> {
+    let mut remaining = Vec::new();
+    rewrite_html(html, &mut |url, _| {
+        remaining.push(url.to_string());
+        None
+    });
+    remaining.dedup();
+    remaining
+}
+
+type Inlined<'a> = Pin<Box<dyn Future<Output = Result<String, String>> + Send + 'a>>;
+
+/// Load a reference and turn it into its inline form
+fn inline<'a>(client: &'a reqwest::Client, url: &'a str, kind: Kind, base: &'a Base, depth: usize) -> Inlined<'a> {
+    Box::pin(async move {
+        let location = resolve(base, url)?;
+        let (bytes, mime) = load(client, &location).await?;
+        match kind {
+            Kind::Resource => Ok(format!("data:{};base64,{}", mime, STANDARD.encode(bytes))),
+            Kind::Script => Ok(escape_end_tag(&String::from_utf8_lossy(&bytes), "script")),
+            Kind::Stylesheet => {
+                if depth >= MAX_IMPORT_DEPTH {
+                    return Err(format!("Stylesheet imports nest too deeply at {}", url));
+                }
+                let css = String::from_utf8_lossy(&bytes).into_owned();
+                let css_base = match location {
+                    Location::File(path) => Base::Dirs(path.parent().map(Path::to_path_buf).into_iter().collect()),
+                    Location::Remote(url) => Base::Remote(url),
+                };
+                let css = inline_css(client, &css, &css_base, depth + 1).await?;
+                Ok(escape_end_tag(&css, "style"))
+            }
+        }
+    })
+}
+
+/// Inline form of each distinct reference
+async fn inline_all(
+    client: &reqwest::Client,
+    references: Vec<(String, Kind)>,
+    base: &Base,
+    depth: usize,
+) -> Result<HashMap<(String, Kind), String>, String> {
+    let mut inlined = HashMap::new();
+    for (url, kind) in references {
+        if let Entry::Vacant(entry) = inlined.entry((url.clone(), kind)) {
+            entry.insert(inline(client, &url, kind, base, depth).await?);
+        }
+    }
+    Ok(inlined)
+}
+
+/// Inline everything a stylesheet references
+async fn inline_css(client: &reqwest::Client, css: &str, base: &Base, depth: usize) -> Result<String, String> {
+    let mut references = Vec::new();
+    rewrite_css(css, &mut |url, kind| {
+        references.push((url.to_string(), kind));
+        None
+    });
+    let inlined = inline_all(client, references, base, depth).await?;
+    Ok(rewrite_css(css, &mut |url, kind| inlined.get(&(url.to_string(), kind)).cloned()))
+}
+
+fn resolve(base: &Base, url: &str) -> Result<Location, String> {
+    let absolute = if url.starts_with("//") {
+        reqwest::Url::parse(&format!("https:{}", url)).ok()
+    } else {
+        reqwest::Url::parse(url).ok().filter(|u| u.scheme().len() > 1)
+    };
+    if let Some(absolute) = absolute {
+        return match absolute.scheme() {
+            "http" | "https" => Ok(Location::Remote(absolute)),
+            "file" => absolute
+                .to_file_path()
+                .map(Location::File)
+                .map_err(|_| format!("Invalid file URL: {}", url)),
+            other => Err(format!("Cannot inline {} (unsupported scheme \"{}\")", url, other)),
+        };
+    }
+    match base {
+        Base::Remote(base_url) => base_url
+            .join(url)
+            .map(Location::Remote)
+            .map_err(|e| format!("Invalid URL {}: {}", url, e)),
+        Base::Dirs(dirs) => {
+            let path = percent_decode(url.split(['?', '#']).next().unwrap_or(url));
+            dirs.iter()
+                .map(|dir| dir.join(&path))
+                .find(|candidate| candidate.is_file())
+                .map(Location::File)
+                .ok_or_else(|| {
+                    format!(
+                        "Cannot inline {}: not found in {}",
+                        url,
+                        dirs.iter().map(|d| d.display().to_string()).collect::<Vec<_>>().join(", ")
+                    )
+                })
+        }
+    }
+}
+
+async fn load(client: &reqwest::Client, location: &Location) -> Result<(Vec<u8>, String), String> {
+    match location {
+        Location::File(path) => {
+            let bytes = tokio::fs::read(path)
+                .await
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            Ok((bytes, mime_type(path.to_string_lossy().as_ref()).to_string()))
+        }
+        Location::Remote(url) => {
+            let response = client
+                .get(url.clone())
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+            let mime = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.split(';').next())
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty() && v != "application/octet-stream")
+                .unwrap_or_else(|| mime_type(url.path()).to_string());
+            let bytes = response
+                .bytes()
+                .await
+                .map_err(|e| format!("Failed to read download for {}: {}", url, e))?;
+            Ok((bytes.to_vec(), mime))
+        }
+    }
+}
+
+/// Decode `%XX` escapes of a relative URL into the file path they stand for
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Keep inlined text from closing its `<script>` or `<style>` element early.
+/// `<\/` means the same as `</` inside JavaScript strings and CSS alike.
+fn escape_end_tag(text: &str, name: &str) -> String {
+    let needle = format!("</{}", name);
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = find_ignore_case(rest, &needle) {
+        out.push_str(&rest[..i]);
+        out.push_str("<\\/");
+        rest = &rest[i + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn mime_type(path: &str) -> &'static str {
+    let extension = path.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+    match extension.as_str() {
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "otf" => "font/otf",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        _ => "application/octet-stream",
+    }
+}
+
+/// References that already live inside the document
+fn is_inline(url: &str) -> bool {
+    let lower = url.trim().to_ascii_lowercase();
+    lower.is_empty()
+        || lower.starts_with('#')
+        || lower.starts_with("data:")
+        || lower.starts_with("about:")
+        || lower.starts_with("javascript:")
+}
+
+/// Pass every external reference in an HTML document to `map` and splice in
+/// what it returns; `None` leaves the reference as it is. Script bodies are
+/// copied untouched and `<a href>` links are not references.
+fn rewrite_html(html: &str, map: &mut dyn FnMut(&str, Kind) -> Option<String>) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(lt) = rest.find('<') {
+        out.push_str(&rest[..lt]);
+        rest = &rest[lt..];
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map(|i| i + 3).unwrap_or(rest.len());
+            out.push_str(&rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+        let Some(tag) = Tag::parse(rest) else {
+            out.push('<');
+            rest = &rest[1..];
+            continue;
+        };
+        let raw = &rest[..tag.len];
+        rest = &rest[tag.len..];
+        match tag.name.as_str() {
+            "script" | "style" => {
+                let close = find_ignore_case(rest, &format!("</{}", tag.name)).unwrap_or(rest.len());
+                let body = &rest[..close];
+                rest = &rest[close..];
+                if tag.name == "style" {
+                    out.push_str(raw);
+                    out.push_str(&rewrite_css(body, map));
+                } else if let Some(code) = tag.attr("src").filter(|src| !is_inline(src)).and_then(|src| map(src, Kind::Script)) {
+                    out.push_str(&tag.render(&[("src", None)]));
+                    out.push_str(&code);
+                } else {
+                    out.push_str(raw);
+                    out.push_str(body);
+                }
+            }
+            "link" => {
+                let rel = tag.attr("rel").unwrap_or("").to_ascii_lowercase();
+                let href = tag.attr("href").filter(|href| !is_inline(href));
+                if rel.split_whitespace().any(|r| r == "preconnect" || r == "dns-prefetch") {
+                    // Connection hints load nothing, and there is nothing left to connect to
+                    continue;
+                }
+                let replacement = href.and_then(|href| {
+                    if rel.split_whitespace().any(|r| r == "stylesheet") {
+                        map(href, Kind::Stylesheet).map(|css| match tag.attr("media") {
+                            Some(media) => format!("<style media=\"{}\">{}</style>", escape_attr(media), css),
+                            None => format!("<style>{}</style>", css),
+                        })
+                    } else {
+                        map(href, Kind::Resource).map(|uri| tag.render(&[("href", Some(&uri))]))
+                    }
+                });
+                out.push_str(replacement.as_deref().unwrap_or(raw));
+            }
+            _ => out.push_str(&rewrite_attributes(&tag, raw, map)),
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `src`, `srcset`, `poster`, `data`, SVG image hrefs and inline styles of one tag
+fn rewrite_attributes(tag: &Tag, raw: &str, map: &mut dyn FnMut(&str, Kind) -> Option<String>) -> String {
+    let svg_link = matches!(tag.name.as_str(), "image" | "use" | "feimage");
+    let mut changes: Vec<(String, String)> = Vec::new();
+    for (name, value) in &tag.attrs {
+        let Some(value) = value else { continue };
+        let lower = name.to_ascii_lowercase();
+        let replacement = match lower.as_str() {
+            "src" | "poster" | "data" => (!is_inline(value)).then(|| map(value, Kind::Resource)).flatten(),
+            "href" | "xlink:href" if svg_link => (!is_inline(value)).then(|| map(value, Kind::Resource)).flatten(),
+            "srcset" => {
+                let mut changed = false;
+                let candidates: Vec<String> = srcset_candidates(value)
+                    .into_iter()
+                    .map(|(url, descriptor)| {
+                        let url = match (!is_inline(url)).then(|| map(url, Kind::Resource)).flatten() {
+                            Some(uri) => {
+                                changed = true;
+                                uri
+                            }
+                            None => url.to_string(),
+                        };
+                        format!("{} {}", url, descriptor).trim_end().to_string()
+                    })
+                    .collect();
+                changed.then(|| candidates.join(", "))
+            }
+            "style" => {
+                let css = rewrite_css(value, map);
+                (css != *value).then_some(css)
+            }
+            _ => None,
+        };
+        if let Some(replacement) = replacement {
+            changes.push((name.clone(), replacement));
+        }
+    }
+    if changes.is_empty() {
+        return raw.to_string();
+    }
+    let overrides: Vec<(&str, Option<&str>)> = changes.iter().map(|(n, v)| (n.as_str(), Some(v.as_str()))).collect();
+    tag.render(&overrides)
+}
+
+/// Pass every `url()` and `@import` in a stylesheet to `map`, like `rewrite_html`
+fn rewrite_css(css: &str, map: &mut dyn FnMut(&str, Kind) -> Option<String>) -> String {
+    let lower = css.to_ascii_lowercase();
+    let mut out = String::with_capacity(css.len());
+    let mut pos = 0;
+    loop {
+        let next = ["/*", "\"", "'", "@import", "url("]
+            .iter()
+            .filter_map(|token| lower[pos..].find(token).map(|i| (pos + i, *token)))
+            .min_by_key(|(i, _)| *i);
+        let Some((start, token)) = next else { break };
+        out.push_str(&css[pos..start]);
+        let rest = &css[start..];
+        let end = match token {
+            "/*" => rest[2..].find("*/").map(|i| i + 4).unwrap_or(rest.len()),
+            "\"" | "'" => string_len(rest),
+            "@import" => rest.find(';').map(|i| i + 1).unwrap_or(rest.len()),
+            _ => rest.find(')').map(|i| i + 1).unwrap_or(rest.len()),
+        };
+        let statement = &rest[..end];
+        pos = start + end;
+        let replacement = match token {
+            "/*" | "\"" | "'" => None,
+            "@import" => {
+                let body = statement["@import".len()..].trim_end_matches(';').trim();
+                let (url, media) = if body.get(..4).is_some_and(|b| b.eq_ignore_ascii_case("url(")) {
+                    let close = body.find(')').unwrap_or(body.len());
+                    (unquote(&body[4..close]), body.get(close + 1..).unwrap_or(""))
+                } else {
+                    let quote = body.chars().next().unwrap_or('"');
+                    let close = body.get(1..).and_then(|b| b.find(quote)).map(|i| i + 2).unwrap_or(body.len());
+                    (unquote(&body[..close]), body.get(close..).unwrap_or(""))
+                };
+                let media = media.trim();
+                (!is_inline(url)).then(|| map(url, Kind::Stylesheet)).flatten().map(|css| {
+                    if media.is_empty() {
+                        css
+                    } else {
+                        format!("@media {} {{\n{}\n}}", media, css)
+                    }
+                })
+            }
+            _ => {
+                let url = unquote(statement[4..].trim_end_matches(')'));
+                (!is_inline(url))
+                    .then(|| map(url, Kind::Resource))
+                    .flatten()
+                    .map(|uri| format!("url(\"{}\")", uri))
+            }
+        };
+        out.push_str(replacement.as_deref().unwrap_or(statement));
+    }
+    out.push_str(&css[pos..]);
+    out
+}
+
+/// Length of the CSS string at the start of `css`, quotes included; a
+/// backslash escapes the next character and a line break ends the string
+fn string_len(css: &str) -> usize {
+    let quote = css.as_bytes()[0];
+    let mut escaped = false;
+    for (i, byte) in css.bytes().enumerate().skip(1) {
+        match byte {
+            _ if escaped => escaped = false,
+            b'\\' => escaped = true,
+            b'\n' => return i,
+            _ if byte == quote => return i + 1,
+            _ => {}
+        }
+    }
+    css.len()
+}
+
+/// URL and descriptor pairs of a `srcset`. URLs run to the next whitespace,
+/// since data URIs contain commas themselves.
+fn srcset_candidates(value: &str) -> Vec<(&str, &str)> {
+    let mut candidates = Vec::new();
+    let mut rest = value;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() {
+            return candidates;
+        }
+        let url_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let url = &rest[..url_end];
+        if let Some(url) = url.strip_suffix(',') {
+            candidates.push((url.trim_end_matches(','), ""));
+            rest = &rest[url_end..];
+            continue;
+        }
+        let descriptor_end = rest[url_end..].find(',').map(|i| url_end + i).unwrap_or(rest.len());
+        candidates.push((url, rest[url_end..descriptor_end].trim()));
+        rest = &rest[descriptor_end..];
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    let value = value.trim();
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+        .unwrap_or(value)
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack.to_ascii_lowercase().find(&needle.to_ascii_lowercase())
+}
+
+/// Start tag with its attributes, values unescaped
+struct Tag {
+    /// Lowercase tag name
+    name: String,
+    attrs: Vec<(String, Option<String>)>,
+    self_closing: bool,
+    /// Length of the tag in the source, including `<` and `>`
+    len: usize,
+}
+
+impl Tag {
+    /// Parse the start tag at the beginning of `source`; `None` for anything else
+    fn parse(source: &str) -> Option<Tag> {
+        let bytes = source.as_bytes();
+        if bytes.len() < 2 || !bytes[1].is_ascii_alphabetic() {
+            return None;
+        }
+        let name_end = source[1..]
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == ':'))
+            .map(|i| i + 1)?;
+        let mut tag = Tag {
+            name: source[1..name_end].to_ascii_lowercase(),
+            attrs: Vec::new(),
+            self_closing: false,
+            len: 0,
+        };
+        let mut i = name_end;
+        loop {
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            match bytes.get(i)? {
+                b'>' => {
+                    tag.len = i + 1;
+                    return Some(tag);
+                }
+                b'/' if bytes.get(i + 1) == Some(&b'>') => {
+                    tag.self_closing = true;
+                    tag.len = i + 2;
+                    return Some(tag);
+                }
+                b'/' => {
+                    i += 1;
+                    continue;
+                }
+                _ => {}
+            }
+            let start = i;
+            while i < bytes.len() && !bytes[i].is_ascii_whitespace() && !matches!(bytes[i], b'=' | b'>' | b'/') {
+                i += 1;
+            }
+            let name = source[start..i].to_string();
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            if bytes.get(i) != Some(&b'=') {
+                tag.attrs.push((name, None));
+                continue;
+            }
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                i += 1;
+            }
+            let value = match bytes.get(i)? {
+                quote @ (b'"' | b'\'') => {
+                    let end = source[i + 1..].find(*quote as char)? + i + 1;
+                    let value = &source[i + 1..end];
+                    i = end + 1;
+                    value
+                }
+                _ => {
+                    let start = i;
+                    while i < bytes.len() && !bytes[i].is_ascii_whitespace() && bytes[i] != b'>' {
+                        i += 1;
+                    }
+                    &source[start..i]
+                }
+            };
+            tag.attrs.push((name, Some(unescape_attr(value))));
+        }
+    }
+
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .and_then(|(_, v)| v.as_deref())
+    }
+
+    /// The tag with some attributes replaced; an override of `None` drops the attribute
+    fn render(&self, overrides: &[(&str, Option<&str>)]) -> String {
+        let mut out = format!("<{}", self.name);
+        for (name, value) in &self.attrs {
+            let value = match overrides.iter().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+                Some((_, None)) => continue,
+                Some((_, Some(replacement))) => Some(*replacement),
+                None => value.as_deref(),
+            };
+            match value {
+                Some(value) => out.push_str(&format!(" {}=\"{}\"", name, escape_attr(value))),
+                None => out.push_str(&format!(" {}", name)),
+            }
+        }
+        out.push_str(if self.self_closing { " />" } else { ">" });
+        out
+    }
+}
+
+fn unescape_attr(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+fn escape_attr(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn collect(html: &str) -> Vec<(String, Kind)> {
+        let mut references = Vec::new();
+        rewrite_html(html, &mut |url, kind| {
+            references.push((url.to_string(), kind));
+            None
+        });
+        references
+    }
+
+    #[tokio::test]
+    async fn inlines_stylesheets_scripts_and_their_resources() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("img")).unwrap();
+        fs::write(dir.path().join("img/bg.png"), b"png").unwrap();
+        fs::write(dir.path().join("my photo.png"), b"photo").unwrap();
+        fs::write(
+            dir.path().join("style.css"),
+            "body { background: url('img/bg.png'); }\n.x::after { content: \"</STYLE>\"; }",
+        )
+        .unwrap();
+        fs::write(dir.path().join("app.js"), "document.write('</script>');").unwrap();
+        let html = concat!(
+            r#"<link rel="preconnect" href="https://fonts.example.com">"#,
+            r#"<link rel="stylesheet" href="style.css" media="print">"#,
+            r#"<script src="app.js"></script>"#,
+            r#"<img src="my%20photo.png" alt="Photo"><a href="https://example.com/">Home</a>"#,
+        );
+
+        let output = inline_resources(html, &[dir.path().to_path_buf()]).await.unwrap();
+        assert!(!output.contains("preconnect"));
+        assert!(output.contains(r#"<style media="print">body { background: url("data:image/png;base64,cG5n"); }"#));
+        assert!(output.contains(r#"content: "<\/STYLE>""#));
+        assert!(output.contains(r#"<script>document.write('<\/script>');</script>"#));
+        assert!(output.contains(r#"<img src="data:image/png;base64,cGhvdG8=" alt="Photo">"#));
+        assert!(output.contains(r#"<a href="https://example.com/">Home</a>"#));
+    }
+
+    #[tokio::test]
+    async fn imports_keep_their_media_queries() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("css")).unwrap();
+        fs::write(dir.path().join("css/print.css"), "@import \"fonts.css\";\nh1 { color: black; }").unwrap();
+        fs::write(dir.path().join("css/fonts.css"), "h1 { font-weight: bold; }").unwrap();
+        fs::write(dir.path().join("css/wide.css"), "main { width: 80%; }").unwrap();
+        let html = concat!(
+            "<style>@import url(\"css/print.css\") print;\n",
+            "@import 'css/wide.css' screen and (min-width: 1200px);\nbody { margin: 0; }</style>",
+        );
+
+        let output = inline_resources(html, &[dir.path().to_path_buf()]).await.unwrap();
+        assert_eq!(
+            output,
+            concat!(
+                "<style>@media print {\nh1 { font-weight: bold; }\nh1 { color: black; }\n}\n",
+                "@media screen and (min-width: 1200px) {\nmain { width: 80%; }\n}\nbody { margin: 0; }</style>",
+            )
+        );
+    }
+
+    #[test]
+    fn srcset_leaves_data_uris_alone() {
+        let html = r#"<img srcset="data:image/png;base64,AAAA 1x, hero@2x.png 2x,hero-wide.png">"#;
+        let references = collect(html);
+        assert_eq!(
+            references,
+            [
+                ("hero@2x.png".to_string(), Kind::Resource),
+                ("hero-wide.png".to_string(), Kind::Resource)
+            ]
+        );
+
+        let output = rewrite_html(html, &mut |url, _| Some(format!("data:{}", url)));
+        assert_eq!(
+            output,
+            r#"<img srcset="data:image/png;base64,AAAA 1x, data:hero@2x.png 2x, data:hero-wide.png">"#
+        );
+    }
+
+    #[test]
+    fn comments_and_css_strings_hold_no_references() {
+        let html = concat!(
+            "<!-- <img src=\"old.png\"> -->",
+            "<style>/* url(commented.png) */ .a::before { content: \"url(quoted.png)\"; }",
+            " .b::before { content: 'it\\'s url(escaped.png)'; } .c { background: url(real.png); }</style>",
+        );
+        assert_eq!(collect(html), [("real.png".to_string(), Kind::Resource)]);
+    }
+
+    #[test]
+    fn remote_references_are_reported() {
+        let html = concat!(
+            r#"<script src="https://cdn.example.com/lib.js"></script>"#,
+            r#"<link rel="stylesheet" href="//fonts.example.com/font.css">"#,
+            r#"<div style="background: url(data:image/gif;base64,R0lGOD)"></div>"#,
+            r#"<a href="https://example.com/">Home</a>"#,
+        );
+        assert_eq!(
+            external_references(html),
+            ["https://cdn.example.com/lib.js", "//fonts.example.com/font.css"]
+        );
+    }
+
+    #[tokio::test]
+    async fn unloadable_references_fail() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = inline_resources(r#"<img src="missing.png">"#, &[dir.path().to_path_buf()]).await;
+        assert!(missing.unwrap_err().starts_with("Cannot inline missing.png: not found in"));
+
+        let unsupported = inline_resources(r#"<img src="ftp://example.com/chart.png">"#, &[]).await;
+        assert_eq!(
+            unsupported.unwrap_err(),
+            "Cannot inline ftp://example.com/chart.png (unsupported scheme \"ftp\")"
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use crate::project::Project;
+
+// Individual brand colors, same names as `BRAND` in build.js
+pub const RED_1: &str = "#F01840";
+pub const RED_2: &str = "#C01830";
+pub const RED_3: &str = "#901226";
+pub const RED_4: &str = "#600C1C";
+pub const RED_5: &str = "#300810";
+pub const PURPLE_1: &str = "#D0C8C8";
+pub const PURPLE_2: &str = "#A0949E";
+pub const PURPLE_3: &str = "#705E74";
+pub const PURPLE_4: &str = "#402848";
+pub const PURPLE_5: &str = "#2A1C30";
+pub const CREAM: &str = "#FFFFF8";
+pub const BLACK: &str = "#141018";
+
+const REDS: [&str; 6] = [RED_1, RED_2, RED_3, RED_4, RED_5, RED_1];
+const PURPLES: [&str; 6] = [PURPLE_3, PURPLE_4, PURPLE_5, PURPLE_3, PURPLE_4, PURPLE_5];
+const ALTERNATING: [&str; 6] = [RED_1, PURPLE_4, RED_2, PURPLE_3, RED_3, PURPLE_5];
+
+/// Task-name text color and left border applied by the `_a`/`_b`/`_c` palette variants
+#[derive(Debug, Clone, Default)]
+pub struct Accents {
+    pub color: Option<String>,
+    pub border: Option<String>,
+}
+
+/// Colors of a palette preset, matching `PALETTE_PRESETS` in build.js
+pub fn preset(name: &str) -> Option<&'static [&'static str]> {
+    match name.trim().to_lowercase().as_str() {
+        "reds" | "reds_a" | "reds_b" => Some(&REDS),
+        "purples_a" | "purples_b" | "purples_c" => Some(&PURPLES),
+        "alternating" | "alternating_b" => Some(&ALTERNATING),
+        _ => None,
+    }
+}
+
+/// Accent styling for a palette preset
+pub fn accents(name: &str) -> Accents {
+    let (color, border) = match name.trim().to_lowercase().as_str() {
+        "reds_b" => (None, Some(PURPLE_4)),
+        "purples_a" => (Some(RED_3), None),
+        "purples_b" => (None, Some(RED_2)),
+        "purples_c" => (Some(RED_3), Some(RED_2)),
+        "alternating_b" => (None, Some(RED_2)),
+        _ => (None, None),
+    };
+    Accents {
+        color: color.map(String::from),
+        border: border.map(String::from),
+    }
+}
+
+/// Apply a palette preset the way build.js does for `--palette`: replace the
+/// palette and re-color every task by its colorIndex, or its position when it
+/// has none. Unknown names fall back to "alternating"; an empty name keeps the
+/// project's own palette.
+pub fn apply_preset(project: &mut Project, name: &str) -> Accents {
+    if name.trim().is_empty() {
+        project.resolve_colors();
+        return Accents::default();
+    }
+
+    let colors = preset(name).unwrap_or(&ALTERNATING);
+    project.palette = colors.iter().map(|c| c.to_string()).collect();
+    for (idx, task) in project.tasks.iter_mut().enumerate() {
+        let color_idx = task.color_index.unwrap_or(idx);
+        task.color = Some(colors[color_idx % colors.len()].to_string());
+    }
+    accents(name)
+}
@@ -0,0 +1,436 @@
+use crate::project::{normalize_date, Task, MAX_SUBTASKS};
+use serde::{Deserialize, Serialize};
+
+/// Tasks recovered from pasted text, plus per-row problems for everything else
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PasteResult {
+    pub tasks: Vec<Task>,
+    pub diagnostics: Vec<RowDiagnostic>,
+    /// 1-based row of the detected header, if the paste had one
+    pub header_row: Option<usize>,
+    /// Field each pasted column was mapped to ("name", "start", "subtask", ...)
+    pub columns: Vec<Option<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RowDiagnostic {
+    /// 1-based row in the pasted data
+    pub row: usize,
+    pub level: String, // "error" or "warning"
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Name,
+    Start,
+    End,
+    Hours,
+    Subtask,
+    SubtaskList,
+    Color,
+    ColorIndex,
+}
+
+impl Field {
+    fn key(self) -> &'static str {
+        match self {
+            Field::Name => "name",
+            Field::Start => "start",
+            Field::End => "end",
+            Field::Hours => "hours",
+            Field::Subtask => "subtask",
+            Field::SubtaskList => "subtasks",
+            Field::Color => "color",
+            Field::ColorIndex => "colorIndex",
+        }
+    }
+
+    /// Map a header cell to a field, accepting the usual spreadsheet spellings
+    fn from_header(header: &str) -> Option<Field> {
+        let key: String = header
+            .to_lowercase()
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .collect();
+        let key = key.trim_end_matches(|c: char| c.is_ascii_digit());
+        match key {
+            "name" | "task" | "taskname" | "tasks" | "phase" | "activity" | "workstream" | "item" => {
+                Some(Field::Name)
+            }
+            "start" | "startdate" | "begin" | "begins" | "from" | "startson" => Some(Field::Start),
+            "end" | "enddate" | "finish" | "finishdate" | "due" | "duedate" | "to" | "until"
+            | "endson" => Some(Field::End),
+            "hours" | "hrs" | "hour" | "effort" | "estimate" | "estimatedhours" | "budgethours" => {
+                Some(Field::Hours)
+            }
+            "subtask" | "sub" | "subitem" => Some(Field::Subtask),
+            "subtasks" | "subitems" | "details" | "deliverables" => Some(Field::SubtaskList),
+            "color" | "colour" => Some(Field::Color),
+            "colorindex" | "colourindex" => Some(Field::ColorIndex),
+            _ => None,
+        }
+    }
+}
+
+/// Column layout of the Tasks sheet, used when the paste has no header row
+const POSITIONAL_LAYOUT: [Field; 4] = [Field::Name, Field::Start, Field::End, Field::Hours];
+
+/// Parse tab-separated or HTML-table text copied from a spreadsheet or email
+pub fn parse_pasted_tasks(text: &str) -> PasteResult {
+    let rows = if looks_like_html(text) {
+        html_table_rows(text)
+    } else {
+        tsv_rows(text)
+    };
+
+    let mut result = PasteResult {
+        tasks: Vec::new(),
+        diagnostics: Vec::new(),
+        header_row: None,
+        columns: Vec::new(),
+    };
+
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    if width == 0 {
+        result.diagnostics.push(RowDiagnostic {
+            row: 0,
+            level: "error".to_string(),
+            message: "No tabular data found in the pasted text".to_string(),
+        });
+        return result;
+    }
+
+    // The header is the first of the leading rows that names a task column and a date column
+    let header = rows.iter().take(5).enumerate().find_map(|(idx, row)| {
+        let fields: Vec<Option<Field>> = row.iter().map(|c| Field::from_header(c)).collect();
+        let has = |f: Field| fields.contains(&Some(f));
+        (has(Field::Name) && (has(Field::Start) || has(Field::End))).then_some((idx, fields))
+    });
+
+    let (first_data_row, mut fields) = match header {
+        Some((idx, fields)) => {
+            result.header_row = Some(idx + 1);
+            (idx + 1, fields)
+        }
+        None => {
+            let mut fields: Vec<Option<Field>> = POSITIONAL_LAYOUT.iter().map(|f| Some(*f)).collect();
+            fields.resize(4 + MAX_SUBTASKS, Some(Field::Subtask));
+            result.diagnostics.push(RowDiagnostic {
+                row: 1,
+                level: "warning".to_string(),
+                message: "No header row detected; assuming name, start, end, hours, subtasks columns"
+                    .to_string(),
+            });
+            (0, fields)
+        }
+    };
+    fields.resize(width, None);
+    result.columns = fields.iter().map(|f| f.map(|f| f.key().to_string())).collect();
+
+    for (idx, row) in rows.iter().enumerate().skip(first_data_row) {
+        let row_number = idx + 1;
+        if row.iter().all(|c| c.trim().is_empty()) {
+            continue;
+        }
+        match parse_task_row(row, &fields) {
+            Ok((task, warnings)) => {
+                for message in warnings {
+                    result.diagnostics.push(RowDiagnostic {
+                        row: row_number,
+                        level: "warning".to_string(),
+                        message,
+                    });
+                }
+                result.tasks.push(task);
+            }
+            Err(errors) => {
+                for message in errors {
+                    result.diagnostics.push(RowDiagnostic {
+                        row: row_number,
+                        level: "error".to_string(),
+                        message,
+                    });
+                }
+            }
+        }
+    }
+
+    result
+}
+
+fn parse_task_row(row: &[String], fields: &[Option<Field>]) -> Result<(Task, Vec<String>), Vec<String>> {
+    let mut task = Task::default();
+    let mut errors = Vec::new();
+    let mut warnings = Vec::new();
+    let mut start_raw = String::new();
+    let mut end_raw = String::new();
+
+    for (value, field) in row.iter().zip(fields) {
+        let value = value.trim();
+        match field {
+            Some(Field::Name) if task.name.is_empty() => task.name = value.to_string(),
+            Some(Field::Start) if start_raw.is_empty() => start_raw = value.to_string(),
+            Some(Field::End) if end_raw.is_empty() => end_raw = value.to_string(),
+            Some(Field::Hours) if !value.is_empty() => match parse_hours(value) {
+                Some(hours) => task.hours = hours,
+                None => warnings.push(format!("Hours '{}' is not a number; using 0", value)),
+            },
+            Some(Field::Subtask) if !value.is_empty() => task.subtasks.push(value.to_string()),
+            Some(Field::SubtaskList) => task.subtasks.extend(split_subtasks(value)),
+            Some(Field::Color) if !value.is_empty() => task.color = Some(value.to_string()),
+            Some(Field::ColorIndex) if !value.is_empty() => match value.parse::<usize>() {
+                Ok(index) => task.color_index = Some(index),
+                Err(_) => warnings.push(format!("colorIndex '{}' is not a whole number", value)),
+            },
+            _ => {}
+        }
+    }
+
+    if task.name.is_empty() {
+        errors.push("Missing task name".to_string());
+    }
+    match date_cell(&start_raw, "start") {
+        Ok(date) => task.start = date,
+        Err(e) => errors.push(e),
+    }
+    match date_cell(&end_raw, "end") {
+        Ok(date) => task.end = date,
+        Err(e) => errors.push(e),
+    }
+    if !task.start.is_empty() && !task.end.is_empty() && task.start >= task.end {
+        errors.push(format!("Start date {} must be before end date {}", task.start, task.end));
+    }
+    if task.subtasks.len() > MAX_SUBTASKS {
+        warnings.push(format!(
+            "{} subtasks found; only the first {} are kept",
+            task.subtasks.len(),
+            MAX_SUBTASKS
+        ));
+        task.subtasks.truncate(MAX_SUBTASKS);
+    }
+
+    if errors.is_empty() {
+        Ok((task, warnings))
+    } else {
+        Err(errors)
+    }
+}
+
+fn date_cell(value: &str, label: &str) -> Result<String, String> {
+    if value.is_empty() {
+        return Err(format!("Missing {} date", label));
+    }
+    normalize_date(value)
+        .ok_or_else(|| format!("Unrecognised {} date '{}'. Expected a date like 2025-01-15", label, value))
+}
+
+/// Hours like "20", "20.5", "20h" or "20 hrs"
+fn parse_hours(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let end = value
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+        .unwrap_or(value.len());
+    let (number, rest) = value.split_at(end);
+    let rest = rest.trim().to_lowercase();
+    if !rest.is_empty() && !["h", "hr", "hrs", "hour", "hours"].contains(&rest.as_str()) {
+        return None;
+    }
+    parse_number(number)
+}
+
+/// Commas followed by groups of three digits separate thousands ("1,000",
+/// "1,000.5"); a single comma anywhere else is a decimal comma ("20,5").
+/// Any other comma is rejected.
+fn parse_number(number: &str) -> Option<f64> {
+    let groups: Vec<&str> = number.split(',').collect();
+    let thousands = groups.len() > 1
+        && !groups[0].is_empty()
+        && !groups[0].contains('.')
+        && groups[1..].iter().enumerate().all(|(i, group)| {
+            let digits = if i == groups.len() - 2 {
+                group.split('.').next().unwrap_or_default()
+            } else {
+                group
+            };
+            digits.len() == 3 && digits.chars().all(|c| c.is_ascii_digit())
+        });
+    let normalized = if groups.len() == 1 {
+        number.to_string()
+    } else if thousands {
+        number.replace(',', "")
+    } else if groups.len() == 2 && !number.contains('.') {
+        number.replace(',', ".")
+    } else {
+        return None;
+    };
+    normalized.parse().ok()
+}
+
+/// A combined subtasks cell may hold several entries separated by line breaks, bullets or semicolons
+fn split_subtasks(value: &str) -> Vec<String> {
+    value
+        .split(['\n', ';', '•'])
+        .map(|s| s.trim().trim_start_matches(['-', '*']).trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn looks_like_html(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    lower.contains("<table") || lower.contains("<tr")
+}
+
+fn tsv_rows(text: &str) -> Vec<Vec<String>> {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    // Spreadsheets quote cells that contain tabs or line breaks, doubling inner quotes
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                cell.push('"');
+                chars.next();
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if cell.is_empty() => in_quotes = true,
+            '\t' if !in_quotes => row.push(std::mem::take(&mut cell)),
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => cell.push(c),
+        }
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+    rows
+}
+
+/// Extract the cells of every `<tr>` in an HTML fragment
+fn html_table_rows(html: &str) -> Vec<Vec<String>> {
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    let mut cell: Option<String> = None;
+    let mut rest = html;
+
+    while let Some(open) = rest.find('<') {
+        if let Some(cell) = cell.as_mut() {
+            cell.push_str(&decode_entities(&rest[..open]));
+        }
+        let Some(close) = rest[open..].find('>') else {
+            break;
+        };
+        let tag = rest[open + 1..open + close].trim().to_lowercase();
+        rest = &rest[open + close + 1..];
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect();
+
+        match (name.as_str(), closing) {
+            ("tr", false) => rows.push(Vec::new()),
+            ("td" | "th", false) => {
+                if rows.is_empty() {
+                    rows.push(Vec::new());
+                }
+                cell = Some(String::new());
+            }
+            ("td" | "th", true) | ("tr", true) => {
+                if let (Some(text), Some(row)) = (cell.take(), rows.last_mut()) {
+                    row.push(text.trim().to_string());
+                }
+            }
+            ("br", _) | ("p" | "li" | "div", true) => {
+                if let Some(cell) = cell.as_mut() {
+                    cell.push('\n');
+                }
+            }
+            _ => {}
+        }
+    }
+
+    rows.retain(|row| !row.is_empty());
+    rows
+}
+
+fn decode_entities(text: &str) -> String {
+    // HTML treats any run of whitespace as a single space
+    let mut collapsed = String::with_capacity(text.len());
+    for c in text.chars() {
+        if !c.is_whitespace() {
+            collapsed.push(c);
+        } else if !collapsed.ends_with(' ') {
+            collapsed.push(' ');
+        }
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = collapsed.as_str();
+
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';').filter(|i| *i <= 10) else {
+            out.push('&');
+            rest = &rest[1..];
+            continue;
+        };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some(' '),
+            _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                u32::from_str_radix(&entity[2..], 16).ok().and_then(char::from_u32)
+            }
+            _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hours_accept_units_and_separators() {
+        assert_eq!(parse_hours("12"), Some(12.0));
+        assert_eq!(parse_hours("7.5 hrs"), Some(7.5));
+        assert_eq!(parse_hours("1,000 hrs"), Some(1000.0));
+        assert_eq!(parse_hours("1,234,567.5h"), Some(1234567.5));
+        assert_eq!(parse_hours("20,5"), Some(20.5));
+        assert_eq!(parse_hours("20,5 hours"), Some(20.5));
+    }
+
+    #[test]
+    fn hours_reject_unclear_numbers() {
+        assert_eq!(parse_hours("1,2,3"), None);
+        assert_eq!(parse_hours("1,5.5"), None);
+        assert_eq!(parse_hours("12 days"), None);
+        assert_eq!(parse_hours(""), None);
+    }
+}
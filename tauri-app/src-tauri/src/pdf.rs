@@ -0,0 +1,600 @@
+use crate::chart::{num, text_spans, text_width, tile, Anchor, Chart, Color, Corners, LineStyle, Shape, Stroke};
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::Write as _;
+
+const POINTS_PER_MM: f64 = 72.0 / 25.4;
+/// CSS pixels print at 96 per inch
+const PX_TO_PT: f64 = 0.75;
+/// Smallest scale the chart is shrunk to before it is tiled across pages;
+/// keeps 16px subtask text at 8pt
+const MIN_SCALE: f64 = 0.5;
+/// Space below the chart reserved for the page number
+const FOOTER_HEIGHT: f64 = 16.0;
+/// Control point offset for quarter circles drawn as cubic Béziers
+const KAPPA: f64 = 0.552_284_75;
+
+/// Printable page in PDF points
+#[derive(Debug, Clone, Copy)]
+pub struct PageSetup {
+    pub width: f64,
+    pub height: f64,
+    pub margin: f64,
+}
+
+impl PageSetup {
+    /// Page size by name ("a4", "letter", "a3", "tabloid"), "portrait" or "landscape", margins in mm
+    pub fn new(size: &str, orientation: &str, margin_mm: f64) -> Result<Self, String> {
+        let (short, long): (f64, f64) = match size.trim().to_lowercase().as_str() {
+            "a4" => (595.28, 841.89),
+            "letter" => (612.0, 792.0),
+            "a3" => (841.89, 1190.55),
+            "tabloid" | "ledger" => (792.0, 1224.0),
+            other => {
+                return Err(format!(
+                    "Unknown page size \"{}\". Expected A4, Letter, A3 or Tabloid",
+                    other
+                ))
+            }
+        };
+        let (width, height) = match orientation.trim().to_lowercase().as_str() {
+            "landscape" => (long, short),
+            "portrait" => (short, long),
+            other => {
+                return Err(format!(
+                    "Unknown page orientation \"{}\". Expected portrait or landscape",
+                    other
+                ))
+            }
+        };
+        let margin = margin_mm * POINTS_PER_MM;
+        if !(0.0..=width.min(height) / 4.0).contains(&margin) {
+            return Err(format!("Page margin of {}mm does not fit the page", margin_mm));
+        }
+        Ok(PageSetup { width, height, margin })
+    }
+}
+
+/// Write a laid out chart as a vector PDF with text in the built-in Helvetica
+/// fonts, so it stays selectable. A chart that would have to shrink below
+/// `MIN_SCALE` to fit is tiled instead: across the timeline at week starts and
+/// down at row boundaries, repeating the task column and the header on every
+/// page. Drop shadows are left out since they do not print well.
+pub fn to_pdf(chart: &Chart, title: &str, setup: &PageSetup) -> Result<Vec<u8>, String> {
+    let printable_w = setup.width - 2.0 * setup.margin;
+    let printable_h = setup.height - 2.0 * setup.margin - FOOTER_HEIGHT;
+    let fit = (printable_w / chart.width).min(printable_h / chart.height);
+    let scale = if fit >= MIN_SCALE { fit.min(PX_TO_PT) } else { MIN_SCALE };
+
+    let text_spans = text_spans(&chart.shapes, chart.header_bottom);
+    let columns = tile(
+        chart.timeline_x,
+        &chart.column_breaks,
+        |b| !text_spans.iter().any(|(x0, x1)| *x0 < b && b < *x1),
+        printable_w / scale - chart.timeline_x,
+        0.5,
+    );
+    let rows = tile(
+        chart.header_bottom,
+        &chart.row_breaks,
+        |_| true,
+        printable_h / scale - chart.header_bottom,
+        0.5,
+    );
+    if columns.is_empty() || rows.is_empty() {
+        return Err("The page is too small for the task column and header".to_string());
+    }
+
+    let mut painter = Painter::default();
+    let mut contents = Vec::new();
+    let page_count = rows.len() * columns.len();
+    for (row_idx, &(y0, y1)) in rows.iter().enumerate() {
+        for (col_idx, &(x0, x1)) in columns.iter().enumerate() {
+            painter.ops.clear();
+            // Chart pixels, y down, from the top left of the printable area
+            let _ = writeln!(
+                painter.ops,
+                "q {s} 0 0 {neg} {x} {y} cm",
+                s = num(scale),
+                neg = num(-scale),
+                x = num(setup.margin),
+                y = num(setup.height - setup.margin)
+            );
+            let (tx, ty) = (chart.timeline_x, chart.header_bottom);
+            // Task column header, timeline header slice, task column rows, timeline rows
+            painter.region(chart, (0.0, 0.0, tx, ty), (0.0, 0.0));
+            painter.region(chart, (x0, 0.0, x1, ty), (tx, 0.0));
+            painter.region(chart, (0.0, y0, tx, y1), (0.0, ty));
+            painter.region(chart, (x0, y0, x1, y1), (tx, ty));
+            painter.ops.push_str("Q\n");
+
+            if page_count > 1 {
+                let number = row_idx * columns.len() + col_idx + 1;
+                let label = format!("Page {} of {}", number, page_count);
+                let x = (setup.width - text_width(&label, 8.0, 400)) / 2.0;
+                let _ = writeln!(
+                    painter.ops,
+                    "BT 0.44 0.37 0.45 rg /F1 8 Tf {} {} Td {} Tj ET",
+                    num(x),
+                    num(setup.margin + 4.0),
+                    pdf_string(&label)
+                );
+            }
+            contents.push(compress(painter.ops.as_bytes())?);
+        }
+    }
+
+    Ok(write_document(title, setup, &contents, &painter.alphas))
+}
+
+/// Content stream builder; tracks the opacity levels the pages use
+#[derive(Default)]
+struct Painter {
+    ops: String,
+    /// ExtGState names by opacity
+    alphas: BTreeMap<String, String>,
+}
+
+impl Painter {
+    /// Draw the part of the chart inside `src` (x0, y0, x1, y1) with its top left at `dest`
+    fn region(&mut self, chart: &Chart, src: (f64, f64, f64, f64), dest: (f64, f64)) {
+        let (x0, y0, x1, y1) = src;
+        if x1 <= x0 || y1 <= y0 {
+            return;
+        }
+        let _ = writeln!(
+            self.ops,
+            "q {} {} {} {} re W n 1 0 0 1 {} {} cm",
+            num(dest.0),
+            num(dest.1),
+            num(x1 - x0),
+            num(y1 - y0),
+            num(dest.0 - x0),
+            num(dest.1 - y0)
+        );
+        for shape in &chart.shapes {
+            self.shape(shape, src);
+        }
+        self.ops.push_str("Q\n");
+    }
+
+    fn shape(&mut self, shape: &Shape, src: (f64, f64, f64, f64)) {
+        // Skipping what the region clips away keeps hidden text out of copy and search
+        let (bx0, by0, bx1, by1) = shape.bounds();
+        if bx1 <= src.0 || bx0 >= src.2 || by1 <= src.1 || by0 >= src.3 {
+            return;
+        }
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                w,
+                h,
+                corners,
+                fill,
+                stroke,
+                ..
+            } => {
+                if let Some(fill) = fill {
+                    self.ops.push_str("q ");
+                    self.fill_color(fill);
+                    rect_path(&mut self.ops, *x, *y, *w, *h, corners);
+                    self.ops.push_str("f Q\n");
+                }
+                if let Some(stroke) = stroke {
+                    // Inset like a CSS border, matching the SVG output
+                    let inset = stroke.width / 2.0;
+                    let inner = Corners {
+                        top_left: (corners.top_left - inset).max(0.0),
+                        top_right: (corners.top_right - inset).max(0.0),
+                        bottom_right: (corners.bottom_right - inset).max(0.0),
+                        bottom_left: (corners.bottom_left - inset).max(0.0),
+                    };
+                    self.ops.push_str("q ");
+                    self.stroke_style(stroke);
+                    rect_path(&mut self.ops, x + inset, y + inset, w - stroke.width, h - stroke.width, &inner);
+                    self.ops.push_str("S Q\n");
+                }
+            }
+            Shape::Line { x1, y1, x2, y2, stroke } => {
+                self.ops.push_str("q ");
+                self.stroke_style(stroke);
+                let _ = writeln!(self.ops, "{} {} m {} {} l S Q", num(*x1), num(*y1), num(*x2), num(*y2));
+            }
+            Shape::Text {
+                x,
+                y,
+                text,
+                size,
+                weight,
+                color,
+                anchor,
+            } => {
+                let x = match anchor {
+                    Anchor::Start => *x,
+                    Anchor::Middle => x - text_width(text, *size, *weight) / 2.0,
+                };
+                let font = if *weight >= 600 { "F2" } else { "F1" };
+                self.ops.push_str("q ");
+                self.fill_color(color);
+                // Flip the text matrix back upright inside the y-down page transform
+                let _ = writeln!(
+                    self.ops,
+                    "BT /{} {} Tf 1 0 0 -1 {} {} Tm {} Tj ET Q",
+                    font,
+                    num(*size),
+                    num(x),
+                    num(*y),
+                    pdf_string(text)
+                );
+            }
+            Shape::Hatch {
+                x,
+                y,
+                w,
+                h,
+                color,
+                stripe,
+                period,
+            } => {
+                self.ops.push_str("q ");
+                let _ = write!(self.ops, "{} {} {} {} re W n ", num(*x), num(*y), num(*w), num(*h));
+                self.stroke_style(&Stroke {
+                    color: *color,
+                    width: *stripe,
+                    style: LineStyle::Solid,
+                });
+                // Stripes every `period` px measured across them, so √2·period along x
+                let step = period * std::f64::consts::SQRT_2;
+                let mut offset = -h - (x % step);
+                while offset < *w {
+                    let _ = write!(
+                        self.ops,
+                        "{} {} m {} {} l ",
+                        num(x + offset),
+                        num(*y),
+                        num(x + offset + h),
+                        num(y + h)
+                    );
+                    offset += step;
+                }
+                self.ops.push_str("S Q\n");
+            }
+            Shape::Clip {
+                x,
+                y,
+                w,
+                h,
+                radius,
+                shapes,
+            } => {
+                self.ops.push_str("q ");
+                rect_path(&mut self.ops, *x, *y, *w, *h, &Corners::all(*radius));
+                self.ops.push_str("W n\n");
+                for shape in shapes {
+                    self.shape(shape, src);
+                }
+                self.ops.push_str("Q\n");
+            }
+        }
+    }
+
+    fn fill_color(&mut self, color: &Color) {
+        let _ = write!(self.ops, "{} rg ", rgb(color));
+        if color.a < 1.0 {
+            let name = self.alpha(color.a);
+            let _ = write!(self.ops, "/{} gs ", name);
+        }
+    }
+
+    fn stroke_style(&mut self, stroke: &Stroke) {
+        let _ = write!(self.ops, "{} RG {} w ", rgb(&stroke.color), num(stroke.width));
+        match stroke.style {
+            LineStyle::Solid => {}
+            LineStyle::Dashed => {
+                let _ = write!(self.ops, "[{0} {0}] 0 d ", num(stroke.width * 3.0));
+            }
+            LineStyle::Dotted => {
+                let _ = write!(self.ops, "[{0} {0}] 0 d ", num(stroke.width));
+            }
+        }
+        if stroke.color.a < 1.0 {
+            let name = self.alpha(stroke.color.a);
+            let _ = write!(self.ops, "/{} gs ", name);
+        }
+    }
+
+    /// ExtGState name setting both fill and stroke opacity
+    fn alpha(&mut self, a: f64) -> String {
+        let key = num(a);
+        let next = format!("GS{}", self.alphas.len() + 1);
+        self.alphas.entry(key).or_insert(next).clone()
+    }
+}
+
+/// Path for a rectangle with per-corner radii
+fn rect_path(ops: &mut String, x: f64, y: f64, w: f64, h: f64, c: &Corners) {
+    if c.is_square() {
+        let _ = write!(ops, "{} {} {} {} re ", num(x), num(y), num(w), num(h));
+        return;
+    }
+    let (r, b) = (x + w, y + h);
+    let _ = write!(ops, "{} {} m {} {} l ", num(x + c.top_left), num(y), num(r - c.top_right), num(y));
+    corner(ops, (r - c.top_right, y), (r, y + c.top_right), c.top_right, true);
+    let _ = write!(ops, "{} {} l ", num(r), num(b - c.bottom_right));
+    corner(ops, (r, b - c.bottom_right), (r - c.bottom_right, b), c.bottom_right, false);
+    let _ = write!(ops, "{} {} l ", num(x + c.bottom_left), num(b));
+    corner(ops, (x + c.bottom_left, b), (x, b - c.bottom_left), c.bottom_left, true);
+    let _ = write!(ops, "{} {} l ", num(x), num(y + c.top_left));
+    corner(ops, (x, y + c.top_left), (x + c.top_left, y), c.top_left, false);
+    ops.push_str("h ");
+}
+
+/// Quarter circle from `from` to `to`; `horizontal_first` when the arc leaves `from` along x
+fn corner(ops: &mut String, from: (f64, f64), to: (f64, f64), radius: f64, horizontal_first: bool) {
+    if radius <= 0.0 {
+        return;
+    }
+    let k = radius * KAPPA;
+    let (c1, c2) = if horizontal_first {
+        (
+            (from.0 + k * (to.0 - from.0).signum(), from.1),
+            (to.0, to.1 - k * (to.1 - from.1).signum()),
+        )
+    } else {
+        (
+            (from.0, from.1 + k * (to.1 - from.1).signum()),
+            (to.0 - k * (to.0 - from.0).signum(), to.1),
+        )
+    };
+    let _ = write!(
+        ops,
+        "{} {} {} {} {} {} c ",
+        num(c1.0),
+        num(c1.1),
+        num(c2.0),
+        num(c2.1),
+        num(to.0),
+        num(to.1)
+    );
+}
+
+fn rgb(color: &Color) -> String {
+    format!(
+        "{} {} {}",
+        num(color.r as f64 / 255.0),
+        num(color.g as f64 / 255.0),
+        num(color.b as f64 / 255.0)
+    )
+}
+
+/// Literal string in WinAnsiEncoding, the encoding of the built-in fonts;
+/// characters outside it become "?"
+fn pdf_string(text: &str) -> String {
+    let mut out = String::from("(");
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            _ => {
+                let _ = write!(out, "\\{:03o}", win_ansi(c).unwrap_or(b'?'));
+            }
+        }
+    }
+    out.push(')');
+    out
+}
+
+fn win_ansi(c: char) -> Option<u8> {
+    let byte = match c {
+        '\u{a0}'..='\u{ff}' => c as u8,
+        '€' => 0x80,
+        '‚' => 0x82,
+        'ƒ' => 0x83,
+        '„' => 0x84,
+        '…' => 0x85,
+        '†' => 0x86,
+        '‡' => 0x87,
+        'ˆ' => 0x88,
+        '‰' => 0x89,
+        'Š' => 0x8a,
+        '‹' => 0x8b,
+        'Œ' => 0x8c,
+        'Ž' => 0x8e,
+        '‘' => 0x91,
+        '’' => 0x92,
+        '“' => 0x93,
+        '”' => 0x94,
+        '•' => 0x95,
+        '–' => 0x96,
+        '—' => 0x97,
+        '˜' => 0x98,
+        '™' => 0x99,
+        'š' => 0x9a,
+        '›' => 0x9b,
+        'œ' => 0x9c,
+        'ž' => 0x9e,
+        'Ÿ' => 0x9f,
+        _ => return None,
+    };
+    Some(byte)
+}
+
+/// Text string for the document info dictionary, as UTF-16BE hex
+fn info_string(text: &str) -> String {
+    let mut out = String::from("<FEFF");
+    for unit in text.encode_utf16() {
+        let _ = write!(out, "{:04X}", unit);
+    }
+    out.push('>');
+    out
+}
+
+fn compress(data: &[u8]) -> Result<Vec<u8>, String> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(data)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| format!("Failed to compress PDF page: {}", e))
+}
+
+/// Assemble the PDF file. Objects 1-6 are the catalog, page tree, the two
+/// fonts, the shared opacity states and the info dictionary; each page then
+/// takes a page object and a content stream. No dates are written, so the same
+/// chart always produces the same bytes.
+fn write_document(title: &str, setup: &PageSetup, contents: &[Vec<u8>], alphas: &BTreeMap<String, String>) -> Vec<u8> {
+    let page_ids: Vec<usize> = (0..contents.len()).map(|i| 7 + 2 * i).collect();
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{} 0 R", id)).collect();
+    let mut states = String::new();
+    for (alpha, name) in alphas {
+        let _ = write!(states, "/{} << /ca {a} /CA {a} >> ", name, a = alpha);
+    }
+
+    let mut objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        format!("<< /Type /Pages /Kids [{}] /Count {} >>", kids.join(" "), contents.len()).into_bytes(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_vec(),
+        format!("<< {}>>", states).into_bytes(),
+        format!("<< /Title {} /Producer (GanttGen) >>", info_string(title)).into_bytes(),
+    ];
+    for (page_id, content) in page_ids.iter().zip(contents) {
+        objects.push(
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R >> /ExtGState 5 0 R >> /Contents {} 0 R >>",
+                num(setup.width),
+                num(setup.height),
+                page_id + 1
+            )
+            .into_bytes(),
+        );
+        let mut stream = format!("<< /Length {} /Filter /FlateDecode >>\nstream\n", content.len()).into_bytes();
+        stream.extend_from_slice(content);
+        stream.extend_from_slice(b"\nendstream");
+        objects.push(stream);
+    }
+
+    let mut out = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::new();
+    for (idx, body) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        out.extend_from_slice(format!("{} 0 obj\n", idx + 1).as_bytes());
+        out.extend_from_slice(body);
+        out.extend_from_slice(b"\nendobj\n");
+    }
+    let xref = out.len();
+    let mut table = format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(table, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        table,
+        "trailer\n<< /Size {} /Root 1 0 R /Info 6 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref
+    );
+    out.extend_from_slice(table.as_bytes());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::{layout, ChartOptions};
+    use crate::project::{Project, Task};
+    use flate2::read::ZlibDecoder;
+    use std::io::Read as _;
+
+    fn chart(tasks: usize, timeline_end: &str) -> Chart {
+        let project = Project {
+            timeline_start: "2025-01-01".to_string(),
+            timeline_end: timeline_end.to_string(),
+            tasks: (0..tasks)
+                .map(|i| Task {
+                    name: format!("Task {}", i + 1),
+                    start: "2025-01-06".to_string(),
+                    end: "2025-01-24".to_string(),
+                    subtasks: vec!["Review the draft with the team".to_string()],
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+        layout(&project, &ChartOptions::default()).unwrap()
+    }
+
+    /// Decompressed content stream of every page
+    fn page_contents(pdf: &[u8]) -> Vec<String> {
+        let mut pages = Vec::new();
+        let mut rest = pdf;
+        while let Some(at) = rest.windows(8).position(|w| w == b"/Length ") {
+            rest = &rest[at + 8..];
+            let digits = rest.iter().take_while(|b| b.is_ascii_digit()).count();
+            let length: usize = std::str::from_utf8(&rest[..digits]).unwrap().parse().unwrap();
+            let start = rest.windows(7).position(|w| w == b"stream\n").unwrap() + 7;
+            let mut content = String::new();
+            ZlibDecoder::new(&rest[start..start + length]).read_to_string(&mut content).unwrap();
+            pages.push(content);
+            rest = &rest[start + length..];
+        }
+        pages
+    }
+
+    #[test]
+    fn page_setup_validates_size_orientation_and_margin() {
+        let a4 = PageSetup::new(" A4 ", "Landscape", 10.0).unwrap();
+        assert_eq!((a4.width, a4.height), (841.89, 595.28));
+        assert!((a4.margin - 28.35).abs() < 0.01);
+        let letter = PageSetup::new("letter", "portrait", 0.0).unwrap();
+        assert_eq!((letter.width, letter.height, letter.margin), (612.0, 792.0, 0.0));
+        assert_eq!(PageSetup::new("ledger", "portrait", 5.0).unwrap().height, 1224.0);
+
+        assert_eq!(
+            PageSetup::new("b5", "portrait", 10.0).unwrap_err(),
+            "Unknown page size \"b5\". Expected A4, Letter, A3 or Tabloid"
+        );
+        assert_eq!(
+            PageSetup::new("a4", "square", 10.0).unwrap_err(),
+            "Unknown page orientation \"square\". Expected portrait or landscape"
+        );
+        // A quarter of A4's short side is about 52mm
+        assert!(PageSetup::new("a4", "portrait", 52.0).is_ok());
+        assert_eq!(
+            PageSetup::new("a4", "portrait", 53.0).unwrap_err(),
+            "Page margin of 53mm does not fit the page"
+        );
+        assert!(PageSetup::new("a4", "portrait", -1.0).is_err());
+        assert!(PageSetup::new("a4", "portrait", f64::NAN).is_err());
+    }
+
+    #[test]
+    fn charts_that_fit_get_one_page_without_a_footer() {
+        // The 1870px chart width shrinks to 0.6 on A3 landscape, above MIN_SCALE
+        let setup = PageSetup::new("a3", "landscape", 10.0).unwrap();
+        let pdf = to_pdf(&chart(3, "2025-02-28"), "Plan", &setup).unwrap();
+        let pages = page_contents(&pdf);
+        assert_eq!(pages.len(), 1);
+        assert!(!pages[0].contains("Page 1"));
+        assert!(String::from_utf8_lossy(&pdf).contains("/Type /Pages /Kids [7 0 R] /Count 1 >>"));
+    }
+
+    #[test]
+    fn large_charts_are_tiled_with_page_numbers() {
+        let setup = PageSetup::new("a4", "portrait", 10.0).unwrap();
+        let pdf = to_pdf(&chart(30, "2025-12-31"), "Plan", &setup).unwrap();
+        let pages = page_contents(&pdf);
+        assert!(pages.len() > 2, "expected tiling, got {} pages", pages.len());
+        assert!(String::from_utf8_lossy(&pdf).contains(&format!("/Count {} >>", pages.len())));
+        for (i, page) in pages.iter().enumerate() {
+            assert!(page.contains(&format!("(Page {} of {}) Tj", i + 1, pages.len())));
+            // Every page repeats the task column
+            assert!(page.contains("(Task "));
+        }
+        assert_eq!(to_pdf(&chart(30, "2025-12-31"), "Plan", &setup).unwrap(), pdf);
+    }
+}
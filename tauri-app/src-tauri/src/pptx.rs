@@ -0,0 +1,640 @@
+use crate::chart::{text_spans, tile, Anchor, Chart, Color, Corners, LineStyle, Shape, Stroke};
+use crate::palette;
+use crate::xml::{escape_xml, relationships, Package, REL, XML_HEADER};
+use std::fmt::Write as _;
+
+/// 16:9 slide in CSS pixels (13.333in × 7.5in)
+const SLIDE_WIDTH: f64 = 1280.0;
+const SLIDE_HEIGHT: f64 = 720.0;
+const SLIDE_MARGIN: f64 = 24.0;
+const EMU_PER_PX: f64 = 9525.0;
+/// Font for every text box; its metrics are closest to the Helvetica the layout measures with
+const TYPEFACE: &str = "Arial";
+
+const NS: &str = r#"xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:p="http://schemas.openxmlformats.org/presentationml/2006/main""#;
+const GROUP_PROPERTIES: &str = r#"<p:nvGrpSpPr><p:cNvPr id="1" name=""/><p:cNvGrpSpPr/><p:nvPr/></p:nvGrpSpPr><p:grpSpPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="0" cy="0"/><a:chOff x="0" y="0"/><a:chExt cx="0" cy="0"/></a:xfrm></p:grpSpPr>"#;
+
+/// Write a laid out chart as a 16:9 PowerPoint deck of native shapes: bars and
+/// backgrounds are rectangles, labels are text boxes and grid lines and
+/// milestone markers are connectors, all grouped per slide so the timeline can
+/// be moved as one piece or edited shape by shape. With `weeks_per_slide` the
+/// timeline is split at week starts, repeating the task column and the header
+/// on every slide.
+pub fn to_pptx(chart: &Chart, title: &str, weeks_per_slide: Option<u32>) -> Result<Vec<u8>, String> {
+    let tx = chart.timeline_x;
+    let end = chart.column_breaks.last().copied().unwrap_or(chart.width);
+    let slices = match weeks_per_slide.filter(|weeks| *weeks > 0) {
+        None => vec![(tx, end)],
+        Some(weeks) => {
+            let week_width = std::iter::once(tx)
+                .chain(chart.column_breaks.iter().copied())
+                .collect::<Vec<_>>()
+                .windows(2)
+                .map(|pair| pair[1] - pair[0])
+                .fold(0.0, f64::max);
+            // Slides may run short of the requested weeks to keep labels whole
+            let text_spans = text_spans(&chart.shapes, chart.header_bottom);
+            tile(
+                tx,
+                &chart.column_breaks,
+                |b| !text_spans.iter().any(|(x0, x1)| *x0 < b && b < *x1),
+                week_width * weeks as f64,
+                0.0,
+            )
+        }
+    };
+
+    // One scale for every slide so the rows line up when flipping through
+    let widest = slices.iter().map(|(x0, x1)| tx + x1 - x0).fold(0.0, f64::max);
+    let scale = ((SLIDE_WIDTH - 2.0 * SLIDE_MARGIN) / widest).min((SLIDE_HEIGHT - 2.0 * SLIDE_MARGIN) / chart.height);
+    let origin = (
+        (SLIDE_WIDTH - widest * scale) / 2.0,
+        (SLIDE_HEIGHT - chart.height * scale) / 2.0,
+    );
+
+    // The timeline's left edge bounds the first month like a divider
+    let mut cells = vec![(tx, 0.0, f64::INFINITY, chart.header_bottom)];
+    collect_cells(&chart.shapes, chart.header_bottom, &mut cells);
+    let slides: Vec<String> = slices
+        .iter()
+        .map(|&(x0, x1)| {
+            let mut slide = Slide::new(scale, origin, &cells);
+            if x0 <= tx + 0.01 {
+                slide.region(chart, 0.0, x1, 0.0);
+            } else {
+                slide.region(chart, 0.0, tx, 0.0);
+                slide.region(chart, x0, x1, tx - x0);
+            }
+            slide.finish(tx + x1 - x0, chart.height)
+        })
+        .collect();
+
+    write_package(title, &slides)
+}
+
+/// Clipping bounds in chart coordinates plus the x offset of the region on the slide
+#[derive(Clone, Copy)]
+struct Bounds {
+    x0: f64,
+    y0: f64,
+    x1: f64,
+    y1: f64,
+    shift: f64,
+}
+
+/// Shape tree of one slide
+struct Slide<'a> {
+    xml: String,
+    next_id: u32,
+    scale: f64,
+    origin: (f64, f64),
+    /// Boxes centered labels may sit in, from `collect_cells`
+    cells: &'a [(f64, f64, f64, f64)],
+}
+
+impl<'a> Slide<'a> {
+    fn new(scale: f64, origin: (f64, f64), cells: &'a [(f64, f64, f64, f64)]) -> Self {
+        Slide {
+            xml: String::new(),
+            // 1 is the slide's shape tree and 2 the chart group
+            next_id: 3,
+            scale,
+            origin,
+            cells,
+        }
+    }
+
+    /// Draw the part of the chart between `x0` and `x1`, moved right by `shift`
+    fn region(&mut self, chart: &Chart, x0: f64, x1: f64, shift: f64) {
+        let bounds = Bounds {
+            x0,
+            y0: f64::NEG_INFINITY,
+            x1,
+            y1: f64::INFINITY,
+            shift,
+        };
+        for shape in &chart.shapes {
+            self.shape(shape, bounds, chart.drop_shadow);
+        }
+    }
+
+    /// Slide XML with every shape drawn so far in one group of `width` × `height` chart pixels
+    fn finish(self, width: f64, height: f64) -> String {
+        let (x, y) = (self.emu_x(0.0), self.emu_y(0.0));
+        let (cx, cy) = (self.emu(width), self.emu(height));
+        format!(
+            r#"{XML_HEADER}<p:sld {NS}><p:cSld><p:spTree>{GROUP_PROPERTIES}<p:grpSp><p:nvGrpSpPr><p:cNvPr id="2" name="Gantt Chart"/><p:cNvGrpSpPr/><p:nvPr/></p:nvGrpSpPr><p:grpSpPr><a:xfrm><a:off x="{x}" y="{y}"/><a:ext cx="{cx}" cy="{cy}"/><a:chOff x="{x}" y="{y}"/><a:chExt cx="{cx}" cy="{cy}"/></a:xfrm></p:grpSpPr>{shapes}</p:grpSp></p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sld>"#,
+            shapes = self.xml
+        )
+    }
+
+    fn shape(&mut self, shape: &Shape, bounds: Bounds, drop_shadow: bool) {
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                w,
+                h,
+                corners,
+                fill,
+                stroke,
+                shadow,
+            } => {
+                let Some((x, y, w, h, corners)) = clamp_rect(*x, *y, *w, *h, *corners, bounds) else {
+                    return;
+                };
+                let mut properties = self.transform(x + bounds.shift, y, w, h, false, false);
+                geometry(&mut properties, w, h, &corners, self.scale);
+                match fill {
+                    Some(fill) => properties.push_str(&solid_fill(fill)),
+                    None => properties.push_str("<a:noFill/>"),
+                }
+                match stroke {
+                    Some(stroke) => properties.push_str(&self.outline(stroke)),
+                    None => properties.push_str("<a:ln><a:noFill/></a:ln>"),
+                }
+                if *shadow && drop_shadow {
+                    // The SVG's feDropShadow: 4px down, 6px blur, 15% black
+                    let _ = write!(
+                        properties,
+                        r#"<a:effectLst><a:outerShdw blurRad="{}" dist="{}" dir="5400000" algn="t" rotWithShape="0"><a:srgbClr val="000000"><a:alpha val="15000"/></a:srgbClr></a:outerShdw></a:effectLst>"#,
+                        self.emu(12.0),
+                        self.emu(4.0)
+                    );
+                }
+                self.push_shape("Rectangle", &properties, "");
+            }
+            Shape::Line { x1, y1, x2, y2, stroke } => {
+                let (lx0, lx1) = (x1.min(*x2).max(bounds.x0), x1.max(*x2).min(bounds.x1));
+                let (ly0, ly1) = (y1.min(*y2).max(bounds.y0), y1.max(*y2).min(bounds.y1));
+                if lx1 < lx0 || ly1 < ly0 || (lx1 - lx0 < 0.01 && (*x1 < bounds.x0 || *x1 >= bounds.x1)) {
+                    return;
+                }
+                let mut properties = self.transform(lx0 + bounds.shift, ly0, lx1 - lx0, ly1 - ly0, x2 < x1, y2 < y1);
+                properties.push_str(r#"<a:prstGeom prst="line"><a:avLst/></a:prstGeom>"#);
+                properties.push_str(&self.outline(stroke));
+                let id = self.next_id();
+                let _ = write!(
+                    self.xml,
+                    r#"<p:cxnSp><p:nvCxnSpPr><p:cNvPr id="{id}" name="Connector {id}"/><p:cNvCxnSpPr/><p:nvPr/></p:nvCxnSpPr><p:spPr>{properties}</p:spPr></p:cxnSp>"#
+                );
+            }
+            Shape::Text {
+                text,
+                size,
+                weight,
+                color,
+                anchor,
+                y,
+                ..
+            } => {
+                let (left, top, right, _) = shape.bounds();
+                if *y <= bounds.y0 || top >= bounds.y1 {
+                    return;
+                }
+                let width = right - left;
+                let fits = |from: f64, to: f64| width <= to - from + 0.01;
+                let left = if left >= bounds.x0 && right <= bounds.x1 {
+                    left
+                } else if let Some((from, to)) = self
+                    .cell(shape)
+                    .map(|(cell_x0, cell_x1)| (cell_x0.max(bounds.x0), cell_x1.min(bounds.x1)))
+                    .filter(|(from, to)| fits(*from, *to))
+                {
+                    // A month, bar or milestone label cut by the slide edge is
+                    // centered again in what is left of its cell
+                    (from + to - width) / 2.0
+                } else if right > bounds.x0 && left < bounds.x1 && fits(bounds.x0, bounds.x1) {
+                    left.clamp(bounds.x0, bounds.x1 - width)
+                } else {
+                    return;
+                };
+                let pad = 2.0;
+                let mut properties =
+                    self.transform(left - pad + bounds.shift, y - size * 0.92, width + 2.0 * pad, size * 1.2, false, false);
+                properties.push_str(r#"<a:prstGeom prst="rect"><a:avLst/></a:prstGeom><a:noFill/>"#);
+                let align = match anchor {
+                    Anchor::Start => "l",
+                    Anchor::Middle => "ctr",
+                };
+                // Slide text is sized in hundredths of a point, at 0.75pt per pixel
+                let points = ((size * self.scale * 75.0).round() as u32).max(100);
+                let body = format!(
+                    r#"<p:txBody><a:bodyPr wrap="none" lIns="0" tIns="0" rIns="0" bIns="0" anchor="t"><a:noAutofit/></a:bodyPr><a:lstStyle/><a:p><a:pPr algn="{align}"><a:lnSpc><a:spcPct val="100000"/></a:lnSpc></a:pPr><a:r><a:rPr lang="en-US" sz="{points}" b="{bold}" dirty="0">{fill}<a:latin typeface="{TYPEFACE}"/><a:cs typeface="{TYPEFACE}"/></a:rPr><a:t>{text}</a:t></a:r></a:p></p:txBody>"#,
+                    bold = u8::from(*weight >= 600),
+                    fill = solid_fill(color),
+                    text = escape_xml(text)
+                );
+                self.push_shape("TextBox", &properties, &body);
+            }
+            Shape::Hatch { x, y, w, h, color, .. } => {
+                let Some((x, y, w, h, _)) = clamp_rect(*x, *y, *w, *h, Corners::default(), bounds) else {
+                    return;
+                };
+                let mut properties = self.transform(x + bounds.shift, y, w, h, false, false);
+                properties.push_str(r#"<a:prstGeom prst="rect"><a:avLst/></a:prstGeom>"#);
+                let _ = write!(
+                    properties,
+                    r#"<a:pattFill prst="wdDnDiag"><a:fgClr>{}</a:fgClr><a:bgClr><a:srgbClr val="FFFFFF"><a:alpha val="0"/></a:srgbClr></a:bgClr></a:pattFill><a:ln><a:noFill/></a:ln>"#,
+                    color_element(color)
+                );
+                self.push_shape("Pattern", &properties, "");
+            }
+            Shape::Clip { x, y, w, h, shapes, .. } => {
+                // Slides cannot clip, so the children are cut to the clip rectangle instead
+                let inner = Bounds {
+                    x0: bounds.x0.max(*x),
+                    y0: bounds.y0.max(*y),
+                    x1: bounds.x1.min(x + w),
+                    y1: bounds.y1.min(y + h),
+                    shift: bounds.shift,
+                };
+                if inner.x1 > inner.x0 && inner.y1 > inner.y0 {
+                    for shape in shapes {
+                        self.shape(shape, inner, drop_shadow);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Horizontal extent of the smallest cell around a text, if the text is centered in it
+    fn cell(&self, text: &Shape) -> Option<(f64, f64)> {
+        let (left, top, right, bottom) = text.bounds();
+        let (x, y) = ((left + right) / 2.0, (top + bottom) / 2.0);
+        let (mut cell_x0, mut cell_x1) = (f64::NEG_INFINITY, f64::INFINITY);
+        for &(x0, y0, x1, y1) in self.cells {
+            if (x0..=x1).contains(&x) && (y0..=y1).contains(&y) {
+                cell_x0 = cell_x0.max(x0);
+                cell_x1 = cell_x1.min(x1);
+            }
+        }
+        ((cell_x0 + cell_x1 - 2.0 * x).abs() < 2.0).then_some((cell_x0, cell_x1))
+    }
+
+    fn push_shape(&mut self, name: &str, properties: &str, body: &str) {
+        let id = self.next_id();
+        let text_box = if body.is_empty() { "" } else { r#" txBox="1""# };
+        let _ = write!(
+            self.xml,
+            r#"<p:sp><p:nvSpPr><p:cNvPr id="{id}" name="{name} {id}"/><p:cNvSpPr{text_box}/><p:nvPr/></p:nvSpPr><p:spPr>{properties}</p:spPr>{body}</p:sp>"#
+        );
+    }
+
+    fn next_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn transform(&self, x: f64, y: f64, w: f64, h: f64, flip_h: bool, flip_v: bool) -> String {
+        let flips = match (flip_h, flip_v) {
+            (false, false) => "",
+            (true, false) => r#" flipH="1""#,
+            (false, true) => r#" flipV="1""#,
+            (true, true) => r#" flipH="1" flipV="1""#,
+        };
+        format!(
+            r#"<a:xfrm{}><a:off x="{}" y="{}"/><a:ext cx="{}" cy="{}"/></a:xfrm>"#,
+            flips,
+            self.emu_x(x),
+            self.emu_y(y),
+            self.emu(w),
+            self.emu(h)
+        )
+    }
+
+    fn outline(&self, stroke: &Stroke) -> String {
+        let dash = match stroke.style {
+            LineStyle::Solid => "solid",
+            LineStyle::Dashed => "dash",
+            LineStyle::Dotted => "sysDot",
+        };
+        format!(
+            r#"<a:ln w="{}">{}<a:prstDash val="{}"/></a:ln>"#,
+            self.emu(stroke.width),
+            solid_fill(&stroke.color),
+            dash
+        )
+    }
+
+    fn emu(&self, px: f64) -> i64 {
+        (px * self.scale * EMU_PER_PX).round() as i64
+    }
+
+    fn emu_x(&self, x: f64) -> i64 {
+        ((self.origin.0 + x * self.scale) * EMU_PER_PX).round() as i64
+    }
+
+    fn emu_y(&self, y: f64) -> i64 {
+        ((self.origin.1 + y * self.scale) * EMU_PER_PX).round() as i64
+    }
+}
+
+/// Rectangles and the header's vertical dividers; a centered label belongs to
+/// the closest edges on either side of it. Grid lines in the rows are left out
+/// so bar labels keep to their bars.
+fn collect_cells(shapes: &[Shape], header_bottom: f64, cells: &mut Vec<(f64, f64, f64, f64)>) {
+    for shape in shapes {
+        match shape {
+            Shape::Rect { x, y, w, h, .. } => {
+                cells.push((*x, *y, x + w, y + h));
+            }
+            Shape::Line { x1, y1, x2, y2, .. } if x1 == x2 && y1.max(*y2) <= header_bottom => {
+                // A divider bounds cells on both sides
+                cells.push((*x1, y1.min(*y2), f64::INFINITY, y1.max(*y2)));
+                cells.push((f64::NEG_INFINITY, y1.min(*y2), *x1, y1.max(*y2)));
+            }
+            Shape::Clip { shapes, .. } => collect_cells(shapes, header_bottom, cells),
+            _ => {}
+        }
+    }
+}
+
+/// Part of a rectangle inside `bounds`; corners on a cut edge become square
+fn clamp_rect(x: f64, y: f64, w: f64, h: f64, corners: Corners, bounds: Bounds) -> Option<(f64, f64, f64, f64, Corners)> {
+    let (x0, y0) = (x.max(bounds.x0), y.max(bounds.y0));
+    let (x1, y1) = ((x + w).min(bounds.x1), (y + h).min(bounds.y1));
+    if x1 - x0 < 0.01 || y1 - y0 < 0.01 {
+        return None;
+    }
+    let (left, top, right, bottom) = (x0 > x, y0 > y, x1 < x + w, y1 < y + h);
+    let corners = Corners {
+        top_left: if left || top { 0.0 } else { corners.top_left },
+        top_right: if right || top { 0.0 } else { corners.top_right },
+        bottom_right: if right || bottom { 0.0 } else { corners.bottom_right },
+        bottom_left: if left || bottom { 0.0 } else { corners.bottom_left },
+    };
+    Some((x0, y0, x1 - x0, y1 - y0, corners))
+}
+
+/// Preset geometry for square and evenly rounded rectangles, a custom path otherwise
+fn geometry(out: &mut String, w: f64, h: f64, corners: &Corners, scale: f64) {
+    let short = w.min(h);
+    let uniform = corners.top_left == corners.top_right
+        && corners.top_left == corners.bottom_right
+        && corners.top_left == corners.bottom_left;
+    if corners.is_square() {
+        out.push_str(r#"<a:prstGeom prst="rect"><a:avLst/></a:prstGeom>"#);
+    } else if uniform {
+        // Radius as a fraction of the short side, in 1/100000
+        let adj = ((corners.top_left / short * 100_000.0).round() as i64).min(50_000);
+        let _ = write!(
+            out,
+            r#"<a:prstGeom prst="roundRect"><a:avLst><a:gd name="adj" fmla="val {}"/></a:avLst></a:prstGeom>"#,
+            adj
+        );
+    } else {
+        let e = |px: f64| (px * scale * EMU_PER_PX).round() as i64;
+        let r = |radius: f64| e(radius.min(short / 2.0));
+        let (cw, ch) = (e(w), e(h));
+        let (tl, tr, br, bl) = (
+            r(corners.top_left),
+            r(corners.top_right),
+            r(corners.bottom_right),
+            r(corners.bottom_left),
+        );
+        // Angles in 1/60000 of a degree, clockwise from the positive x axis
+        let _ = write!(
+            out,
+            concat!(
+                r#"<a:custGeom><a:avLst/><a:gdLst/><a:ahLst/><a:cxnLst/><a:rect l="0" t="0" r="r" b="b"/><a:pathLst><a:path w="{cw}" h="{ch}">"#,
+                r#"<a:moveTo><a:pt x="{tl}" y="0"/></a:moveTo><a:lnTo><a:pt x="{tr_x}" y="0"/></a:lnTo><a:arcTo wR="{tr}" hR="{tr}" stAng="16200000" swAng="5400000"/>"#,
+                r#"<a:lnTo><a:pt x="{cw}" y="{br_y}"/></a:lnTo><a:arcTo wR="{br}" hR="{br}" stAng="0" swAng="5400000"/>"#,
+                r#"<a:lnTo><a:pt x="{bl}" y="{ch}"/></a:lnTo><a:arcTo wR="{bl}" hR="{bl}" stAng="5400000" swAng="5400000"/>"#,
+                r#"<a:lnTo><a:pt x="0" y="{tl}"/></a:lnTo><a:arcTo wR="{tl}" hR="{tl}" stAng="10800000" swAng="5400000"/>"#,
+                r#"<a:close/></a:path></a:pathLst></a:custGeom>"#
+            ),
+            cw = cw,
+            ch = ch,
+            tl = tl,
+            tr = tr,
+            br = br,
+            bl = bl,
+            tr_x = cw - tr,
+            br_y = ch - br
+        );
+    }
+}
+
+fn solid_fill(color: &Color) -> String {
+    format!("<a:solidFill>{}</a:solidFill>", color_element(color))
+}
+
+fn color_element(color: &Color) -> String {
+    let hex = format!("{:02X}{:02X}{:02X}", color.r, color.g, color.b);
+    if color.a < 1.0 {
+        format!(
+            r#"<a:srgbClr val="{}"><a:alpha val="{}"/></a:srgbClr>"#,
+            hex,
+            (color.a * 100_000.0).round() as i64
+        )
+    } else {
+        format!(r#"<a:srgbClr val="{}"/>"#, hex)
+    }
+}
+
+/// Zip the slides with a blank master and layout and a theme in the brand colors
+fn write_package(title: &str, slides: &[String]) -> Result<Vec<u8>, String> {
+    const PRESENTATION: &str = "application/vnd.openxmlformats-officedocument.presentationml";
+    let mut slide_ids = String::new();
+    let mut presentation_rels = format!(
+        concat!(
+            r#"<Relationship Id="rId1" Type="{rel}/slideMaster" Target="slideMasters/slideMaster1.xml"/>"#,
+            r#"<Relationship Id="rId2" Type="{rel}/theme" Target="theme/theme1.xml"/>"#,
+            r#"<Relationship Id="rId3" Type="{rel}/presProps" Target="presProps.xml"/>"#,
+            r#"<Relationship Id="rId4" Type="{rel}/viewProps" Target="viewProps.xml"/>"#,
+            r#"<Relationship Id="rId5" Type="{rel}/tableStyles" Target="tableStyles.xml"/>"#,
+        ),
+        rel = REL
+    );
+    for number in 1..=slides.len() {
+        let _ = write!(slide_ids, r#"<p:sldId id="{}" r:id="rId{}"/>"#, 255 + number, 5 + number);
+        let _ = write!(
+            presentation_rels,
+            r#"<Relationship Id="rId{}" Type="{}/slide" Target="slides/slide{}.xml"/>"#,
+            5 + number,
+            REL,
+            number
+        );
+    }
+
+    let mut package = Package::new(
+        "PPTX",
+        title,
+        "ppt/presentation.xml",
+        &format!("{PRESENTATION}.presentation.main+xml"),
+        &format!("<Slides>{}</Slides>", slides.len()),
+    );
+    package.add(
+        "ppt/presentation.xml",
+        None,
+        format!(
+            r#"{XML_HEADER}<p:presentation {NS} saveSubsetFonts="1"><p:sldMasterIdLst><p:sldMasterId id="2147483648" r:id="rId1"/></p:sldMasterIdLst><p:sldIdLst>{slide_ids}</p:sldIdLst><p:sldSz cx="12192000" cy="6858000"/><p:notesSz cx="6858000" cy="9144000"/></p:presentation>"#
+        ),
+    );
+    package.add("ppt/_rels/presentation.xml.rels", None, relationships(&presentation_rels));
+    package.add(
+        "ppt/presProps.xml",
+        Some(&format!("{PRESENTATION}.presProps+xml")),
+        format!("{XML_HEADER}<p:presentationPr {NS}/>"),
+    );
+    package.add(
+        "ppt/viewProps.xml",
+        Some(&format!("{PRESENTATION}.viewProps+xml")),
+        format!("{XML_HEADER}<p:viewPr {NS}/>"),
+    );
+    package.add(
+        "ppt/tableStyles.xml",
+        Some(&format!("{PRESENTATION}.tableStyles+xml")),
+        format!(
+            r#"{XML_HEADER}<a:tblStyleLst xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" def="{{5C22544A-7EE6-4342-B048-85BDC9FD1C3A}}"/>"#
+        ),
+    );
+    package.add(
+        "ppt/slideMasters/slideMaster1.xml",
+        Some(&format!("{PRESENTATION}.slideMaster+xml")),
+        format!(
+            r#"{XML_HEADER}<p:sldMaster {NS}><p:cSld><p:bg><p:bgRef idx="1001"><a:schemeClr val="bg1"/></p:bgRef></p:bg><p:spTree>{GROUP_PROPERTIES}</p:spTree></p:cSld><p:clrMap bg1="lt1" tx1="dk1" bg2="lt2" tx2="dk2" accent1="accent1" accent2="accent2" accent3="accent3" accent4="accent4" accent5="accent5" accent6="accent6" hlink="hlink" folHlink="folHlink"/><p:sldLayoutIdLst><p:sldLayoutId id="2147483649" r:id="rId1"/></p:sldLayoutIdLst></p:sldMaster>"#
+        ),
+    );
+    package.add(
+        "ppt/slideMasters/_rels/slideMaster1.xml.rels",
+        None,
+        relationships(&format!(
+            r#"<Relationship Id="rId1" Type="{rel}/slideLayout" Target="../slideLayouts/slideLayout1.xml"/><Relationship Id="rId2" Type="{rel}/theme" Target="../theme/theme1.xml"/>"#,
+            rel = REL
+        )),
+    );
+    package.add(
+        "ppt/slideLayouts/slideLayout1.xml",
+        Some(&format!("{PRESENTATION}.slideLayout+xml")),
+        format!(
+            r#"{XML_HEADER}<p:sldLayout {NS} type="blank" preserve="1"><p:cSld name="Blank"><p:spTree>{GROUP_PROPERTIES}</p:spTree></p:cSld><p:clrMapOvr><a:masterClrMapping/></p:clrMapOvr></p:sldLayout>"#
+        ),
+    );
+    package.add(
+        "ppt/slideLayouts/_rels/slideLayout1.xml.rels",
+        None,
+        relationships(&format!(
+            r#"<Relationship Id="rId1" Type="{}/slideMaster" Target="../slideMasters/slideMaster1.xml"/>"#,
+            REL
+        )),
+    );
+    package.add(
+        "ppt/theme/theme1.xml",
+        Some("application/vnd.openxmlformats-officedocument.theme+xml"),
+        theme(),
+    );
+    for (idx, slide) in slides.iter().enumerate() {
+        package.add(
+            format!("ppt/slides/slide{}.xml", idx + 1),
+            Some(&format!("{PRESENTATION}.slide+xml")),
+            slide.as_str(),
+        );
+        package.add(
+            format!("ppt/slides/_rels/slide{}.xml.rels", idx + 1),
+            None,
+            relationships(&format!(
+                r#"<Relationship Id="rId1" Type="{}/slideLayout" Target="../slideLayouts/slideLayout1.xml"/>"#,
+                REL
+            )),
+        );
+    }
+    package.finish()
+}
+
+/// Theme with the brand palette as its accent colors, so recoloring in
+/// PowerPoint offers them first
+fn theme() -> String {
+    let color = |hex: &str| hex.trim_start_matches('#').to_uppercase();
+    let fill = r#"<a:solidFill><a:schemeClr val="phClr"/></a:solidFill>"#;
+    let line = r#"<a:ln w="6350"><a:solidFill><a:schemeClr val="phClr"/></a:solidFill></a:ln>"#;
+    let effect = "<a:effectStyle><a:effectLst/></a:effectStyle>";
+    let font = format!(r#"<a:latin typeface="{TYPEFACE}"/><a:ea typeface=""/><a:cs typeface=""/>"#);
+    format!(
+        concat!(
+            r#"{header}<a:theme xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" name="GanttGen"><a:themeElements>"#,
+            r#"<a:clrScheme name="GanttGen"><a:dk1><a:srgbClr val="000000"/></a:dk1><a:lt1><a:srgbClr val="FFFFFF"/></a:lt1>"#,
+            r#"<a:dk2><a:srgbClr val="{dk2}"/></a:dk2><a:lt2><a:srgbClr val="{lt2}"/></a:lt2>"#,
+            r#"<a:accent1><a:srgbClr val="{a1}"/></a:accent1><a:accent2><a:srgbClr val="{a2}"/></a:accent2>"#,
+            r#"<a:accent3><a:srgbClr val="{a3}"/></a:accent3><a:accent4><a:srgbClr val="{a4}"/></a:accent4>"#,
+            r#"<a:accent5><a:srgbClr val="{a5}"/></a:accent5><a:accent6><a:srgbClr val="{a6}"/></a:accent6>"#,
+            r#"<a:hlink><a:srgbClr val="0563C1"/></a:hlink><a:folHlink><a:srgbClr val="954F72"/></a:folHlink></a:clrScheme>"#,
+            r#"<a:fontScheme name="GanttGen"><a:majorFont>{font}</a:majorFont><a:minorFont>{font}</a:minorFont></a:fontScheme>"#,
+            r#"<a:fmtScheme name="GanttGen"><a:fillStyleLst>{fill}{fill}{fill}</a:fillStyleLst><a:lnStyleLst>{line}{line}{line}</a:lnStyleLst>"#,
+            r#"<a:effectStyleLst>{effect}{effect}{effect}</a:effectStyleLst><a:bgFillStyleLst>{fill}{fill}{fill}</a:bgFillStyleLst></a:fmtScheme>"#,
+            r#"</a:themeElements></a:theme>"#
+        ),
+        header = XML_HEADER,
+        dk2 = color(palette::PURPLE_5),
+        lt2 = color(palette::PURPLE_1),
+        a1 = color(palette::RED_1),
+        a2 = color(palette::PURPLE_4),
+        a3 = color(palette::RED_3),
+        a4 = color(palette::PURPLE_3),
+        a5 = color(palette::RED_2),
+        a6 = color(palette::PURPLE_2),
+        font = font,
+        fill = fill,
+        line = line,
+        effect = effect
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::{layout, ChartOptions};
+    use crate::project::{Project, Task};
+    use std::io::{Cursor, Read as _};
+
+    /// 2024-12-31 to 2025-03-31 once padded: a six-day week, twelve full
+    /// weeks and a single day
+    fn chart() -> Chart {
+        let project = Project {
+            timeline_start: "2025-01-01".to_string(),
+            timeline_end: "2025-03-30".to_string(),
+            tasks: vec![Task {
+                name: "Build".to_string(),
+                start: "2025-01-02".to_string(),
+                end: "2025-01-03".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        layout(&project, &ChartOptions::default()).unwrap()
+    }
+
+    fn slide_count(pptx: &[u8]) -> usize {
+        let mut archive = zip::ZipArchive::new(Cursor::new(pptx)).unwrap();
+        let slides = archive
+            .file_names()
+            .filter(|name| name.starts_with("ppt/slides/slide") && name.ends_with(".xml"))
+            .count();
+
+        let mut presentation = String::new();
+        archive.by_name("ppt/presentation.xml").unwrap().read_to_string(&mut presentation).unwrap();
+        assert_eq!(presentation.matches("<p:sldId ").count(), slides);
+        let mut app = String::new();
+        archive.by_name("docProps/app.xml").unwrap().read_to_string(&mut app).unwrap();
+        assert!(app.contains(&format!("<Slides>{}</Slides>", slides)));
+        slides
+    }
+
+    #[test]
+    fn whole_timeline_fits_one_slide() {
+        let chart = chart();
+        assert_eq!(slide_count(&to_pptx(&chart, "Plan", None).unwrap()), 1);
+        assert_eq!(slide_count(&to_pptx(&chart, "Plan", Some(0)).unwrap()), 1);
+        assert_eq!(slide_count(&to_pptx(&chart, "Plan", Some(52)).unwrap()), 1);
+    }
+
+    #[test]
+    fn weeks_per_slide_splits_the_timeline() {
+        let chart = chart();
+        // 27, 28, 28 and 8 days
+        assert_eq!(slide_count(&to_pptx(&chart, "Plan", Some(4)).unwrap()), 4);
+        assert_eq!(slide_count(&to_pptx(&chart, "Plan", Some(1)).unwrap()), 14);
+    }
+}
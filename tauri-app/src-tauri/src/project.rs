@@ -0,0 +1,458 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// Default SEI palette (alternating red/purple), same order as `BRAND_COLORS` in build.js
+pub const BRAND_COLORS: [&str; 6] = [
+    "#F01840", // Bright red
+    "#402848", // Dark purple
+    "#C01830", // Darker red
+    "#705E74", // Medium purple
+    "#901226", // Burgundy
+    "#2A1C30", // Deep purple
+];
+
+/// Maximum number of subtask columns read from a task row
+pub const MAX_SUBTASKS: usize = 10;
+
+fn default_title() -> String {
+    "PROJECT TIMELINE".to_string()
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Write whole-hour values as integers so the JSON matches what JS produces
+fn serialize_hours<S: Serializer>(hours: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    if hours.fract() == 0.0 && hours.abs() < 1e15 {
+        serializer.serialize_i64(*hours as i64)
+    } else {
+        serializer.serialize_f64(*hours)
+    }
+}
+
+/// Read text the way build.js uses it: numbers become their digits, null is empty
+fn deserialize_text<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(text) => text,
+        Value::Number(number) => number.to_string(),
+        Value::Bool(flag) => flag.to_string(),
+        _ => String::new(),
+    })
+}
+
+/// Hours given as a number or numeric text; anything else counts as none
+fn deserialize_hours<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    Ok(json_number(&Value::deserialize(deserializer)?).unwrap_or(0.0))
+}
+
+/// An index given as a number or numeric text, like build.js's `Number(value)`.
+/// Negative, fractional or non-numeric values are no index rather than an error.
+fn deserialize_index<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<usize>, D::Error> {
+    Ok(json_number(&Value::deserialize(deserializer)?)
+        .filter(|n| *n >= 0.0 && n.fract() == 0.0 && *n <= u32::MAX as f64)
+        .map(|n| n as usize))
+}
+
+fn json_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+    .filter(|n: &f64| n.is_finite())
+}
+
+/// Normalized project model, serialized with the same keys as the JSON config
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Project {
+    #[serde(default = "default_title")]
+    pub title: String,
+    #[serde(default)]
+    pub timeline_start: String,
+    #[serde(default)]
+    pub timeline_end: String,
+    #[serde(default = "default_true")]
+    pub show_milestones: bool,
+    #[serde(default)]
+    pub palette: Vec<String>,
+    #[serde(default)]
+    pub tasks: Vec<Task>,
+    #[serde(default)]
+    pub milestones: Vec<Milestone>,
+    #[serde(default)]
+    pub pause_periods: Vec<PausePeriod>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Task {
+    #[serde(default, deserialize_with = "deserialize_text")]
+    pub name: String,
+    #[serde(default, deserialize_with = "deserialize_text")]
+    pub start: String,
+    #[serde(default, deserialize_with = "deserialize_text")]
+    pub end: String,
+    #[serde(default, serialize_with = "serialize_hours", deserialize_with = "deserialize_hours")]
+    pub hours: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subtasks: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_index")]
+    pub color_index: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Milestone {
+    #[serde(default, deserialize_with = "deserialize_text")]
+    pub name: String,
+    #[serde(default, deserialize_with = "deserialize_text")]
+    pub date: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", deserialize_with = "deserialize_index")]
+    pub task_index: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PausePeriod {
+    #[serde(default, deserialize_with = "deserialize_text")]
+    pub start: String,
+    #[serde(default, deserialize_with = "deserialize_text")]
+    pub end: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+}
+
+impl Default for Project {
+    fn default() -> Self {
+        Project {
+            title: default_title(),
+            timeline_start: String::new(),
+            timeline_end: String::new(),
+            show_milestones: true,
+            palette: Vec::new(),
+            tasks: Vec::new(),
+            milestones: Vec::new(),
+            pause_periods: Vec::new(),
+        }
+    }
+}
+
+impl Project {
+    /// Fill in missing palette and task colors the same way build.js does
+    pub fn resolve_colors(&mut self) {
+        if self.palette.is_empty() {
+            self.palette = BRAND_COLORS.iter().map(|c| c.to_string()).collect();
+        }
+
+        let palette = &self.palette;
+        for (idx, task) in self.tasks.iter_mut().enumerate() {
+            // Prefer colorIndex over direct color when both are present
+            if let Some(color) = task.color_index.and_then(|i| palette.get(i)) {
+                task.color = Some(color.clone());
+            } else if task.color.is_none() {
+                task.color = Some(palette[idx % palette.len()].clone());
+            }
+        }
+    }
+}
+
+/// Serialize a project for saving under version control.
+///
+/// Only model fields are kept, in struct order, so transient keys such as
+/// `dropShadow`, `viewMode` or `palettePreset` are dropped. Dates are written
+/// as YYYY-MM-DD, a task's `color` is omitted when `colorIndex` already picks
+/// it, and line endings inside text become `\n`. The output is two-space
+/// indented with a trailing newline, so saving the same plan twice gives
+/// byte-identical files.
+pub fn to_canonical_json(project: &Project) -> Result<String, String> {
+    let mut project = project.clone();
+    let iso = |value: &mut String| {
+        if let Some(date) = normalize_date(value) {
+            *value = date;
+        }
+    };
+    let text = |value: &mut String| {
+        if value.contains('\r') {
+            *value = value.replace("\r\n", "\n").replace('\r', "\n");
+        }
+    };
+
+    text(&mut project.title);
+    iso(&mut project.timeline_start);
+    iso(&mut project.timeline_end);
+    for task in &mut project.tasks {
+        text(&mut task.name);
+        task.subtasks.iter_mut().for_each(text);
+        iso(&mut task.start);
+        iso(&mut task.end);
+        if task.color_index.is_some() {
+            task.color = None;
+        }
+    }
+    for milestone in &mut project.milestones {
+        text(&mut milestone.name);
+        iso(&mut milestone.date);
+    }
+    for pause in &mut project.pause_periods {
+        iso(&mut pause.start);
+        iso(&mut pause.end);
+        if let Some(label) = &mut pause.label {
+            text(label);
+        }
+    }
+
+    let mut json = serde_json::to_string_pretty(&project)
+        .map_err(|e| format!("Failed to serialize project: {}", e))?;
+    json.push('\n');
+    Ok(json)
+}
+
+/// Write line breaks as `\n`, the convention milestone names use in the
+/// Milestones sheet and CSV section. Backslashes are doubled so text that
+/// already contains `\n` reads back unchanged.
+pub fn escape_line_breaks(text: &str) -> String {
+    text.replace('\\', "\\\\").replace("\r\n", "\n").replace(['\n', '\r'], "\\n")
+}
+
+/// Undo `escape_line_breaks`. A backslash before anything but `n` or another
+/// backslash is kept, so hand-typed paths such as `C:\temp` survive.
+pub fn unescape_line_breaks(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some('n')) => {
+                out.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                out.push('\\');
+                chars.next();
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Convert an Excel serial day number (1900 date system) to a date
+pub fn excel_serial_to_date(serial: f64) -> Option<NaiveDate> {
+    if !serial.is_finite() || !(1.0..2_958_466.0).contains(&serial) {
+        return None;
+    }
+    // Day 0 is 1899-12-30 once Excel's phantom 1900-02-29 is accounted for
+    let base = NaiveDate::from_ymd_opt(1899, 12, 30)?;
+    base.checked_add_signed(Duration::days(serial.floor() as i64))
+}
+
+/// Normalize the date spellings found in workbooks and pasted tables to YYYY-MM-DD
+///
+/// Accepts ISO dates and datetimes, slash/dot separated dates (US month-first
+/// unless the first part can only be a day), month-name forms such as
+/// "Jan 6, 2025" or "6 January 2025", JS `Date.toString()` output, and Excel
+/// serial day numbers. Returns `None` when the value is not recognisable.
+pub fn normalize_date(value: &str) -> Option<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    parse_loose_date(value).map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+/// Parse a YYYY-MM-DD date of the project model; `what` names it in the error
+pub fn parse_date(value: &str, what: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("Invalid {} date: \"{}\" (expected YYYY-MM-DD)", what, value))
+}
+
+fn parse_loose_date(value: &str) -> Option<NaiveDate> {
+    // ISO date, optionally followed by a time component
+    let head: String = value.chars().take(10).collect();
+    if let Ok(date) = NaiveDate::parse_from_str(&head, "%Y-%m-%d") {
+        let rest = &value[head.len()..];
+        if rest.is_empty() || rest.starts_with('T') || rest.starts_with(' ') {
+            return Some(date);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y/%m/%d") {
+        return Some(date);
+    }
+    if let Ok(dt) = NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.fZ") {
+        return Some(dt.date());
+    }
+
+    // Excel serial day numbers (numeric cells that were not date formatted)
+    if value.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        if let Ok(serial) = value.parse::<f64>() {
+            if serial >= 20_000.0 {
+                return excel_serial_to_date(serial);
+            }
+        }
+    }
+
+    if let Some(date) = parse_numeric_date(value) {
+        return Some(date);
+    }
+
+    parse_named_month_date(value)
+}
+
+/// Dates like 1/6/2025, 01.06.25 or 25/12/2025
+fn parse_numeric_date(value: &str) -> Option<NaiveDate> {
+    let parts: Vec<&str> = value.split(['/', '.', '-']).collect();
+    if parts.len() != 3 || parts.iter().any(|p| p.is_empty() || !p.chars().all(|c| c.is_ascii_digit())) {
+        return None;
+    }
+    if parts[0].len() == 4 {
+        let year: i32 = parts[0].parse().ok()?;
+        return NaiveDate::from_ymd_opt(year, parts[1].parse().ok()?, parts[2].parse().ok()?);
+    }
+    let a: u32 = parts[0].parse().ok()?;
+    let b: u32 = parts[1].parse().ok()?;
+    let year = expand_year(parts[2])?;
+
+    // Month-first like the US spreadsheets we receive, unless that is impossible
+    let (month, day) = if a > 12 && b <= 12 { (b, a) } else { (a, b) };
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+fn expand_year(part: &str) -> Option<i32> {
+    let year: i32 = part.parse().ok()?;
+    match part.len() {
+        2 => Some(2000 + year),
+        4 => Some(year),
+        _ => None,
+    }
+}
+
+/// Dates like "Jan 6, 2025", "6 January 2025" or "Mon Jan 06 2025 00:00:00 GMT+0000"
+fn parse_named_month_date(value: &str) -> Option<NaiveDate> {
+    let tokens: Vec<&str> = value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|t| !t.is_empty())
+        .collect();
+
+    let month_pos = tokens.iter().position(|t| month_from_name(t).is_some())?;
+    let month = month_from_name(tokens[month_pos])?;
+
+    let numbers: Vec<&str> = tokens
+        .iter()
+        .enumerate()
+        .filter(|(i, t)| *i != month_pos && t.chars().all(|c| c.is_ascii_digit()))
+        .map(|(_, t)| *t)
+        .take(2)
+        .collect();
+    if numbers.len() != 2 {
+        return None;
+    }
+
+    let (day, year) = if numbers[0].len() == 4 {
+        (numbers[1].parse().ok()?, expand_year(numbers[0])?)
+    } else {
+        (numbers[0].parse().ok()?, expand_year(numbers[1])?)
+    };
+    let date = NaiveDate::from_ymd_opt(year, month, day)?;
+    (date.month() == month).then_some(date)
+}
+
+fn month_from_name(token: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let lower = token.trim_end_matches('.').to_lowercase();
+    if lower.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|m| lower.starts_with(m) && (lower.len() == 3 || full_month_matches(&lower)))
+        .map(|i| i as u32 + 1)
+}
+
+fn full_month_matches(lower: &str) -> bool {
+    const FULL: [&str; 13] = [
+        "january", "february", "march", "april", "may", "june", "july", "august", "september",
+        "sept", "october", "november", "december",
+    ];
+    FULL.contains(&lower)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loads_json_that_build_js_accepts() {
+        let project: Project = serde_json::from_str(
+            r##"{
+                "palette": ["#111111", "#222222", "#333333"],
+                "tasks": [
+                    { "start": "2025-01-06", "end": "2025-01-10", "colorIndex": "2", "hours": "7.5" },
+                    { "name": 2025, "start": null, "colorIndex": -1 },
+                    { "name": "Fractional", "colorIndex": 1.5 }
+                ],
+                "milestones": [{ "date": "2025-01-10", "taskIndex": "0" }],
+                "pausePeriods": [{ "start": "2025-01-07" }]
+            }"##,
+        )
+        .unwrap();
+
+        let tasks = &project.tasks;
+        assert_eq!(tasks[0].name, "");
+        assert_eq!(tasks[0].color_index, Some(2));
+        assert_eq!(tasks[0].hours, 7.5);
+        assert_eq!(tasks[1].name, "2025");
+        assert_eq!(tasks[1].start, "");
+        assert_eq!(tasks[1].color_index, None);
+        assert_eq!(tasks[2].color_index, None);
+        assert_eq!(project.milestones[0].name, "");
+        assert_eq!(project.milestones[0].task_index, Some(0));
+        assert_eq!(project.pause_periods[0].end, "");
+    }
+
+    #[test]
+    fn canonical_json_is_stable_and_round_trips() {
+        let saved: Project = serde_json::from_str(
+            r##"{
+                "title": "Launch\r\nplan",
+                "viewMode": "week",
+                "dropShadow": true,
+                "timelineStart": "1/6/2025",
+                "timelineEnd": "2025-06-30T00:00:00.000Z",
+                "palette": ["#111111", "#222222"],
+                "tasks": [
+                    { "name": "Design", "start": "Jan 6, 2025", "end": "2025-01-31",
+                      "hours": 40, "color": "#222222", "colorIndex": 1,
+                      "subtasks": ["Wireframes\rReview"] },
+                    { "name": "Build", "start": "2025-02-03", "end": "2025-03-28",
+                      "hours": 12.5, "color": "#ABCDEF" }
+                ],
+                "milestones": [{ "name": "Go\r\nlive", "date": "2025-03-28", "taskIndex": 1 }],
+                "pausePeriods": [{ "start": "2025-04-18", "end": "2025-04-21", "label": "Easter" }]
+            }"##,
+        )
+        .unwrap();
+
+        let json = to_canonical_json(&saved).unwrap();
+        assert!(json.ends_with("}\n"));
+        assert!(!json.contains("viewMode") && !json.contains("dropShadow") && !json.contains('\r'));
+        assert!(json.contains(r#""hours": 40,"#));
+
+        let read: Project = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.title, "Launch\nplan");
+        assert_eq!(read.timeline_start, "2025-01-06");
+        assert_eq!(read.timeline_end, "2025-06-30");
+        assert_eq!(read.tasks[0].start, "2025-01-06");
+        assert_eq!(read.tasks[0].color, None);
+        assert_eq!(read.tasks[0].color_index, Some(1));
+        assert_eq!(read.tasks[0].subtasks, ["Wireframes\nReview"]);
+        assert_eq!(read.tasks[1].color.as_deref(), Some("#ABCDEF"));
+        assert_eq!(read.milestones[0].name, "Go\nlive");
+        assert_eq!(to_canonical_json(&read).unwrap(), json);
+    }
+}
@@ -0,0 +1,367 @@
+use crate::chart::{Chart, Color};
+use crate::svg;
+use resvg::tiny_skia::{self, IntRect, Pixmap, PixmapPaint, Transform};
+use resvg::usvg::{self, fontdb};
+use std::sync::{Arc, OnceLock};
+
+/// Same resolution as the `deviceScaleFactor: 2` screenshots build.js takes
+pub const DEFAULT_DPI: f64 = 192.0;
+/// CSS pixels per inch
+const CSS_DPI: f64 = 96.0;
+const MIN_DPI: f64 = 24.0;
+const MAX_DPI: f64 = 600.0;
+/// The only fonts text is drawn with, so output doesn't depend on what is
+/// installed. DejaVu Sans Condensed advances are within about 1% of the
+/// Helvetica metrics `chart::text_width` lays text out with.
+const FONTS: [&[u8]; 2] = [
+    include_bytes!("../fonts/DejaVuSansCondensed.ttf"),
+    include_bytes!("../fonts/DejaVuSansCondensed-Bold.ttf"),
+];
+
+/// Background presets accepted besides CSS colors
+const NAMED_BACKGROUNDS: [(&str, &str); 2] = [("white", "#FFFFFF"), ("cream", "#FFFFF8")];
+const MAX_WIDTH: u32 = 16384;
+const MAX_PADDING: f64 = 500.0;
+
+/// Rasterization settings
+#[derive(Debug, Clone, Copy)]
+pub struct RasterOptions {
+    pub dpi: f64,
+    /// Exact output width in pixels, padding included; the scale is derived from it
+    pub width: Option<u32>,
+    /// Solid color behind the chart; transparent when unset
+    pub background: Option<Color>,
+    /// Margin around the cropped chart, in CSS pixels
+    pub padding: f64,
+}
+
+impl RasterOptions {
+    /// Options from a DPI, or a device scale factor or pixel width that takes
+    /// its place, a background (`transparent`, `white`, `cream` or a CSS color
+    /// such as `#RRGGBB` or `rgb()`) and padding in CSS pixels
+    pub fn new(
+        dpi: f64,
+        scale: Option<f64>,
+        width: Option<u32>,
+        background: Option<&str>,
+        padding: f64,
+    ) -> Result<Self, String> {
+        let dpi = match scale {
+            Some(scale) if !(MIN_DPI / CSS_DPI..=MAX_DPI / CSS_DPI).contains(&scale) => {
+                return Err(format!(
+                    "PNG scale factor must be between {} and {}, got {}",
+                    MIN_DPI / CSS_DPI,
+                    MAX_DPI / CSS_DPI,
+                    scale
+                ));
+            }
+            Some(scale) => scale * CSS_DPI,
+            None => dpi,
+        };
+        if !(MIN_DPI..=MAX_DPI).contains(&dpi) {
+            return Err(format!("PNG DPI must be between {} and {}, got {}", MIN_DPI, MAX_DPI, dpi));
+        }
+        if let Some(width) = width.filter(|w| !(1..=MAX_WIDTH).contains(w)) {
+            return Err(format!("PNG width must be between 1 and {} pixels, got {}", MAX_WIDTH, width));
+        }
+        if !(0.0..=MAX_PADDING).contains(&padding) {
+            return Err(format!("PNG padding must be between 0 and {} pixels, got {}", MAX_PADDING, padding));
+        }
+        let background = match background.map(str::trim).filter(|b| !b.is_empty()) {
+            None => None,
+            Some(value) if value.eq_ignore_ascii_case("transparent") => None,
+            Some(value) => {
+                let color = NAMED_BACKGROUNDS
+                    .iter()
+                    .find(|(name, _)| value.eq_ignore_ascii_case(name))
+                    .map_or(value, |(_, hex)| hex);
+                Some(Color::parse(color).ok_or_else(|| format!("Invalid PNG background color: \"{}\"", value))?)
+            }
+        };
+        Ok(RasterOptions { dpi, width, background, padding })
+    }
+
+    /// The same settings at a fixed pixel width, for thumbnails rendered straight at their size
+    pub fn thumbnail(self, width: u32) -> Result<Self, String> {
+        if !(1..=MAX_WIDTH).contains(&width) {
+            return Err(format!("Thumbnail width must be between 1 and {} pixels, got {}", MAX_WIDTH, width));
+        }
+        Ok(RasterOptions { width: Some(width), ..self })
+    }
+}
+
+/// A rendered chart, encoded on demand to any of the raster formats
+pub struct Image {
+    pixmap: Pixmap,
+}
+
+impl Image {
+    pub fn width(&self) -> u32 {
+        self.pixmap.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.pixmap.height()
+    }
+
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        self.pixmap
+            .encode_png()
+            .map_err(|e| format!("Failed to encode PNG: {}", e))
+    }
+
+    /// JPEG has no transparency, so transparent areas come out white
+    pub fn to_jpeg(&self, quality: u8) -> Result<Vec<u8>, String> {
+        let rgb: Vec<u8> = self
+            .pixmap
+            .pixels()
+            .iter()
+            .flat_map(|p| {
+                // Premultiplied, so compositing over white only adds the uncovered part
+                let white = 255 - p.alpha();
+                [p.red() + white, p.green() + white, p.blue() + white]
+            })
+            .collect();
+        let (width, height) = (self.width(), self.height());
+        let too_large = || format!("JPEG of {}x{} pixels is too large", width, height);
+        let mut data = Vec::new();
+        jpeg_encoder::Encoder::new(&mut data, quality)
+            .encode(
+                &rgb,
+                u16::try_from(width).map_err(|_| too_large())?,
+                u16::try_from(height).map_err(|_| too_large())?,
+                jpeg_encoder::ColorType::Rgb,
+            )
+            .map_err(|e| format!("Failed to encode JPEG: {}", e))?;
+        Ok(data)
+    }
+
+    /// Lossy WebP that keeps the transparency
+    pub fn to_webp(&self, quality: u8) -> Result<Vec<u8>, String> {
+        let rgba: Vec<u8> = self
+            .pixmap
+            .pixels()
+            .iter()
+            .flat_map(|p| {
+                let c = p.demultiply();
+                [c.red(), c.green(), c.blue(), c.alpha()]
+            })
+            .collect();
+        webp::Encoder::from_rgba(&rgba, self.width(), self.height())
+            .encode_simple(false, quality as f32)
+            .map(|data| data.to_vec())
+            .map_err(|e| format!("Failed to encode WebP: {:?}", e))
+    }
+}
+
+/// JPEG and WebP quality runs from 1 (smallest) to 100 (best)
+pub fn check_quality(format: &str, quality: u8) -> Result<(), String> {
+    if (1..=100).contains(&quality) {
+        Ok(())
+    } else {
+        Err(format!("{} quality must be between 1 and 100, got {}", format, quality))
+    }
+}
+
+/// Rasterize a laid out chart. The image is trimmed to its visible pixels
+/// like `cropPNGToVisible` in build.js before padding is added; the same
+/// chart and options always give the same bytes, on any machine.
+pub fn render(chart: &Chart, options: &RasterOptions) -> Result<Image, String> {
+    render_pixmap(chart, options).map(|pixmap| Image { pixmap })
+}
+
+fn render_pixmap(chart: &Chart, options: &RasterOptions) -> Result<Pixmap, String> {
+    let usvg_options = usvg::Options {
+        fontdb: fonts(),
+        ..usvg::Options::default()
+    };
+    let tree = usvg::Tree::from_str(&svg::to_svg(chart), &usvg_options)
+        .map_err(|e| format!("Failed to prepare chart for rasterizing: {}", e))?;
+
+    let scale = match options.width {
+        // Measure the visible chart once at the default resolution, then scale it to fill the width
+        Some(width) => {
+            let measure = (DEFAULT_DPI / CSS_DPI) as f32;
+            let visible = crop(rasterize(&tree, measure)?)?.width() as f32 / measure;
+            width as f32 / (visible + 2.0 * options.padding as f32)
+        }
+        None => (options.dpi / CSS_DPI) as f32,
+    };
+    let image = crop(rasterize(&tree, scale)?)?;
+
+    let padding = (options.padding as f32 * scale).round() as u32;
+    let width = options.width.unwrap_or(image.width() + 2 * padding);
+    let height = image.height() + 2 * padding;
+    let mut canvas = Pixmap::new(width, height)
+        .ok_or_else(|| format!("PNG of {}x{} pixels is too large", width, height))?;
+    if let Some(background) = options.background {
+        canvas.fill(tiny_skia::Color::from_rgba8(
+            background.r,
+            background.g,
+            background.b,
+            (background.a * 255.0).round() as u8,
+        ));
+    }
+    // Centered so rounding in the derived scale never shifts the chart to one side
+    let x = (width as i32 - image.width() as i32) / 2;
+    canvas.draw_pixmap(x, padding as i32, image.as_ref(), &PixmapPaint::default(), Transform::identity(), None);
+    Ok(canvas)
+}
+
+fn rasterize(tree: &usvg::Tree, scale: f32) -> Result<Pixmap, String> {
+    let size = tree.size();
+    let width = (size.width() * scale).ceil() as u32;
+    let height = (size.height() * scale).ceil() as u32;
+    let mut pixmap = Pixmap::new(width, height)
+        .ok_or_else(|| format!("PNG of {}x{} pixels is too large", width, height))?;
+    resvg::render(tree, Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    Ok(pixmap)
+}
+
+/// Trim a rendering to its visible pixels, leaving a blank one untouched
+fn crop(pixmap: Pixmap) -> Result<Pixmap, String> {
+    match visible_bounds(&pixmap) {
+        Some(bounds) => pixmap
+            .clone_rect(bounds)
+            .ok_or_else(|| "Failed to crop PNG to visible pixels".to_string()),
+        None => Ok(pixmap),
+    }
+}
+
+/// Smallest rectangle holding every pixel that is not fully transparent
+fn visible_bounds(pixmap: &Pixmap) -> Option<IntRect> {
+    let width = pixmap.width() as usize;
+    let (mut left, mut top, mut right, mut bottom) = (usize::MAX, usize::MAX, 0, 0);
+    for (idx, pixel) in pixmap.pixels().iter().enumerate() {
+        if pixel.alpha() > 0 {
+            let (x, y) = (idx % width, idx / width);
+            left = left.min(x);
+            right = right.max(x);
+            top = top.min(y);
+            bottom = bottom.max(y);
+        }
+    }
+    if left > right {
+        return None;
+    }
+    IntRect::from_ltrb(left as i32, top as i32, right as i32 + 1, bottom as i32 + 1)
+}
+
+/// The bundled fonts, loaded once per process. None of the SVG's named
+/// families are present, so every label falls through to `sans-serif`.
+fn fonts() -> Arc<fontdb::Database> {
+    static DATABASE: OnceLock<Arc<fontdb::Database>> = OnceLock::new();
+    DATABASE
+        .get_or_init(|| {
+            let mut db = fontdb::Database::new();
+            for font in FONTS {
+                db.load_font_data(font.to_vec());
+            }
+            let family = db.faces().find_map(|face| face.families.first()).map(|(name, _)| name.clone());
+            if let Some(family) = family {
+                db.set_sans_serif_family(family);
+            }
+            Arc::new(db)
+        })
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::{layout, ChartOptions};
+    use crate::project::{Project, Task};
+
+    fn chart() -> Chart {
+        let project = Project {
+            timeline_start: "2025-01-01".to_string(),
+            timeline_end: "2025-02-28".to_string(),
+            tasks: vec![Task {
+                name: "Build".to_string(),
+                start: "2025-01-06".to_string(),
+                end: "2025-01-24".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        layout(&project, &ChartOptions::default()).unwrap()
+    }
+
+    fn options(width: Option<u32>, background: Option<&str>, padding: f64) -> RasterOptions {
+        RasterOptions::new(CSS_DPI, None, width, background, padding).unwrap()
+    }
+
+    #[test]
+    fn options_are_range_checked() {
+        assert_eq!(RasterOptions::new(DEFAULT_DPI, Some(3.0), None, None, 0.0).unwrap().dpi, 288.0);
+        assert_eq!(
+            RasterOptions::new(10.0, None, None, None, 0.0).unwrap_err(),
+            "PNG DPI must be between 24 and 600, got 10"
+        );
+        assert_eq!(
+            RasterOptions::new(DEFAULT_DPI, Some(7.0), None, None, 0.0).unwrap_err(),
+            "PNG scale factor must be between 0.25 and 6.25, got 7"
+        );
+        assert!(RasterOptions::new(DEFAULT_DPI, None, Some(0), None, 0.0).is_err());
+        assert!(RasterOptions::new(DEFAULT_DPI, None, Some(MAX_WIDTH + 1), None, 0.0).is_err());
+        assert!(RasterOptions::new(DEFAULT_DPI, None, None, None, -1.0).is_err());
+        assert!(RasterOptions::new(DEFAULT_DPI, None, None, None, MAX_PADDING + 1.0).is_err());
+        assert!(options(None, None, 0.0).thumbnail(0).is_err());
+        assert_eq!(options(None, None, 0.0).thumbnail(320).unwrap().width, Some(320));
+        assert!(check_quality("JPEG", 0).is_err());
+        assert!(check_quality("WebP", 100).is_ok());
+    }
+
+    #[test]
+    fn backgrounds_accept_presets_and_css_colors() {
+        let background = |value: Option<&str>| options(None, value, 0.0).background;
+        assert_eq!(background(None), None);
+        assert_eq!(background(Some(" ")), None);
+        assert_eq!(background(Some("Transparent")), None);
+        assert_eq!(background(Some("CREAM")), Some(Color::rgba(255, 255, 248, 1.0)));
+        assert_eq!(background(Some("#336699")), Some(Color::rgba(0x33, 0x66, 0x99, 1.0)));
+        assert_eq!(background(Some("rgba(1, 2, 3, 0.5)")), Some(Color::rgba(1, 2, 3, 0.5)));
+        assert_eq!(
+            RasterOptions::new(DEFAULT_DPI, None, None, Some("beige"), 0.0).unwrap_err(),
+            "Invalid PNG background color: \"beige\""
+        );
+    }
+
+    #[test]
+    fn rendering_is_deterministic() {
+        let chart = chart();
+        let options = options(None, Some("white"), 8.0);
+        let first = render(&chart, &options).unwrap().to_png().unwrap();
+        let second = render(&chart, &options).unwrap().to_png().unwrap();
+        assert!(first == second, "the same chart rendered to different PNGs");
+    }
+
+    #[test]
+    fn explicit_width_is_exact_and_padding_takes_the_background() {
+        let chart = chart();
+        let natural = render(&chart, &options(None, None, 0.0)).unwrap();
+        for width in [300, 1001] {
+            let image = render(&chart, &options(Some(width), Some("cream"), 20.0)).unwrap();
+            assert_eq!(image.width(), width);
+            let corner = image.pixmap.pixel(0, 0).unwrap();
+            assert_eq!((corner.red(), corner.green(), corner.blue(), corner.alpha()), (255, 255, 248, 255));
+        }
+
+        // Padding scales with the DPI, and is left transparent without a background
+        let padded = render(&chart, &options(None, None, 10.0)).unwrap();
+        assert_eq!(padded.width(), natural.width() + 20);
+        assert_eq!(padded.height(), natural.height() + 20);
+        assert_eq!(padded.pixmap.pixel(0, 0).unwrap().alpha(), 0);
+        let hires = render(&chart, &RasterOptions::new(DEFAULT_DPI, None, None, None, 0.0).unwrap()).unwrap();
+        assert!(hires.width().abs_diff(natural.width() * 2) <= 2);
+    }
+
+    #[test]
+    fn encodes_every_format() {
+        let image = render(&chart(), &options(Some(200), None, 0.0)).unwrap();
+        assert!(image.to_png().unwrap().starts_with(b"\x89PNG"));
+        assert!(image.to_jpeg(80).unwrap().starts_with(&[0xFF, 0xD8]));
+        let webp = image.to_webp(80).unwrap();
+        assert_eq!((&webp[..4], &webp[8..12]), (&b"RIFF"[..], &b"WEBP"[..]));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::build_events::{self, BuildEvent};
+use crate::jobs;
+use crate::transcript::{Stream, TranscriptLine};
+use std::process::Stdio;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{mpsc, Mutex, MutexGuard};
+
+/// A build.js process in `--worker` mode, kept running between generations so
+/// Node and its modules are already loaded
+#[derive(Default)]
+pub struct Renderer {
+    worker: Mutex<Option<Worker>>,
+}
+
+struct Worker {
+    /// Node, script and environment the worker was started with; a change means a restart
+    key: String,
+    child: Child,
+    stdin: ChildStdin,
+    /// stdout and stderr lines in arrival order; closes when the process exits
+    output: mpsc::UnboundedReceiver<(Stream, String)>,
+    next_id: u64,
+    /// A request was sent but its `done` never read, e.g. after a cancel or timeout
+    in_flight: bool,
+}
+
+/// Sole use of the worker for one generation
+pub struct RendererLock<'a>(MutexGuard<'a, Option<Worker>>);
+
+impl Renderer {
+    /// Wait until no other generation is using the worker
+    pub async fn lock(&self) -> RendererLock<'_> {
+        RendererLock(self.worker.lock().await)
+    }
+
+    /// Stop the worker once dependencies change
+    pub async fn stop(&self) {
+        self.lock().await.stop();
+    }
+}
+
+impl RendererLock<'_> {
+    /// Run one build, given build.js's CLI arguments without the script, on the
+    /// worker started by `command` (build.js plus `--worker`). A worker that dies
+    /// mid-build is restarted and the build tried once more. `on_process` gets the
+    /// worker's PID for cancellation. Returns whether the build succeeded.
+    pub async fn render(
+        &mut self,
+        key: &str,
+        command: impl Fn() -> Command,
+        args: &[String],
+        on_process: impl Fn(Option<u32>),
+        transcript: &mut Vec<TranscriptLine>,
+        mut on_line: impl FnMut(Stream, String),
+    ) -> Result<bool, String> {
+        let worker = &mut *self.0;
+        for attempt in 0..2 {
+            if let Some(mut old) = worker.take() {
+                if old.key == key && !old.in_flight && old.is_running() {
+                    *worker = Some(old);
+                } else {
+                    old.kill();
+                }
+            }
+            let current = match worker.as_mut() {
+                Some(current) => current,
+                None => worker.insert(Worker::spawn(key, command())?),
+            };
+            on_process(current.child.id());
+
+            // Anything printed since the last build finished belongs to no build
+            while current.output.try_recv().is_ok() {}
+
+            current.next_id += 1;
+            let id = current.next_id.to_string();
+            let request = serde_json::json!({ "id": id, "args": args }).to_string() + "\n";
+            current.in_flight = true;
+            if current.stdin.write_all(request.as_bytes()).await.is_ok() && current.stdin.flush().await.is_ok() {
+                while let Some((stream, line)) = current.output.recv().await {
+                    transcript.push(TranscriptLine::new(stream, &line));
+                    match (stream == Stream::Stdout).then(|| build_events::parse_line(&line)).flatten() {
+                        Some(BuildEvent::Done { id: done, ok }) if done == id => {
+                            current.in_flight = false;
+                            return Ok(ok);
+                        }
+                        Some(BuildEvent::Ready) => {}
+                        _ => on_line(stream, line),
+                    }
+                }
+            }
+
+            on_process(None);
+            if let Some(mut dead) = worker.take() {
+                dead.kill();
+            }
+            if attempt == 0 {
+                on_line(Stream::Stderr, "Renderer worker exited during the build; restarting it".to_string());
+            }
+        }
+        Err("Renderer worker exited during the build".to_string())
+    }
+
+    /// Stop the worker, e.g. after a cancelled or timed-out build
+    pub fn stop(&mut self) {
+        if let Some(mut worker) = self.0.take() {
+            worker.kill();
+        }
+    }
+}
+
+impl Worker {
+    fn spawn(key: &str, mut command: Command) -> Result<Self, String> {
+        command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to start renderer worker: {}", e))?;
+        let stdin = child.stdin.take().ok_or("Failed to open renderer stdin")?;
+        let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+        let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+
+        // Both pipes are read all the time, so neither can fill up between builds
+        let (sender, output) = mpsc::unbounded_channel();
+        forward(Stream::Stdout, stdout, sender.clone());
+        forward(Stream::Stderr, stderr, sender);
+
+        Ok(Self {
+            key: key.to_string(),
+            child,
+            stdin,
+            output,
+            next_id: 0,
+            in_flight: false,
+        })
+    }
+
+    fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Kill the worker along with anything it started
+    fn kill(&mut self) {
+        if let Some(pid) = self.child.id() {
+            jobs::kill_process_tree(pid);
+        }
+        let _ = self.child.start_kill();
+    }
+}
+
+fn forward<R>(stream: Stream, reader: R, sender: mpsc::UnboundedSender<(Stream, String)>)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if sender.send((stream, line)).is_err() {
+                break;
+            }
+        }
+    });
+}
@@ -0,0 +1,334 @@
+use crate::project::{parse_date, Project};
+use crate::xml::escape_xml;
+use chrono::NaiveDate;
+use std::path::{Component, Path};
+
+/// Milestones due within this many days are listed when no window is given
+pub const DEFAULT_UPCOMING_DAYS: i64 = 14;
+
+pub struct ReportOptions {
+    /// Day the report is written for; "upcoming" is measured from here
+    pub today: NaiveDate,
+    /// Milestones due from `today` through `today + upcoming_days` are listed
+    pub upcoming_days: i64,
+    /// Relative link to the chart image, embedded under the title when set
+    pub image_link: Option<String>,
+}
+
+/// Content shared by the Markdown and HTML renderings
+struct Report {
+    title: String,
+    subtitle: String,
+    image_link: Option<String>,
+    summary: Vec<(&'static str, String)>,
+    sections: Vec<Section>,
+}
+
+struct Section {
+    heading: String,
+    headers: Vec<&'static str>,
+    /// Cells are lists of lines so subtasks can be shown one per line
+    rows: Vec<Vec<Vec<String>>>,
+    empty: String,
+}
+
+/// Write a Markdown status report: summary, task table, upcoming milestones and pauses
+pub fn to_markdown(project: &Project, options: &ReportOptions) -> Result<String, String> {
+    let report = build(project, options)?;
+    let mut out = vec![format!("# {}", md_text(&report.title)), String::new(), format!("_{}_", report.subtitle)];
+
+    if let Some(link) = &report.image_link {
+        out.push(String::new());
+        out.push(format!("![Gantt chart]({})", link));
+    }
+
+    out.push(String::new());
+    out.push("## Summary".to_string());
+    out.push(String::new());
+    for (label, value) in &report.summary {
+        out.push(format!("- **{}:** {}", label, md_text(value)));
+    }
+
+    for section in &report.sections {
+        out.push(String::new());
+        out.push(format!("## {}", md_text(&section.heading)));
+        out.push(String::new());
+        if section.rows.is_empty() {
+            out.push(format!("_{}_", section.empty));
+            continue;
+        }
+        out.push(format!("| {} |", section.headers.join(" | ")));
+        out.push(format!(
+            "|{}|",
+            section
+                .headers
+                .iter()
+                .map(|h| if is_numeric_column(h) { " ---: " } else { " --- " })
+                .collect::<Vec<_>>()
+                .join("|")
+        ));
+        for row in &section.rows {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|lines| lines.iter().map(|l| md_cell(l)).collect::<Vec<_>>().join("<br>"))
+                .collect();
+            out.push(format!("| {} |", cells.join(" | ")));
+        }
+    }
+
+    out.push(String::new());
+    Ok(out.join("\n"))
+}
+
+/// Write the same report as a standalone HTML page
+pub fn to_html(project: &Project, options: &ReportOptions) -> Result<String, String> {
+    let report = build(project, options)?;
+    let mut out = vec![
+        "<!DOCTYPE html>".to_string(),
+        "<html lang=\"en\">".to_string(),
+        "<head>".to_string(),
+        "<meta charset=\"utf-8\">".to_string(),
+        format!("<title>{}</title>", escape_xml(&report.title)),
+        "<style>".to_string(),
+        "body{font-family:-apple-system,BlinkMacSystemFont,\"Segoe UI\",Helvetica,Arial,sans-serif;color:#2A1C30;max-width:1100px;margin:2rem auto;padding:0 1rem}".to_string(),
+        "h1{margin-bottom:0}.subtitle{color:#705E74;margin-top:.25rem}img{max-width:100%;border:1px solid #E5E0E6}".to_string(),
+        "table{border-collapse:collapse;width:100%}th,td{border:1px solid #E5E0E6;padding:.4rem .6rem;text-align:left;vertical-align:top}".to_string(),
+        "th{background:#F7F4F8}td.num{text-align:right;white-space:nowrap}ul{margin:0;padding-left:1.1rem}.empty{color:#705E74;font-style:italic}".to_string(),
+        "</style>".to_string(),
+        "</head>".to_string(),
+        "<body>".to_string(),
+        format!("<h1>{}</h1>", escape_xml(&report.title)),
+        format!("<p class=\"subtitle\">{}</p>", escape_xml(&report.subtitle)),
+    ];
+
+    if let Some(link) = &report.image_link {
+        out.push(format!("<p><img src=\"{}\" alt=\"Gantt chart\"></p>", escape_xml(link)));
+    }
+
+    out.push("<h2>Summary</h2>".to_string());
+    out.push("<ul>".to_string());
+    for (label, value) in &report.summary {
+        out.push(format!("<li><strong>{}:</strong> {}</li>", label, escape_xml(value)));
+    }
+    out.push("</ul>".to_string());
+
+    for section in &report.sections {
+        out.push(format!("<h2>{}</h2>", escape_xml(&section.heading)));
+        if section.rows.is_empty() {
+            out.push(format!("<p class=\"empty\">{}</p>", escape_xml(&section.empty)));
+            continue;
+        }
+        out.push("<table>".to_string());
+        let headers: Vec<String> = section.headers.iter().map(|h| format!("<th>{}</th>", h)).collect();
+        out.push(format!("<tr>{}</tr>", headers.concat()));
+        for row in &section.rows {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&section.headers)
+                .map(|(lines, header)| {
+                    let class = if is_numeric_column(header) { " class=\"num\"" } else { "" };
+                    let content = match lines.as_slice() {
+                        [line] => escape_xml(line),
+                        _ if lines.is_empty() => String::new(),
+                        _ => format!(
+                            "<ul>{}</ul>",
+                            lines.iter().map(|l| format!("<li>{}</li>", escape_xml(l))).collect::<String>()
+                        ),
+                    };
+                    format!("<td{}>{}</td>", class, content)
+                })
+                .collect();
+            out.push(format!("<tr>{}</tr>", cells.concat()));
+        }
+        out.push("</table>".to_string());
+    }
+
+    out.push("</body>".to_string());
+    out.push("</html>".to_string());
+    out.push(String::new());
+    Ok(out.join("\n"))
+}
+
+fn build(project: &Project, options: &ReportOptions) -> Result<Report, String> {
+    let mut summary = Vec::new();
+    if !project.timeline_start.is_empty() && !project.timeline_end.is_empty() {
+        summary.push(("Timeline", format!("{} – {}", long_date_str(&project.timeline_start), long_date_str(&project.timeline_end))));
+    }
+
+    let mut task_rows = Vec::new();
+    let mut span: Option<(NaiveDate, NaiveDate)> = None;
+    for (idx, task) in project.tasks.iter().enumerate() {
+        let start = parse_date(&task.start, &format!("start of task \"{}\"", task.name))?;
+        let end = parse_date(&task.end, &format!("end of task \"{}\"", task.name))?;
+        span = Some(match span {
+            Some((first, last)) => (first.min(start), last.max(end)),
+            None => (start, end),
+        });
+        task_rows.push(vec![
+            vec![(idx + 1).to_string()],
+            vec![task.name.clone()],
+            vec![long_date(start)],
+            vec![long_date(end)],
+            vec![days_between(start, end).to_string()],
+            vec![format_hours(task.hours)],
+            task.subtasks.clone(),
+        ]);
+    }
+
+    let mut pauses = Vec::new();
+    for pause in &project.pause_periods {
+        let start = parse_date(&pause.start, "pause period start")?;
+        let end = parse_date(&pause.end, "pause period end")?;
+        pauses.push((pause.label.clone().unwrap_or_else(|| "Pause".to_string()), start, end));
+    }
+
+    let total_hours: f64 = project.tasks.iter().map(|t| t.hours).sum();
+    summary.push(("Tasks", project.tasks.len().to_string()));
+    summary.push(("Total hours", format_hours(total_hours)));
+    if let Some((first, last)) = span {
+        let days = days_between(first, last);
+        let paused = (0..days)
+            .map(|offset| first + chrono::Duration::days(offset))
+            .filter(|day| pauses.iter().any(|(_, start, end)| start <= day && day <= end))
+            .count() as i64;
+        let mut duration = format!(
+            "{} – {} ({} days, {:.1} weeks)",
+            long_date(first),
+            long_date(last),
+            days,
+            days as f64 / 7.0
+        );
+        if paused > 0 {
+            duration.push_str(&format!(", {} days excluding pauses", days - paused));
+        }
+        summary.push(("Duration", duration));
+    }
+
+    let horizon = options.today + chrono::Duration::days(options.upcoming_days.max(0));
+    let mut upcoming = Vec::new();
+    for milestone in &project.milestones {
+        let name = milestone.name.replace('\n', " ");
+        let date = parse_date(&milestone.date, &format!("date of milestone \"{}\"", name))?;
+        if date < options.today || date > horizon {
+            continue;
+        }
+        let task = milestone
+            .task_index
+            .and_then(|i| project.tasks.get(i))
+            .map(|t| t.name.clone())
+            .unwrap_or_default();
+        upcoming.push((date, vec![vec![name], vec![long_date(date)], vec![due_in(options.today, date)], vec![task]]));
+    }
+    upcoming.sort_by_key(|(date, _)| *date);
+
+    let pause_rows = pauses
+        .into_iter()
+        .map(|(label, start, end)| {
+            vec![vec![label], vec![long_date(start)], vec![long_date(end)], vec![days_between(start, end).to_string()]]
+        })
+        .collect();
+
+    let window = match options.upcoming_days {
+        1 => "day".to_string(),
+        n => format!("{} days", n.max(0)),
+    };
+    Ok(Report {
+        title: format!("{} — Status Report", project.title.trim()),
+        subtitle: format!("As of {}", long_date(options.today)),
+        image_link: options.image_link.clone(),
+        summary,
+        sections: vec![
+            Section {
+                heading: "Tasks".to_string(),
+                headers: vec!["#", "Task", "Start", "End", "Days", "Hours", "Subtasks"],
+                rows: task_rows,
+                empty: "No tasks.".to_string(),
+            },
+            Section {
+                heading: format!("Upcoming milestones (next {})", window),
+                headers: vec!["Milestone", "Date", "Due", "Task"],
+                rows: upcoming.into_iter().map(|(_, row)| row).collect(),
+                empty: format!("No milestones in the next {}.", window),
+            },
+            Section {
+                heading: "Pause periods".to_string(),
+                headers: vec!["Pause", "Start", "End", "Days"],
+                rows: pause_rows,
+                empty: "No pause periods.".to_string(),
+            },
+        ],
+    })
+}
+
+/// Path from `from_dir` to `target` with forward slashes, percent-encoded for use as a link.
+/// Falls back to the absolute path when the two share no root (e.g. different drives).
+pub fn relative_link(from_dir: &Path, target: &Path) -> String {
+    let from: Vec<Component> = from_dir.components().filter(|c| *c != Component::CurDir).collect();
+    let to: Vec<Component> = target.components().filter(|c| *c != Component::CurDir).collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let parts: Vec<String> = if common == 0 && (from_dir.has_root() || target.has_root()) {
+        to.iter().map(|c| c.as_os_str().to_string_lossy().replace('\\', "/")).collect()
+    } else {
+        std::iter::repeat_n("..".to_string(), from.len() - common)
+            .chain(to[common..].iter().map(|c| c.as_os_str().to_string_lossy().to_string()))
+            .collect()
+    };
+    let path = parts.join("/").replace("//", "/");
+    path.chars()
+        .map(|c| match c {
+            ' ' | '(' | ')' | '<' | '>' | '#' | '%' | '?' | '"' | '[' | ']' => format!("%{:02X}", c as u32),
+            _ => c.to_string(),
+        })
+        .collect()
+}
+
+fn is_numeric_column(header: &str) -> bool {
+    matches!(header, "#" | "Days" | "Hours")
+}
+
+/// Inclusive day count, matching how the chart draws end dates
+fn days_between(start: NaiveDate, end: NaiveDate) -> i64 {
+    (end - start).num_days() + 1
+}
+
+fn due_in(today: NaiveDate, date: NaiveDate) -> String {
+    match (date - today).num_days() {
+        0 => "Today".to_string(),
+        1 => "Tomorrow".to_string(),
+        n => format!("In {} days", n),
+    }
+}
+
+fn long_date(date: NaiveDate) -> String {
+    date.format("%b %-d, %Y").to_string()
+}
+
+pub fn long_date_str(value: &str) -> String {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(long_date)
+        .unwrap_or_else(|_| value.to_string())
+}
+
+/// Hours to at most two decimals, so summed fractions don't print float noise
+pub fn format_hours(hours: f64) -> String {
+    let text = format!("{:.2}", hours);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// Escape characters Markdown would treat as inline formatting
+fn md_text(value: &str) -> String {
+    let mut out = String::new();
+    for c in value.chars() {
+        if matches!(c, '\\' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Table cells additionally need pipes escaped and line breaks flattened
+fn md_cell(value: &str) -> String {
+    md_text(value).replace('|', "\\|").replace(['\r', '\n'], " ")
+}
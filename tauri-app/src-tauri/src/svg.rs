@@ -0,0 +1,259 @@
+use crate::chart::{num, Anchor, Chart, Color, Corners, LineStyle, Shape, Stroke};
+use crate::xml::escape_xml;
+use std::fmt::Write;
+
+/// Space around the chart so drop shadows are not cut off
+pub const PADDING: f64 = 12.0;
+
+const FONT_FAMILY: &str = "-apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, Helvetica, Arial, sans-serif";
+
+/// Serialize a laid out chart as a standalone SVG document with a transparent background
+pub fn to_svg(chart: &Chart) -> String {
+    let mut writer = SvgWriter::default();
+    for shape in &chart.shapes {
+        writer.shape(shape);
+    }
+
+    let width = chart.width + 2.0 * PADDING;
+    let height = chart.height + 2.0 * PADDING;
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = num(width),
+        h = num(height)
+    );
+    svg.push_str("<defs>\n");
+    if chart.drop_shadow {
+        svg.push_str(concat!(
+            r#"<filter id="shadow" x="-10%" y="-30%" width="120%" height="180%">"#,
+            r##"<feDropShadow dx="0" dy="4" stdDeviation="6" flood-color="#000000" flood-opacity="0.15"/>"##,
+            "</filter>\n"
+        ));
+    }
+    svg.push_str(&writer.defs);
+    svg.push_str("</defs>\n");
+    let _ = writeln!(
+        svg,
+        r#"<g transform="translate({p} {p})" font-family="{f}">"#,
+        p = num(PADDING),
+        f = FONT_FAMILY
+    );
+    svg.push_str(&writer.body);
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+#[derive(Default)]
+struct SvgWriter {
+    defs: String,
+    body: String,
+    clip_count: usize,
+    /// Hatch patterns already defined, by (color, stripe, period)
+    patterns: Vec<(String, String)>,
+}
+
+impl SvgWriter {
+    fn shape(&mut self, shape: &Shape) {
+        match shape {
+            Shape::Rect {
+                x,
+                y,
+                w,
+                h,
+                corners,
+                fill,
+                stroke,
+                shadow,
+            } => {
+                if let Some(fill) = fill {
+                    let filter = if *shadow { r#" filter="url(#shadow)""# } else { "" };
+                    let outline = rect_outline(*x, *y, *w, *h, corners);
+                    let _ = writeln!(self.body, "{} {}{}/>", outline, paint("fill", fill), filter);
+                }
+                if let Some(stroke) = stroke {
+                    // CSS borders sit inside the box, SVG strokes straddle the path
+                    let inset = stroke.width / 2.0;
+                    let inner = Corners {
+                        top_left: (corners.top_left - inset).max(0.0),
+                        top_right: (corners.top_right - inset).max(0.0),
+                        bottom_right: (corners.bottom_right - inset).max(0.0),
+                        bottom_left: (corners.bottom_left - inset).max(0.0),
+                    };
+                    let outline = rect_outline(x + inset, y + inset, w - stroke.width, h - stroke.width, &inner);
+                    let _ = writeln!(self.body, r#"{} fill="none" {}/>"#, outline, stroke_attrs(stroke));
+                }
+            }
+            Shape::Line { x1, y1, x2, y2, stroke } => {
+                let _ = writeln!(
+                    self.body,
+                    r#"<line x1="{}" y1="{}" x2="{}" y2="{}" {}/>"#,
+                    num(*x1),
+                    num(*y1),
+                    num(*x2),
+                    num(*y2),
+                    stroke_attrs(stroke)
+                );
+            }
+            Shape::Text {
+                x,
+                y,
+                text,
+                size,
+                weight,
+                color,
+                anchor,
+            } => {
+                let anchor = match anchor {
+                    Anchor::Start => "",
+                    Anchor::Middle => r#" text-anchor="middle""#,
+                };
+                let _ = writeln!(
+                    self.body,
+                    r#"<text x="{}" y="{}" font-size="{}" font-weight="{}" {}{}>{}</text>"#,
+                    num(*x),
+                    num(*y),
+                    num(*size),
+                    weight,
+                    paint("fill", color),
+                    anchor,
+                    escape_xml(text)
+                );
+            }
+            Shape::Hatch {
+                x,
+                y,
+                w,
+                h,
+                color,
+                stripe,
+                period,
+            } => {
+                let id = self.pattern(color, *stripe, *period);
+                let _ = writeln!(
+                    self.body,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="url(#{})"/>"#,
+                    num(*x),
+                    num(*y),
+                    num(*w),
+                    num(*h),
+                    id
+                );
+            }
+            Shape::Clip {
+                x,
+                y,
+                w,
+                h,
+                radius,
+                shapes,
+            } => {
+                self.clip_count += 1;
+                let id = format!("clip{}", self.clip_count);
+                let _ = writeln!(
+                    self.defs,
+                    r#"<clipPath id="{}"><rect x="{}" y="{}" width="{}" height="{}" rx="{}"/></clipPath>"#,
+                    id,
+                    num(*x),
+                    num(*y),
+                    num(*w),
+                    num(*h),
+                    num(*radius)
+                );
+                let _ = writeln!(self.body, r#"<g clip-path="url(#{})">"#, id);
+                for shape in shapes {
+                    self.shape(shape);
+                }
+                self.body.push_str("</g>\n");
+            }
+        }
+    }
+
+    /// Id of a pattern of stripes along the 45deg gradient direction, defining it on first use
+    fn pattern(&mut self, color: &Color, stripe: f64, period: f64) -> String {
+        let key = format!("{}|{}|{}", paint("fill", color), num(stripe), num(period));
+        if let Some((_, id)) = self.patterns.iter().find(|(k, _)| *k == key) {
+            return id.clone();
+        }
+        let id = format!("hatch{}", self.patterns.len() + 1);
+        let _ = writeln!(
+            self.defs,
+            r#"<pattern id="{id}" patternUnits="userSpaceOnUse" width="{p}" height="{p}" patternTransform="rotate(-45)"><rect width="{s}" height="{p}" {fill}/></pattern>"#,
+            id = id,
+            p = num(period),
+            s = num(stripe),
+            fill = paint("fill", color)
+        );
+        self.patterns.push((key, id.clone()));
+        id
+    }
+}
+
+/// `<rect` or `<path` start tag (unterminated) for a rectangle with per-corner radii
+fn rect_outline(x: f64, y: f64, w: f64, h: f64, c: &Corners) -> String {
+    if c.is_square() {
+        return format!(
+            r#"<rect x="{}" y="{}" width="{}" height="{}""#,
+            num(x),
+            num(y),
+            num(w),
+            num(h)
+        );
+    }
+    if c.top_left == c.top_right && c.top_left == c.bottom_right && c.top_left == c.bottom_left {
+        return format!(
+            r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}""#,
+            num(x),
+            num(y),
+            num(w),
+            num(h),
+            num(c.top_left)
+        );
+    }
+    let (r, b) = (x + w, y + h);
+    format!(
+        r#"<path d="M{} {}H{}A{tr} {tr} 0 0 1 {} {}V{}A{br} {br} 0 0 1 {} {}H{}A{bl} {bl} 0 0 1 {} {}V{}A{tl} {tl} 0 0 1 {} {}Z""#,
+        num(x + c.top_left),
+        num(y),
+        num(r - c.top_right),
+        num(r),
+        num(y + c.top_right),
+        num(b - c.bottom_right),
+        num(r - c.bottom_right),
+        num(b),
+        num(x + c.bottom_left),
+        num(x),
+        num(b - c.bottom_left),
+        num(y + c.top_left),
+        num(x + c.top_left),
+        num(y),
+        tr = num(c.top_right),
+        br = num(c.bottom_right),
+        bl = num(c.bottom_left),
+        tl = num(c.top_left),
+    )
+}
+
+fn stroke_attrs(stroke: &Stroke) -> String {
+    let dash = match stroke.style {
+        LineStyle::Solid => String::new(),
+        LineStyle::Dashed => format!(r#" stroke-dasharray="{0} {0}""#, num(stroke.width * 3.0)),
+        LineStyle::Dotted => format!(r#" stroke-dasharray="{0} {0}""#, num(stroke.width)),
+    };
+    format!(
+        r#"{} stroke-width="{}"{}"#,
+        paint("stroke", &stroke.color),
+        num(stroke.width),
+        dash
+    )
+}
+
+/// `fill="#rrggbb"` plus an opacity attribute for translucent colors
+fn paint(attr: &str, color: &Color) -> String {
+    let hex = format!("#{:02x}{:02x}{:02x}", color.r, color.g, color.b);
+    if color.a < 1.0 {
+        format!(r#"{attr}="{hex}" {attr}-opacity="{}""#, num(color.a))
+    } else {
+        format!(r#"{attr}="{hex}""#)
+    }
+}
@@ -0,0 +1,149 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+/// Finished transcripts kept for `get_transcript`, oldest dropped first
+const KEPT_TRANSCRIPTS: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// One line a child process printed, in the order it arrived
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptLine {
+    pub timestamp: String,
+    pub stream: Stream,
+    pub text: String,
+}
+
+impl TranscriptLine {
+    pub fn new(stream: Stream, text: &str) -> Self {
+        Self {
+            timestamp: chrono::Local::now().format("%H:%M:%S%.3f").to_string(),
+            stream,
+            text: text.to_string(),
+        }
+    }
+}
+
+/// Child output of recent jobs by job ID, oldest first
+#[derive(Default)]
+pub struct Transcripts {
+    saved: Mutex<VecDeque<(String, Vec<TranscriptLine>)>>,
+}
+
+impl Transcripts {
+    /// Store a job's transcript, replacing any earlier one with the same ID
+    pub fn save(&self, id: &str, lines: Vec<TranscriptLine>) {
+        let mut saved = self.saved.lock().unwrap_or_else(|e| e.into_inner());
+        saved.retain(|(saved_id, _)| saved_id != id);
+        saved.push_back((id.to_string(), lines));
+        while saved.len() > KEPT_TRANSCRIPTS {
+            saved.pop_front();
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<Vec<TranscriptLine>> {
+        self.saved
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .find(|(saved_id, _)| saved_id == id)
+            .map(|(_, lines)| lines.clone())
+    }
+}
+
+/// Read stdout and stderr together until both close, so neither pipe can fill
+/// up and stall the child. Lines are recorded and handed on in arrival order.
+pub async fn drain<O, E>(
+    stdout: O,
+    stderr: E,
+    transcript: &mut Vec<TranscriptLine>,
+    mut on_line: impl FnMut(Stream, String),
+) where
+    O: AsyncRead + Unpin,
+    E: AsyncRead + Unpin,
+{
+    let mut stdout = BufReader::new(stdout).lines();
+    let mut stderr = BufReader::new(stderr).lines();
+    let (mut stdout_open, mut stderr_open) = (true, true);
+    while stdout_open || stderr_open {
+        let (stream, line) = tokio::select! {
+            line = stdout.next_line(), if stdout_open => (Stream::Stdout, line),
+            line = stderr.next_line(), if stderr_open => (Stream::Stderr, line),
+        };
+        let Ok(Some(line)) = line else {
+            match stream {
+                Stream::Stdout => stdout_open = false,
+                Stream::Stderr => stderr_open = false,
+            }
+            continue;
+        };
+        transcript.push(TranscriptLine::new(stream, &line));
+        on_line(stream, line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(texts: &[&str]) -> Vec<TranscriptLine> {
+        texts.iter().map(|text| TranscriptLine::new(Stream::Stdout, text)).collect()
+    }
+
+    fn texts(lines: &[TranscriptLine]) -> Vec<&str> {
+        lines.iter().map(|line| line.text.as_str()).collect()
+    }
+
+    #[test]
+    fn keeps_the_most_recent_transcripts() {
+        let transcripts = Transcripts::default();
+        for i in 1..=KEPT_TRANSCRIPTS + 2 {
+            transcripts.save(&format!("job-{}", i), lines(&[&format!("run {}", i)]));
+        }
+        assert!(transcripts.get("job-1").is_none());
+        assert!(transcripts.get("job-2").is_none());
+        assert_eq!(texts(&transcripts.get("job-3").unwrap()), ["run 3"]);
+        assert_eq!(texts(&transcripts.get("job-22").unwrap()), ["run 22"]);
+    }
+
+    #[test]
+    fn saving_an_id_again_replaces_it_and_makes_it_newest() {
+        let transcripts = Transcripts::default();
+        transcripts.save("export", lines(&["first"]));
+        for i in 1..KEPT_TRANSCRIPTS {
+            transcripts.save(&format!("job-{}", i), Vec::new());
+        }
+        transcripts.save("export", lines(&["second"]));
+        transcripts.save("job-20", Vec::new());
+
+        assert_eq!(texts(&transcripts.get("export").unwrap()), ["second"]);
+        assert!(transcripts.get("job-1").is_none());
+        assert!(transcripts.get("job-2").is_some());
+    }
+
+    #[tokio::test]
+    async fn drain_records_both_streams() {
+        let mut transcript = Vec::new();
+        let mut seen = Vec::new();
+        let stdout: &[u8] = b"Parsing\r\n\nGenerated chart.html\n";
+        let stderr: &[u8] = b"warning: slow font";
+        drain(stdout, stderr, &mut transcript, |stream, line| seen.push((stream, line))).await;
+
+        let stream_texts = |stream: Stream| -> Vec<&str> {
+            transcript.iter().filter(|line| line.stream == stream).map(|line| line.text.as_str()).collect()
+        };
+        assert_eq!(stream_texts(Stream::Stdout), ["Parsing", "", "Generated chart.html"]);
+        assert_eq!(stream_texts(Stream::Stderr), ["warning: slow font"]);
+        assert_eq!(
+            seen,
+            transcript.iter().map(|line| (line.stream, line.text.clone())).collect::<Vec<_>>()
+        );
+    }
+}
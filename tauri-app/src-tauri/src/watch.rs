@@ -0,0 +1,83 @@
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Quiet time after the last change before regenerating; Excel writes one save
+/// as several events (temp file, rename, attribute updates)
+const DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Input files being watched, by canonical path
+#[derive(Default)]
+pub struct Watches {
+    watching: Mutex<HashMap<PathBuf, notify::RecommendedWatcher>>,
+    runs: AtomicU64,
+}
+
+impl Watches {
+    /// Start watching a file, replacing any earlier watch on it. The receiver gets
+    /// a message per change and closes on `unwatch`.
+    pub fn watch(&self, path: &Path) -> Result<mpsc::UnboundedReceiver<()>, String> {
+        let path = path
+            .canonicalize()
+            .map_err(|e| format!("Failed to watch {}: {}", path.display(), e))?;
+        let name = path.file_name().map(|name| name.to_os_string());
+        // Watch the folder: saving through a temp file and a rename replaces the file
+        // a direct watch would be attached to
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        let (sender, changes) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else { return };
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            if event.paths.iter().any(|changed| changed.file_name() == name.as_deref()) {
+                let _ = sender.send(());
+            }
+        })
+        .map_err(|e| format!("Failed to watch {}: {}", path.display(), e))?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(|e| format!("Failed to watch {}: {}", dir.display(), e))?;
+
+        self.watching
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(path, watcher);
+        Ok(changes)
+    }
+
+    /// Stop watching a file; false when it wasn't watched
+    pub fn unwatch(&self, path: &Path) -> bool {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        // Dropping the watcher closes the channel, which ends the watch loop
+        self.watching
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&path)
+            .is_some()
+    }
+
+    /// Job ID for the next regeneration, distinct from IDs the UI picks
+    pub fn next_job_id(&self) -> String {
+        format!("watch-{}", self.runs.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+}
+
+/// Wait for a change, then until none has arrived for `DEBOUNCE`; false once unwatched
+pub async fn next_change(changes: &mut mpsc::UnboundedReceiver<()>) -> bool {
+    if changes.recv().await.is_none() {
+        return false;
+    }
+    loop {
+        match tokio::time::timeout(DEBOUNCE, changes.recv()).await {
+            Ok(Some(())) => continue,
+            Ok(None) => return false,
+            Err(_) => return true,
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use crate::build_events::Stage;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Log lines kept for a timeout report
+const TAIL_LINES: usize = 20;
+
+/// Overall and per-stage deadlines for one generation run
+pub struct Watchdog {
+    overall: Option<Duration>,
+    per_stage: Option<Duration>,
+    clock: Mutex<Clock>,
+}
+
+#[derive(Clone, Copy)]
+struct Clock {
+    started: Instant,
+    stage: Stage,
+    entered: Instant,
+}
+
+/// A deadline that passed
+#[derive(Debug, Clone, Copy)]
+pub struct Timeout {
+    pub stage: Stage,
+    pub limit: Duration,
+    /// The whole-run limit passed rather than the stage's own
+    pub overall: bool,
+}
+
+impl Watchdog {
+    /// Limits are in seconds; 0 turns one off
+    pub fn new(overall_secs: u64, stage_secs: u64, stage: Stage) -> Self {
+        let limit = |secs: u64| (secs > 0).then(|| Duration::from_secs(secs));
+        let now = Instant::now();
+        Self {
+            overall: limit(overall_secs),
+            per_stage: limit(stage_secs),
+            clock: Mutex::new(Clock {
+                started: now,
+                stage,
+                entered: now,
+            }),
+        }
+    }
+
+    /// Start a stage, restarting the per-stage clock
+    pub fn enter(&self, stage: Stage) {
+        let mut clock = self.clock.lock().unwrap_or_else(|e| e.into_inner());
+        clock.stage = stage;
+        clock.entered = Instant::now();
+    }
+
+    /// Leave time spent queued for the renderer out of the overall limit
+    pub fn exclude(&self, waited: Duration) {
+        self.clock.lock().unwrap_or_else(|e| e.into_inner()).started += waited;
+    }
+
+    /// Resolves once a deadline passes; never when both limits are off
+    pub async fn expired(&self) -> Timeout {
+        loop {
+            let Clock { started, stage, entered } = *self.clock.lock().unwrap_or_else(|e| e.into_inner());
+            let overall = self.overall.map(|limit| (started + limit, limit, true));
+            let per_stage = self.per_stage.map(|limit| (entered + limit, limit, false));
+            let Some((at, limit, overall)) = overall.into_iter().chain(per_stage).min_by_key(|(at, ..)| *at) else {
+                return std::future::pending().await;
+            };
+            if at <= Instant::now() {
+                return Timeout { stage, limit, overall };
+            }
+            // A stage entered meanwhile moves the deadline, so check again after waking
+            tokio::time::sleep_until(at).await;
+        }
+    }
+}
+
+impl Timeout {
+    /// Which stage hung, whether HTML made it out, and what the build last printed
+    pub fn report(&self, html: &[&str], tail: &Tail) -> String {
+        let mut message = if self.overall {
+            format!(
+                "Generation timed out after {}s in the {} stage",
+                self.limit.as_secs(),
+                self.stage.label()
+            )
+        } else {
+            format!(
+                "Generation timed out: the {} stage hung for {}s",
+                self.stage.label(),
+                self.limit.as_secs()
+            )
+        };
+        if html.is_empty() {
+            message.push_str("\nNo HTML was produced");
+        } else {
+            message.push_str(&format!("\nHTML was still written to {}", html.join(", ")));
+        }
+        message.push_str(&tail.report());
+        message
+    }
+}
+
+/// The most recent lines a child process printed
+#[derive(Debug, Default)]
+pub struct Tail(VecDeque<String>);
+
+impl Tail {
+    pub fn push(&mut self, line: &str) {
+        if line.trim().is_empty() {
+            return;
+        }
+        if self.0.len() == TAIL_LINES {
+            self.0.pop_front();
+        }
+        self.0.push_back(line.to_string());
+    }
+
+    /// "Last output:" and the lines, or nothing when the process printed nothing
+    pub fn report(&self) -> String {
+        if self.0.is_empty() {
+            return String::new();
+        }
+        let lines: Vec<&str> = self.0.iter().map(String::as_str).collect();
+        format!("\nLast output:\n{}", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `Watchdog::new` takes whole seconds; tests use milliseconds to stay fast
+    fn watchdog(overall_ms: u64, stage_ms: u64) -> Watchdog {
+        let limit = |ms: u64| (ms > 0).then(|| Duration::from_millis(ms));
+        let now = Instant::now();
+        Watchdog {
+            overall: limit(overall_ms),
+            per_stage: limit(stage_ms),
+            clock: Mutex::new(Clock {
+                started: now,
+                stage: Stage::Parse,
+                entered: now,
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn stage_limit_restarts_on_each_stage() {
+        let watchdog = watchdog(5000, 100);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        watchdog.enter(Stage::Html);
+        let entered = Instant::now();
+
+        let timeout = watchdog.expired().await;
+        assert!(entered.elapsed() >= Duration::from_millis(100));
+        assert_eq!(timeout.stage, Stage::Html);
+        assert_eq!(timeout.limit, Duration::from_millis(100));
+        assert!(!timeout.overall);
+    }
+
+    #[tokio::test]
+    async fn overall_limit_spans_stages() {
+        let watchdog = watchdog(100, 5000);
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        watchdog.enter(Stage::Png);
+
+        let timeout = watchdog.expired().await;
+        assert_eq!(timeout.stage, Stage::Png);
+        assert_eq!(timeout.limit, Duration::from_millis(100));
+        assert!(timeout.overall);
+    }
+
+    #[tokio::test]
+    async fn excluded_wait_moves_the_overall_deadline() {
+        let watchdog = watchdog(50, 0);
+        watchdog.exclude(Duration::from_millis(500));
+        assert!(tokio::time::timeout(Duration::from_millis(150), watchdog.expired()).await.is_err());
+        assert!(tokio::time::timeout(Duration::from_secs(5), watchdog.expired()).await.unwrap().overall);
+    }
+
+    #[tokio::test]
+    async fn never_expires_with_both_limits_off() {
+        let watchdog = Watchdog::new(0, 0, Stage::Parse);
+        assert!(tokio::time::timeout(Duration::from_millis(50), watchdog.expired()).await.is_err());
+    }
+
+    #[test]
+    fn tail_keeps_the_last_lines() {
+        let mut tail = Tail::default();
+        assert_eq!(tail.report(), "");
+        for i in 1..=25 {
+            tail.push(&format!("line {}", i));
+            tail.push("   ");
+        }
+        let report = tail.report();
+        let lines: Vec<&str> = report.lines().skip(2).collect();
+        assert!(report.starts_with("\nLast output:\nline 6\n"));
+        assert_eq!(lines.len(), TAIL_LINES);
+        assert_eq!(lines.last(), Some(&"line 25"));
+    }
+
+    #[test]
+    fn report_names_the_stage_and_the_html_written() {
+        let mut tail = Tail::default();
+        tail.push("Launching browser");
+        let stage = Timeout {
+            stage: Stage::Png,
+            limit: Duration::from_secs(180),
+            overall: false,
+        };
+        assert_eq!(
+            stage.report(&["/out/a.html", "/out/b.html"], &tail),
+            "Generation timed out: the PNG stage hung for 180s\n\
+             HTML was still written to /out/a.html, /out/b.html\n\
+             Last output:\nLaunching browser"
+        );
+        let overall = Timeout {
+            stage: Stage::Html,
+            limit: Duration::from_secs(600),
+            overall: true,
+        };
+        assert_eq!(
+            overall.report(&[], &Tail::default()),
+            "Generation timed out after 600s in the HTML stage\nNo HTML was produced"
+        );
+    }
+}
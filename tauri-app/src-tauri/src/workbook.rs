@@ -0,0 +1,936 @@
+use crate::project::{
+    escape_line_breaks, excel_serial_to_date, normalize_date, unescape_line_breaks, Milestone, PausePeriod, Project,
+    Task, BRAND_COLORS, MAX_SUBTASKS,
+};
+use crate::xml::{escape_xml, relationships, Package, REL, XML_HEADER};
+use calamine::{open_workbook, Data, Range, Reader, Xls, Xlsx};
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::Path;
+
+/// Legacy BIFF (.xls) workbooks are stored as OLE compound documents
+const OLE_SIGNATURE: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// Check whether a file is a legacy binary workbook, regardless of its extension
+pub fn is_legacy_workbook(path: &Path) -> bool {
+    let mut header = [0u8; 8];
+    File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .map(|_| header == OLE_SIGNATURE)
+        .unwrap_or(false)
+}
+
+/// Read a GanttGen workbook (.xlsx or legacy .xls) into the normalized project model.
+///
+/// Mirrors `parseExcel` in scripts/build.js: the first row of every sheet is a
+/// header, columns are read by position, and empty rows are skipped. The format
+/// is sniffed from the file contents since clients often send renamed files.
+pub fn read_workbook(path: &Path) -> Result<Project, String> {
+    if is_legacy_workbook(path) {
+        let mut workbook: Xls<_> = open_workbook(path)
+            .map_err(|e| format!("Failed to open legacy workbook {}: {}", path.display(), e))?;
+        read_sheets(&mut workbook)
+    } else {
+        let mut workbook: Xlsx<_> = open_workbook(path)
+            .map_err(|e| format!("Failed to open workbook {}: {}", path.display(), e))?;
+        read_sheets(&mut workbook)
+    }
+}
+
+fn read_sheets<RS, R>(workbook: &mut R) -> Result<Project, String>
+where
+    RS: Read + Seek,
+    R: Reader<RS>,
+    R::Error: std::fmt::Display,
+{
+    let mut project = Project::default();
+
+    // Palette must be read first so colorIndex values can be resolved
+    if let Some(sheet) = worksheet(workbook, "Palette")? {
+        for row in data_rows(&sheet) {
+            let color = cell_text(cell(&sheet, row, 0));
+            if !color.is_empty() {
+                project.palette.push(color);
+            }
+        }
+    }
+    if project.palette.is_empty() {
+        project.palette = BRAND_COLORS.iter().map(|c| c.to_string()).collect();
+    }
+
+    if let Some(sheet) = worksheet(workbook, "Project")? {
+        let title = cell_text(cell(&sheet, 1, 0));
+        if !title.is_empty() {
+            project.title = title;
+        }
+        project.timeline_start = cell_date(cell(&sheet, 1, 1));
+        project.timeline_end = cell_date(cell(&sheet, 1, 2));
+        project.show_milestones = cell_bool(cell(&sheet, 1, 3)).unwrap_or(true);
+    }
+
+    if let Some(sheet) = worksheet(workbook, "Tasks")? {
+        for row in data_rows(&sheet) {
+            let name = cell_text(cell(&sheet, row, 0));
+            if name.is_empty() {
+                continue;
+            }
+
+            let mut task = Task {
+                name,
+                start: cell_date(cell(&sheet, row, 1)),
+                end: cell_date(cell(&sheet, row, 2)),
+                hours: cell_number(cell(&sheet, row, 3)).unwrap_or(0.0),
+                ..Task::default()
+            };
+
+            for col in 4..4 + MAX_SUBTASKS as u32 {
+                let subtask = cell_text(cell(&sheet, row, col));
+                if !subtask.is_empty() {
+                    task.subtasks.push(subtask);
+                }
+            }
+
+            // Column 15 = color, column 16 = colorIndex (preferred when present)
+            let color_index = cell(&sheet, row, 15);
+            if !is_blank(color_index) {
+                task.color_index = cell_number(color_index)
+                    .filter(|i| *i >= 0.0 && i.fract() == 0.0)
+                    .map(|i| i as usize)
+                    .filter(|i| *i < project.palette.len());
+            } else {
+                let color = cell_text(cell(&sheet, row, 14));
+                if !color.is_empty() {
+                    task.color = Some(color);
+                }
+            }
+
+            project.tasks.push(task);
+        }
+    }
+
+    project.resolve_colors();
+
+    if let Some(sheet) = worksheet(workbook, "Milestones")? {
+        for row in data_rows(&sheet) {
+            let name = cell_text(cell(&sheet, row, 0));
+            if name.is_empty() {
+                continue;
+            }
+
+            // linkedTask is a task name from the dropdown; older sheets hold a numeric
+            // taskIndex. A number that is no valid index may still be a task's name.
+            let linked = cell(&sheet, row, 2);
+            let task_index = match linked {
+                Data::Int(_) | Data::Float(_) => cell_number(linked)
+                    .filter(|i| *i >= 0.0 && i.fract() == 0.0 && *i < project.tasks.len() as f64)
+                    .map(|i| i as usize),
+                _ => None,
+            }
+            .or_else(|| {
+                let linked = cell_text(linked);
+                project.tasks.iter().position(|t| !linked.is_empty() && t.name == linked)
+            });
+
+            project.milestones.push(Milestone {
+                name: unescape_line_breaks(&name),
+                date: cell_date(cell(&sheet, row, 1)),
+                task_index,
+            });
+        }
+    }
+
+    if let Some(sheet) = worksheet(workbook, "PausePeriods")? {
+        for row in data_rows(&sheet) {
+            let start = cell_date(cell(&sheet, row, 0));
+            let end = cell_date(cell(&sheet, row, 1));
+            if start.is_empty() || end.is_empty() {
+                continue;
+            }
+            project.pause_periods.push(PausePeriod {
+                start,
+                end,
+                label: None,
+            });
+        }
+    }
+
+    Ok(project)
+}
+
+/// Look up a sheet by name, returning `None` when the workbook does not have it
+fn worksheet<RS, R>(workbook: &mut R, name: &str) -> Result<Option<Range<Data>>, String>
+where
+    RS: Read + Seek,
+    R: Reader<RS>,
+    R::Error: std::fmt::Display,
+{
+    if !workbook.sheet_names().iter().any(|n| n == name) {
+        return Ok(None);
+    }
+    workbook
+        .worksheet_range(name)
+        .map(Some)
+        .map_err(|e| format!("Failed to read {} sheet: {}", name, e))
+}
+
+/// Absolute row numbers after the header row
+fn data_rows(sheet: &Range<Data>) -> std::ops::RangeInclusive<u32> {
+    let last = sheet.end().map(|(row, _)| row).unwrap_or(0);
+    1..=last
+}
+
+fn cell(sheet: &Range<Data>, row: u32, col: u32) -> &Data {
+    sheet.get_value((row, col)).unwrap_or(&Data::Empty)
+}
+
+fn is_blank(value: &Data) -> bool {
+    match value {
+        Data::Empty => true,
+        Data::String(s) => s.trim().is_empty(),
+        _ => false,
+    }
+}
+
+/// Cell value as trimmed text, the way `String(value).trim()` reads it in build.js
+fn cell_text(value: &Data) -> String {
+    match value {
+        Data::String(s) | Data::DurationIso(s) => s.trim().to_string(),
+        Data::DateTimeIso(s) => normalize_date(s).unwrap_or_else(|| s.trim().to_string()),
+        Data::Float(f) => format_number(*f),
+        Data::Int(i) => i.to_string(),
+        Data::Bool(b) => b.to_string(),
+        Data::DateTime(dt) => dt
+            .as_datetime()
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| format_number(dt.as_f64())),
+        Data::Error(_) | Data::Empty => String::new(),
+    }
+}
+
+/// Cell value as a YYYY-MM-DD date, falling back to the raw text when unrecognised
+fn cell_date(value: &Data) -> String {
+    match value {
+        Data::Float(f) => excel_serial_to_date(*f)
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| format_number(*f)),
+        Data::Int(i) => excel_serial_to_date(*i as f64)
+            .map(|d| d.format("%Y-%m-%d").to_string())
+            .unwrap_or_else(|| i.to_string()),
+        _ => {
+            let text = cell_text(value);
+            normalize_date(&text).unwrap_or(text)
+        }
+    }
+}
+
+fn cell_number(value: &Data) -> Option<f64> {
+    match value {
+        Data::Float(f) => Some(*f),
+        Data::Int(i) => Some(*i as f64),
+        Data::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn cell_bool(value: &Data) -> Option<bool> {
+    match value {
+        Data::Bool(b) => Some(*b),
+        Data::Float(f) => Some(*f != 0.0),
+        Data::Int(i) => Some(*i != 0),
+        Data::String(s) => match s.trim().to_lowercase().as_str() {
+            "" => None,
+            "false" | "no" | "0" | "n" => Some(false),
+            _ => Some(true),
+        },
+        _ => None,
+    }
+}
+
+/// Format a number like JS `String(n)` does for the values we care about
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        value.to_string()
+    }
+}
+
+const SHEET_NS: &str = r#"xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships""#;
+/// Custom number format id for ISO dates; ids below 164 are built in
+const DATE_FORMAT_ID: usize = 164;
+/// Task names offered by the linkedTask dropdown, matching the template
+const TASK_NAMES_RANGE: &str = "Tasks!$A$2:$A$1000";
+/// Rows that get the dropdown validations even when they are still empty
+const VALIDATED_ROWS: usize = 100;
+
+const DARK_TEXT: &str = "FF2C3E50";
+const WHITE: &str = "FFFFFFFF";
+const GRID: &str = "FFE0E0E0";
+
+/// Content of the Instructions sheet: (text, kind) where kind styles the row
+const INSTRUCTIONS: [(&str, Line); 35] = [
+    ("", Line::Blank),
+    ("QUICK START", Line::Section),
+    ("1. Fill in the Project sheet with your timeline dates", Line::Text),
+    ("2. Add your tasks in the Tasks sheet", Line::Text),
+    ("3. Add milestones in the Milestones sheet (optional)", Line::Text),
+    ("4. Add pause periods if needed (optional)", Line::Text),
+    ("5. Run: node scripts/build.js --input your_file.xlsx --palette alternating_b", Line::Text),
+    ("", Line::Blank),
+    ("SHEET GUIDE", Line::Section),
+    ("", Line::Blank),
+    ("Sheet Name|Purpose|Required|Notes", Line::TableHeader),
+    ("Palette|Color definitions|Optional*|*Palette preset flag overrides this", Line::Text),
+    ("Project|Timeline & settings|Required|Set start/end dates and title", Line::Text),
+    ("Tasks|Task definitions|Required|Add tasks with dates, hours, subtasks", Line::Text),
+    ("Milestones|Key milestones|Optional|Link to tasks via dropdown", Line::Text),
+    ("PausePeriods|Break periods|Optional|Holidays, reviews, etc.", Line::Text),
+    ("", Line::Blank),
+    ("COLOR PALETTES", Line::Section),
+    ("", Line::Blank),
+    ("Use --palette flag to override template colors:", Line::Text),
+    ("  • reds, reds_b - Red gradients", Line::Blank),
+    ("  • purples_a, purples_b, purples_c - Purple gradients", Line::Blank),
+    ("  • alternating, alternating_b - Red/purple mix (recommended)", Line::Blank),
+    ("", Line::Blank),
+    ("DATE FORMAT", Line::Section),
+    ("All dates must be in YYYY-MM-DD format (e.g., 2025-01-15)", Line::Text),
+    ("", Line::Blank),
+    ("TIPS", Line::Section),
+    ("• Use colorIndex in Tasks sheet to reference Palette colors (0-based)", Line::Bullet),
+    ("• Milestones link to tasks via dropdown menu", Line::Bullet),
+    ("• Tasks spanning pause periods show diagonal stripe breaks", Line::Bullet),
+    ("• Leave empty cells if you have fewer than 10 subtasks", Line::Bullet),
+    ("", Line::Blank),
+    ("DOCUMENTATION", Line::Section),
+    ("See docs/EXCEL_TEMPLATE_GUIDE.md for detailed instructions", Line::Text),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Line {
+    Blank,
+    Section,
+    TableHeader,
+    Text,
+    Bullet,
+}
+
+/// Write a project as a GanttGen workbook with the layout of templates/gantt_template.xlsx.
+///
+/// Mirrors scripts/json_to_excel.js: an Instructions sheet, a Palette sheet
+/// previewing each color, and Project, Tasks, Milestones and PausePeriods
+/// sheets with frozen header rows. Dates are written as date-formatted serial
+/// numbers. Milestones get the linkedTask dropdown over the task names and
+/// tasks a colorIndex dropdown over the palette positions. Like the template,
+/// only the first ten subtasks of a task have a column. An empty palette is
+/// written as the brand colors so colorIndex values still resolve.
+pub fn write_workbook(project: &Project) -> Result<Vec<u8>, String> {
+    let palette: Vec<String> = if project.palette.is_empty() {
+        BRAND_COLORS.iter().map(|c| c.to_string()).collect()
+    } else {
+        project.palette.clone()
+    };
+    let mut styles = Styles::new();
+    let mut strings = SharedStrings::default();
+    let mut sheets = Vec::new();
+
+    // Instructions
+    let mut sheet = Sheet::new("Instructions", &[(1, 1, 50.0), (2, 2, 30.0), (3, 3, 15.0), (4, 4, 40.0)]);
+    let title = styles.xf(Font::bold(WHITE, 16), Some("FF2C3E50"), None, false, Some(r#"horizontal="center" vertical="center""#));
+    sheet.row(Some(35.0), vec![Value::text("GanttGen Excel Template - Instructions"), Value::Empty, Value::Empty, Value::Empty], title);
+    sheet.merges.push("A1:D1".to_string());
+    let section = styles.xf(Font::bold(WHITE, 12), Some("FF3498DB"), None, false, None);
+    let table_header = styles.xf(Font::bold(WHITE, 11), Some("FF27AE60"), None, false, None);
+    let text = styles.xf(Font::plain(None, 11), None, None, false, None);
+    let bullet = styles.xf(Font::italic(10), None, None, false, Some(r#"indent="2""#));
+    for (line, kind) in INSTRUCTIONS {
+        let mut values: Vec<Value> = line.split('|').map(Value::text).collect();
+        values.resize_with(4, || Value::text(""));
+        let (height, style) = match kind {
+            Line::Section => (Some(25.0), section),
+            Line::TableHeader => (Some(25.0), table_header),
+            Line::Text => (None, text),
+            Line::Bullet => (None, bullet),
+            Line::Blank => (None, 0),
+        };
+        if kind == Line::Section {
+            let row = sheet.rows.len() + 1;
+            sheet.merges.push(format!("A{}:D{}", row, row));
+        }
+        sheet.row(height, values, style);
+    }
+    sheets.push(sheet);
+
+    // Palette, each color previewed as the cell background
+    let mut sheet = Sheet::new("Palette", &[(1, 1, 20.0)]);
+    let header = header_style(&mut styles, "FFE67E22", false);
+    sheet.row(Some(25.0), vec![Value::text("color")], header);
+    for color in &palette {
+        let style = match argb(color) {
+            Some(fill) => styles.xf(Font::bold(WHITE, 11), Some(&fill), None, false, None),
+            None => styles.xf(Font::plain(Some(DARK_TEXT), 11), Some("FFF0F0F0"), None, false, None),
+        };
+        sheet.row(None, vec![Value::text(color)], style);
+    }
+    sheets.push(sheet);
+
+    // Project
+    let mut sheet = Sheet::new("Project", &[(1, 1, 25.0), (2, 4, 18.0)]);
+    let header = header_style(&mut styles, "FF3498DB", false);
+    sheet.row(
+        Some(25.0),
+        ["title", "timelineStart", "timelineEnd", "showMilestones"].map(Value::text).into(),
+        header,
+    );
+    let body = RowStyle::new(&mut styles, "FFF0F0F0", None);
+    sheet.styled_row(vec![
+        (Value::text(&project.title), body.text),
+        date_value(&project.timeline_start, &body),
+        date_value(&project.timeline_end, &body),
+        (Value::Bool(project.show_milestones), body.text),
+    ]);
+    sheets.push(sheet);
+
+    // Tasks
+    let mut sheet = Sheet::new("Tasks", &[(1, 1, 35.0), (2, 3, 15.0), (4, 4, 10.0), (5, 14, 40.0), (15, 16, 12.0)]);
+    let header = header_style(&mut styles, "FF27AE60", true);
+    let mut columns = vec!["name", "start", "end", "hours"];
+    let subtask_headers: Vec<String> = (1..=MAX_SUBTASKS).map(|i| format!("subtask{}", i)).collect();
+    columns.extend(subtask_headers.iter().map(String::as_str));
+    columns.extend(["color", "colorIndex"]);
+    sheet.row(Some(30.0), columns.into_iter().map(Value::text).collect(), header);
+    for task in &project.tasks {
+        // The left border shows the task's color, from colorIndex when it resolves
+        let color = task
+            .color_index
+            .and_then(|i| palette.get(i))
+            .or(task.color.as_ref())
+            .and_then(|c| argb(c));
+        let body = RowStyle::new(&mut styles, "FFF8F9FA", Some(color.as_deref().unwrap_or(GRID)));
+        let mut cells = vec![
+            (Value::text(&task.name), body.text),
+            date_value(&task.start, &body),
+            date_value(&task.end, &body),
+            (Value::Number(task.hours), body.text),
+        ];
+        cells.extend((0..MAX_SUBTASKS).map(|i| (Value::text(task.subtasks.get(i).map(String::as_str).unwrap_or("")), body.text)));
+        cells.push((Value::text(task.color.as_deref().unwrap_or("")), body.text));
+        cells.push((task.color_index.map(|i| Value::Number(i as f64)).unwrap_or(Value::Empty), body.text));
+        sheet.styled_row(cells);
+    }
+    let last_task_row = VALIDATED_ROWS.max(project.tasks.len() + 1);
+    sheet.validations.push(color_index_validation(palette.len(), &format!("P2:P{}", last_task_row)));
+    sheets.push(sheet);
+
+    // Milestones
+    let mut sheet = Sheet::new("Milestones", &[(1, 1, 25.0), (2, 2, 18.0), (3, 3, 35.0)]);
+    let header = header_style(&mut styles, "FF9B59B6", false);
+    sheet.row(Some(25.0), ["name", "date", "linkedTask"].map(Value::text).into(), header);
+    let rows = [RowStyle::new(&mut styles, "FFFFFFFF", None), RowStyle::new(&mut styles, "FFF8F9FA", None)];
+    for (idx, milestone) in project.milestones.iter().enumerate() {
+        let body = &rows[idx % 2];
+        let linked = milestone
+            .task_index
+            .and_then(|i| project.tasks.get(i))
+            .map(|t| t.name.as_str())
+            .unwrap_or("");
+        sheet.styled_row(vec![
+            (Value::text(&escape_line_breaks(&milestone.name)), body.text),
+            date_value(&milestone.date, body),
+            (Value::text(linked), body.text),
+        ]);
+    }
+    let last_milestone_row = VALIDATED_ROWS.max(project.milestones.len() + 1);
+    sheet.validations.push(format!(
+        r#"<dataValidation type="list" allowBlank="1" showErrorMessage="1" errorStyle="warning" errorTitle="Invalid Task" error="Please select a task from the dropdown list or leave blank." sqref="C2:C{}"><formula1>{}</formula1></dataValidation>"#,
+        last_milestone_row, TASK_NAMES_RANGE
+    ));
+    sheets.push(sheet);
+
+    // PausePeriods
+    let mut sheet = Sheet::new("PausePeriods", &[(1, 2, 18.0)]);
+    let header = header_style(&mut styles, "FFE67E22", false);
+    sheet.row(Some(25.0), ["start", "end"].map(Value::text).into(), header);
+    let body = RowStyle::new(&mut styles, "FFF0F0F0", None);
+    for pause in &project.pause_periods {
+        sheet.styled_row(vec![date_value(&pause.start, &body), date_value(&pause.end, &body)]);
+    }
+    sheets.push(sheet);
+
+    let sheet_xml: Vec<String> = sheets.iter().map(|s| s.to_xml(&mut strings)).collect();
+    write_package(&project.title, &sheets, sheet_xml, strings, styles)
+}
+
+/// colorIndex dropdown of palette positions; Excel caps inline lists at 255
+/// characters, so very long palettes fall back to a whole-number range check
+fn color_index_validation(palette_len: usize, sqref: &str) -> String {
+    let attrs = format!(
+        r#"allowBlank="1" showErrorMessage="1" errorStyle="warning" errorTitle="Invalid colorIndex" error="Pick a row of the Palette sheet (0 is the first color) or leave blank." sqref="{}""#,
+        sqref
+    );
+    let list = (0..palette_len).map(|i| i.to_string()).collect::<Vec<_>>().join(",");
+    if list.len() <= 255 {
+        format!(r#"<dataValidation type="list" {}><formula1>"{}"</formula1></dataValidation>"#, attrs, list)
+    } else {
+        format!(
+            r#"<dataValidation type="whole" operator="between" {}><formula1>0</formula1><formula2>{}</formula2></dataValidation>"#,
+            attrs,
+            palette_len - 1
+        )
+    }
+}
+
+/// Date cell as a serial day number, or the raw text when it is not a YYYY-MM-DD date
+fn date_value(value: &str, style: &RowStyle) -> (Value, usize) {
+    match chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        Ok(date) => {
+            let base = chrono::NaiveDate::from_ymd_opt(1899, 12, 30).expect("valid base date");
+            (Value::Number((date - base).num_days() as f64), style.date)
+        }
+        Err(_) => (Value::text(value), style.text),
+    }
+}
+
+/// "#RRGGBB" (or "RRGGBB") as an opaque ARGB color for styles.xml
+fn argb(color: &str) -> Option<String> {
+    let hex = color.trim().trim_start_matches('#');
+    (hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit())).then(|| format!("FF{}", hex.to_uppercase()))
+}
+
+fn header_style(styles: &mut Styles, fill: &str, wrap: bool) -> usize {
+    let alignment = if wrap {
+        r#"horizontal="center" vertical="center" wrapText="1""#
+    } else {
+        r#"horizontal="center" vertical="center""#
+    };
+    styles.xf(Font::bold(WHITE, 11), Some(fill), None, false, Some(alignment))
+}
+
+enum Value {
+    Empty,
+    Text(String),
+    Number(f64),
+    Bool(bool),
+}
+
+impl Value {
+    fn text(value: &str) -> Value {
+        Value::Text(value.to_string())
+    }
+}
+
+/// Text and date styles for the body rows of a sheet
+struct RowStyle {
+    text: usize,
+    date: usize,
+}
+
+impl RowStyle {
+    /// `left_border` adds the grid border with a medium left edge in that color
+    fn new(styles: &mut Styles, fill: &str, left_border: Option<&str>) -> Self {
+        let border = left_border.map(|left| {
+            format!(
+                r#"<border><left style="medium"><color rgb="{left}"/></left><right style="thin"><color rgb="{GRID}"/></right><top style="thin"><color rgb="{GRID}"/></top><bottom style="thin"><color rgb="{GRID}"/></bottom><diagonal/></border>"#
+            )
+        });
+        RowStyle {
+            text: styles.xf(Font::plain(Some(DARK_TEXT), 11), Some(fill), border.as_deref(), false, None),
+            date: styles.xf(Font::plain(Some(DARK_TEXT), 11), Some(fill), border.as_deref(), true, None),
+        }
+    }
+}
+
+struct Font;
+
+impl Font {
+    fn plain(color: Option<&str>, size: u32) -> String {
+        let color = color.map(|c| format!(r#"<color rgb="{}"/>"#, c)).unwrap_or_default();
+        format!(r#"<font>{}<sz val="{}"/><name val="Calibri"/><family val="2"/></font>"#, color, size)
+    }
+
+    fn bold(color: &str, size: u32) -> String {
+        format!(r#"<font><b/><color rgb="{}"/><sz val="{}"/><name val="Calibri"/><family val="2"/></font>"#, color, size)
+    }
+
+    fn italic(size: u32) -> String {
+        format!(r#"<font><i/><sz val="{}"/><name val="Calibri"/><family val="2"/></font>"#, size)
+    }
+}
+
+/// styles.xml builder that hands out cell format ids, reusing identical entries
+struct Styles {
+    fonts: Vec<String>,
+    fills: Vec<String>,
+    borders: Vec<String>,
+    xfs: Vec<String>,
+}
+
+impl Styles {
+    fn new() -> Self {
+        Styles {
+            fonts: vec![Font::plain(None, 11)],
+            fills: vec![
+                r#"<fill><patternFill patternType="none"/></fill>"#.to_string(),
+                r#"<fill><patternFill patternType="gray125"/></fill>"#.to_string(),
+            ],
+            borders: vec!["<border><left/><right/><top/><bottom/><diagonal/></border>".to_string()],
+            xfs: vec![r#"<xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/>"#.to_string()],
+        }
+    }
+
+    fn xf(&mut self, font: String, fill: Option<&str>, border: Option<&str>, date: bool, alignment: Option<&str>) -> usize {
+        let font_id = intern(&mut self.fonts, font);
+        let fill_id = fill
+            .map(|rgb| {
+                intern(
+                    &mut self.fills,
+                    format!(r#"<fill><patternFill patternType="solid"><fgColor rgb="{}"/></patternFill></fill>"#, rgb),
+                )
+            })
+            .unwrap_or(0);
+        let border_id = border.map(|b| intern(&mut self.borders, b.to_string())).unwrap_or(0);
+        let mut xf = format!(
+            r#"<xf numFmtId="{}" fontId="{}" fillId="{}" borderId="{}" xfId="0" applyFont="1""#,
+            if date { DATE_FORMAT_ID } else { 0 },
+            font_id,
+            fill_id,
+            border_id
+        );
+        if date {
+            xf.push_str(r#" applyNumberFormat="1""#);
+        }
+        if fill.is_some() {
+            xf.push_str(r#" applyFill="1""#);
+        }
+        if border.is_some() {
+            xf.push_str(r#" applyBorder="1""#);
+        }
+        match alignment {
+            Some(alignment) => xf.push_str(&format!(r#" applyAlignment="1"><alignment {}/></xf>"#, alignment)),
+            None => xf.push_str("/>"),
+        }
+        intern(&mut self.xfs, xf)
+    }
+
+    fn to_xml(&self) -> String {
+        let list = |tag: &str, items: &[String]| format!(r#"<{tag} count="{}">{}</{tag}>"#, items.len(), items.concat());
+        format!(
+            r#"{XML_HEADER}<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><numFmts count="1"><numFmt numFmtId="{DATE_FORMAT_ID}" formatCode="yyyy\-mm\-dd"/></numFmts>{}{}{}<cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>{}<cellStyles count="1"><cellStyle name="Normal" xfId="0" builtinId="0"/></cellStyles></styleSheet>"#,
+            list("fonts", &self.fonts),
+            list("fills", &self.fills),
+            list("borders", &self.borders),
+            list("cellXfs", &self.xfs),
+        )
+    }
+}
+
+/// Index of `item` in `items`, appending it first when it is new
+fn intern(items: &mut Vec<String>, item: String) -> usize {
+    items.iter().position(|i| *i == item).unwrap_or_else(|| {
+        items.push(item);
+        items.len() - 1
+    })
+}
+
+#[derive(Default)]
+struct SharedStrings {
+    strings: Vec<String>,
+    index: std::collections::HashMap<String, usize>,
+    count: usize,
+}
+
+impl SharedStrings {
+    fn add(&mut self, value: &str) -> usize {
+        self.count += 1;
+        if let Some(idx) = self.index.get(value) {
+            return *idx;
+        }
+        self.strings.push(value.to_string());
+        self.index.insert(value.to_string(), self.strings.len() - 1);
+        self.strings.len() - 1
+    }
+
+    fn to_xml(&self) -> String {
+        let items: String = self
+            .strings
+            .iter()
+            .map(|s| {
+                let space = if s.trim() != s || s.contains('\n') { r#" xml:space="preserve""# } else { "" };
+                format!("<si><t{}>{}</t></si>", space, escape_xml(s))
+            })
+            .collect();
+        format!(
+            r#"{XML_HEADER}<sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" count="{}" uniqueCount="{}">{}</sst>"#,
+            self.count,
+            self.strings.len(),
+            items
+        )
+    }
+}
+
+struct Sheet {
+    name: &'static str,
+    columns: Vec<(u32, u32, f64)>,
+    rows: Vec<Row>,
+    merges: Vec<String>,
+    validations: Vec<String>,
+}
+
+struct Row {
+    height: Option<f64>,
+    style: Option<usize>,
+    cells: Vec<(Value, usize)>,
+}
+
+impl Sheet {
+    fn new(name: &'static str, columns: &[(u32, u32, f64)]) -> Self {
+        Sheet {
+            name,
+            columns: columns.to_vec(),
+            rows: Vec::new(),
+            merges: Vec::new(),
+            validations: Vec::new(),
+        }
+    }
+
+    /// A row whose cells and row format all share `style`
+    fn row(&mut self, height: Option<f64>, values: Vec<Value>, style: usize) {
+        let row_style = (style != 0).then_some(style);
+        self.rows.push(Row {
+            height,
+            style: row_style,
+            cells: values.into_iter().map(|v| (v, style)).collect(),
+        });
+    }
+
+    fn styled_row(&mut self, cells: Vec<(Value, usize)>) {
+        let style = cells.first().map(|(_, s)| *s);
+        self.rows.push(Row { height: None, style, cells });
+    }
+
+    fn to_xml(&self, strings: &mut SharedStrings) -> String {
+        let width = self.rows.iter().map(|r| r.cells.len()).max().unwrap_or(1).max(1);
+        let mut out = format!(
+            r#"{XML_HEADER}<worksheet {SHEET_NS}><dimension ref="A1:{}{}"/><sheetViews><sheetView workbookViewId="0"><pane ySplit="1" topLeftCell="A2" activePane="bottomLeft" state="frozen"/><selection pane="bottomLeft"/></sheetView></sheetViews><sheetFormatPr defaultRowHeight="15"/><cols>"#,
+            column_name(width - 1),
+            self.rows.len().max(1)
+        );
+        for (min, max, width) in &self.columns {
+            out.push_str(&format!(r#"<col min="{}" max="{}" width="{}" customWidth="1"/>"#, min, max, width));
+        }
+        out.push_str("</cols><sheetData>");
+        for (idx, row) in self.rows.iter().enumerate() {
+            let number = idx + 1;
+            out.push_str(&format!(r#"<row r="{}""#, number));
+            if let Some(height) = row.height {
+                out.push_str(&format!(r#" ht="{}" customHeight="1""#, height));
+            }
+            if let Some(style) = row.style {
+                out.push_str(&format!(r#" s="{}" customFormat="1""#, style));
+            }
+            out.push('>');
+            for (col, (value, style)) in row.cells.iter().enumerate() {
+                let reference = format!("{}{}", column_name(col), number);
+                let style = if *style != 0 { format!(r#" s="{}""#, style) } else { String::new() };
+                match value {
+                    Value::Empty => out.push_str(&format!(r#"<c r="{}"{}/>"#, reference, style)),
+                    Value::Text(text) => out.push_str(&format!(
+                        r#"<c r="{}"{} t="s"><v>{}</v></c>"#,
+                        reference,
+                        style,
+                        strings.add(text)
+                    )),
+                    Value::Number(number) => {
+                        out.push_str(&format!(r#"<c r="{}"{}><v>{}</v></c>"#, reference, style, number))
+                    }
+                    Value::Bool(flag) => out.push_str(&format!(
+                        r#"<c r="{}"{} t="b"><v>{}</v></c>"#,
+                        reference,
+                        style,
+                        u8::from(*flag)
+                    )),
+                }
+            }
+            out.push_str("</row>");
+        }
+        out.push_str("</sheetData>");
+        if !self.merges.is_empty() {
+            out.push_str(&format!(r#"<mergeCells count="{}">"#, self.merges.len()));
+            for merge in &self.merges {
+                out.push_str(&format!(r#"<mergeCell ref="{}"/>"#, merge));
+            }
+            out.push_str("</mergeCells>");
+        }
+        if !self.validations.is_empty() {
+            out.push_str(&format!(
+                r#"<dataValidations count="{}">{}</dataValidations>"#,
+                self.validations.len(),
+                self.validations.concat()
+            ));
+        }
+        out.push_str(r#"<pageMargins left="0.7" right="0.7" top="0.75" bottom="0.75" header="0.3" footer="0.3"/></worksheet>"#);
+        out
+    }
+}
+
+/// Zero-based column index as a spreadsheet column name (0 → A, 26 → AA)
+fn column_name(mut index: usize) -> String {
+    let mut name = Vec::new();
+    loop {
+        name.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    name.reverse();
+    String::from_utf8(name).unwrap_or_default()
+}
+
+/// Zip the sheets into an .xlsx package with their styles and shared strings
+fn write_package(
+    title: &str,
+    sheets: &[Sheet],
+    sheet_xml: Vec<String>,
+    strings: SharedStrings,
+    styles: Styles,
+) -> Result<Vec<u8>, String> {
+    const SPREADSHEET: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml";
+    let mut sheet_entries = String::new();
+    let mut workbook_rels = format!(
+        r#"<Relationship Id="rId1" Type="{REL}/styles" Target="styles.xml"/><Relationship Id="rId2" Type="{REL}/sharedStrings" Target="sharedStrings.xml"/>"#
+    );
+    for (idx, sheet) in sheets.iter().enumerate() {
+        let number = idx + 1;
+        sheet_entries.push_str(&format!(r#"<sheet name="{}" sheetId="{}" r:id="rId{}"/>"#, sheet.name, number, number + 2));
+        workbook_rels.push_str(&format!(
+            r#"<Relationship Id="rId{}" Type="{}/worksheet" Target="worksheets/sheet{}.xml"/>"#,
+            number + 2,
+            REL,
+            number
+        ));
+    }
+
+    let mut package = Package::new("workbook", title, "xl/workbook.xml", &format!("{SPREADSHEET}.sheet.main+xml"), "");
+    package.add(
+        "xl/workbook.xml",
+        None,
+        format!(r#"{XML_HEADER}<workbook {SHEET_NS}><workbookPr/><bookViews><workbookView/></bookViews><sheets>{sheet_entries}</sheets></workbook>"#),
+    );
+    package.add("xl/_rels/workbook.xml.rels", None, relationships(&workbook_rels));
+    package.add("xl/styles.xml", Some(&format!("{SPREADSHEET}.styles+xml")), styles.to_xml());
+    package.add(
+        "xl/sharedStrings.xml",
+        Some(&format!("{SPREADSHEET}.sharedStrings+xml")),
+        strings.to_xml(),
+    );
+    for (idx, xml) in sheet_xml.into_iter().enumerate() {
+        package.add(
+            format!("xl/worksheets/sheet{}.xml", idx + 1),
+            Some(&format!("{SPREADSHEET}.worksheet+xml")),
+            xml,
+        );
+    }
+    package.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn sample() -> Project {
+        let palette: Vec<String> = ["#E31E26", "#2CABDB", "#5C2D91"].map(String::from).into();
+        Project {
+            title: "Audit <2026>".to_string(),
+            timeline_start: "2025-12-01".to_string(),
+            timeline_end: "2026-02-14".to_string(),
+            show_milestones: false,
+            tasks: vec![
+                Task {
+                    name: "Planning & Preparation".to_string(),
+                    start: "2025-12-02".to_string(),
+                    end: "2025-12-14".to_string(),
+                    hours: 12.5,
+                    subtasks: (1..=MAX_SUBTASKS).map(|i| format!("Step {}", i)).collect(),
+                    color: Some(palette[1].clone()),
+                    color_index: Some(1),
+                },
+                Task {
+                    name: "Scans".to_string(),
+                    start: "2025-12-15".to_string(),
+                    end: "2026-01-11".to_string(),
+                    hours: 20.0,
+                    subtasks: vec!["Scan site".to_string()],
+                    color: Some("#123456".to_string()),
+                    color_index: None,
+                },
+            ],
+            milestones: vec![
+                Milestone {
+                    name: "Project\nKickoff".to_string(),
+                    date: "2025-12-02".to_string(),
+                    task_index: Some(1),
+                },
+                Milestone {
+                    name: "Files in C:\\new".to_string(),
+                    date: "2026-01-25".to_string(),
+                    task_index: None,
+                },
+            ],
+            pause_periods: vec![PausePeriod {
+                start: "2025-12-23".to_string(),
+                end: "2026-01-04".to_string(),
+                label: None,
+            }],
+            palette,
+        }
+    }
+
+    fn sheet_xml(xlsx: &[u8], number: usize) -> String {
+        let mut archive = zip::ZipArchive::new(Cursor::new(xlsx)).unwrap();
+        let mut xml = String::new();
+        archive
+            .by_name(&format!("xl/worksheets/sheet{}.xml", number))
+            .unwrap()
+            .read_to_string(&mut xml)
+            .unwrap();
+        xml
+    }
+
+    #[test]
+    fn written_workbook_reads_back() {
+        let project = sample();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plan.xlsx");
+        std::fs::write(&path, write_workbook(&project).unwrap()).unwrap();
+        assert!(!is_legacy_workbook(&path));
+
+        let read = read_workbook(&path).unwrap();
+        assert_eq!(serde_json::to_value(&read).unwrap(), serde_json::to_value(&project).unwrap());
+    }
+
+    #[test]
+    fn milestone_line_breaks_are_written_as_backslash_n() {
+        let xlsx = write_workbook(&sample()).unwrap();
+        let mut archive = zip::ZipArchive::new(Cursor::new(xlsx.as_slice())).unwrap();
+        let mut strings = String::new();
+        archive.by_name("xl/sharedStrings.xml").unwrap().read_to_string(&mut strings).unwrap();
+        assert!(strings.contains(">Project\\nKickoff<"));
+        assert!(strings.contains(">Files in C:\\\\new<"));
+    }
+
+    #[test]
+    fn dropdowns_cover_the_validated_rows() {
+        let xlsx = write_workbook(&sample()).unwrap();
+        let tasks = sheet_xml(&xlsx, 4);
+        assert!(tasks.contains(r#"sqref="P2:P100"><formula1>"0,1,2"</formula1></dataValidation>"#));
+        let milestones = sheet_xml(&xlsx, 5);
+        assert!(milestones.contains(r#"sqref="C2:C100"><formula1>Tasks!$A$2:$A$1000</formula1></dataValidation>"#));
+
+        // Long palettes outgrow Excel's 255-character list limit
+        let range = color_index_validation(200, "P2:P300");
+        assert!(range.starts_with(r#"<dataValidation type="whole" operator="between""#));
+        assert!(range.ends_with(r#"sqref="P2:P300"><formula1>0</formula1><formula2>199</formula2></dataValidation>"#));
+    }
+}
@@ -0,0 +1,114 @@
+use std::io::{Cursor, Write as _};
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+pub const XML_HEADER: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n";
+/// Namespace of the relationship types between Office package parts
+pub const REL: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+/// Escape text for XML (and HTML) element content and quoted attribute values
+pub fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// A `.rels` part holding the given `<Relationship>` elements
+pub fn relationships(body: &str) -> String {
+    format!(
+        r#"{XML_HEADER}<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">{body}</Relationships>"#
+    )
+}
+
+/// An Office Open XML package (.docx, .pptx, .xlsx) being assembled. Starts with
+/// the parts every such file carries: root relationships and document properties.
+pub struct Package {
+    /// Names the format in error messages
+    kind: &'static str,
+    defaults: String,
+    overrides: String,
+    parts: Vec<(String, Vec<u8>)>,
+}
+
+impl Package {
+    /// `main` is the path of the main document part and `main_type` its content
+    /// type; `app` is extra XML for docProps/app.xml, e.g. a slide count
+    pub fn new(kind: &'static str, title: &str, main: &str, main_type: &str, app: &str) -> Self {
+        let mut package = Self {
+            kind,
+            defaults: String::new(),
+            overrides: String::new(),
+            parts: Vec::new(),
+        };
+        package.default_type("rels", "application/vnd.openxmlformats-package.relationships+xml");
+        package.default_type("xml", "application/xml");
+        package.add(
+            "_rels/.rels",
+            None,
+            relationships(&format!(
+                concat!(
+                    r#"<Relationship Id="rId1" Type="{rel}/officeDocument" Target="{main}"/>"#,
+                    r#"<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/>"#,
+                    r#"<Relationship Id="rId3" Type="{rel}/extended-properties" Target="docProps/app.xml"/>"#,
+                ),
+                rel = REL,
+                main = main
+            )),
+        );
+        package.add(
+            "docProps/core.xml",
+            Some("application/vnd.openxmlformats-package.core-properties+xml"),
+            format!(
+                r#"{XML_HEADER}<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/"><dc:title>{}</dc:title><dc:creator>GanttGen</dc:creator></cp:coreProperties>"#,
+                escape_xml(title)
+            ),
+        );
+        package.add(
+            "docProps/app.xml",
+            Some("application/vnd.openxmlformats-officedocument.extended-properties+xml"),
+            format!(
+                r#"{XML_HEADER}<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/extended-properties"><Application>GanttGen</Application>{app}</Properties>"#
+            ),
+        );
+        package.overrides.push_str(&format!(r#"<Override PartName="/{main}" ContentType="{main_type}"/>"#));
+        package
+    }
+
+    /// Content type for every part with this file extension
+    pub fn default_type(&mut self, extension: &str, content_type: &str) {
+        self.defaults
+            .push_str(&format!(r#"<Default Extension="{extension}" ContentType="{content_type}"/>"#));
+    }
+
+    /// Add a part; `content_type` is needed unless the extension's default fits
+    pub fn add(&mut self, name: impl Into<String>, content_type: Option<&str>, body: impl Into<Vec<u8>>) {
+        let name = name.into();
+        if let Some(content_type) = content_type {
+            self.overrides
+                .push_str(&format!(r#"<Override PartName="/{name}" ContentType="{content_type}"/>"#));
+        }
+        self.parts.push((name, body.into()));
+    }
+
+    /// Zip the parts in the order they were added. Entries carry the zip format's
+    /// default timestamp, so the same input always produces the same bytes.
+    pub fn finish(self) -> Result<Vec<u8>, String> {
+        let content_types = format!(
+            r#"{XML_HEADER}<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">{}{}</Types>"#,
+            self.defaults, self.overrides
+        );
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+        let parts = std::iter::once(("[Content_Types].xml".to_string(), content_types.into_bytes())).chain(self.parts);
+        for (name, body) in parts {
+            zip.start_file(name.as_str(), options)
+                .and_then(|_| zip.write_all(&body).map_err(Into::into))
+                .map_err(|e| format!("Failed to write {} to {}: {}", name, self.kind, e))?;
+        }
+        let cursor = zip.finish().map_err(|e| format!("Failed to finish {}: {}", self.kind, e))?;
+        Ok(cursor.into_inner())
+    }
+}
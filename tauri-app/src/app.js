@@ -39,6 +39,9 @@ let state = {
     selectedPalette: 'alternating',
     outputDir: null,
     isGenerating: false,
+    jobId: null,
+    lastJobId: null,
+    watchedFile: null,
     lastResult: null,
     // Manual entry state
     manualData: {
@@ -60,6 +63,7 @@ const elements = {
     browseBtn: document.getElementById('browseBtn'),
     selectedFile: document.getElementById('selectedFile'),
     fileName: document.getElementById('fileName'),
+    watchInput: document.getElementById('watchInput'),
     clearFileBtn: document.getElementById('clearFileBtn'),
     manualTab: document.getElementById('manualTab'),
     // Manual entry
@@ -78,10 +82,32 @@ const elements = {
     copyJsonBtn: document.getElementById('copyJsonBtn'),
     saveJsonBtn: document.getElementById('saveJsonBtn'),
     saveXlsxBtn: document.getElementById('saveXlsxBtn'),
+    saveDocxBtn: document.getElementById('saveDocxBtn'),
     // Palette and output
     paletteGrid: document.getElementById('paletteGrid'),
     exportHtml: document.getElementById('exportHtml'),
+    selfContainedHtml: document.getElementById('selfContainedHtml'),
     exportPng: document.getElementById('exportPng'),
+    exportJpeg: document.getElementById('exportJpeg'),
+    exportWebp: document.getElementById('exportWebp'),
+    exportThumbnail: document.getElementById('exportThumbnail'),
+    exportSvg: document.getElementById('exportSvg'),
+    exportPdf: document.getElementById('exportPdf'),
+    exportPptx: document.getElementById('exportPptx'),
+    pptxOptions: document.getElementById('pptxOptions'),
+    pptxWeeksPerSlide: document.getElementById('pptxWeeksPerSlide'),
+    timeoutSecs: document.getElementById('timeoutSecs'),
+    stageTimeoutSecs: document.getElementById('stageTimeoutSecs'),
+    pngOptions: document.getElementById('pngOptions'),
+    pngDpi: document.getElementById('pngDpi'),
+    pngBackground: document.getElementById('pngBackground'),
+    pngWidth: document.getElementById('pngWidth'),
+    pngPadding: document.getElementById('pngPadding'),
+    imageQuality: document.getElementById('imageQuality'),
+    pdfOptions: document.getElementById('pdfOptions'),
+    pdfPageSize: document.getElementById('pdfPageSize'),
+    pdfOrientation: document.getElementById('pdfOrientation'),
+    pdfMargin: document.getElementById('pdfMargin'),
     pngDropShadow: document.getElementById('pngDropShadow'),
     outputDir: document.getElementById('outputDir'),
     selectOutputBtn: document.getElementById('selectOutputBtn'),
@@ -90,6 +116,7 @@ const elements = {
     progressSection: document.getElementById('progressSection'),
     progressBar: document.getElementById('progressBar'),
     progressText: document.getElementById('progressText'),
+    cancelGenerationBtn: document.getElementById('cancelGenerationBtn'),
     resultSection: document.getElementById('resultSection'),
     resultSuccess: document.getElementById('resultSuccess'),
     resultError: document.getElementById('resultError'),
@@ -98,6 +125,7 @@ const elements = {
     openOutputBtn: document.getElementById('openOutputBtn'),
     viewHtmlBtn: document.getElementById('viewHtmlBtn'),
     viewPngBtn: document.getElementById('viewPngBtn'),
+    copyTranscriptBtn: document.getElementById('copyTranscriptBtn'),
     tryAgainBtn: document.getElementById('tryAgainBtn')
 };
 
@@ -120,6 +148,7 @@ async function initializeMainApp() {
     setupDragAndDrop();
     setupManualEntry();
     await setupProgressListener();
+    await setupWatchListener();
     initializeDefaultDates();
     await initializeDefaultOutputFolder();
     updateGenerateButton();
@@ -643,11 +672,41 @@ function setupEventListeners() {
         elements.selectOutputBtn.addEventListener('click', selectOutputDirectory);
     }
 
+    // Resolution and background only matter for image output
+    if (elements.pngOptions) {
+        const imageToggles = [elements.exportPng, elements.exportJpeg, elements.exportWebp, elements.exportThumbnail]
+            .filter(Boolean);
+        imageToggles.forEach(toggle => {
+            toggle.addEventListener('change', () => {
+                const anyImage = imageToggles.some(t => t.checked);
+                elements.pngOptions.style.display = anyImage ? 'flex' : 'none';
+            });
+        });
+    }
+
+    // Page settings only matter for PDF output
+    if (elements.exportPdf && elements.pdfOptions) {
+        elements.exportPdf.addEventListener('change', () => {
+            elements.pdfOptions.style.display = elements.exportPdf.checked ? 'flex' : 'none';
+        });
+    }
+
+    // Slide splitting only matters for PPTX output
+    if (elements.exportPptx && elements.pptxOptions) {
+        elements.exportPptx.addEventListener('change', () => {
+            elements.pptxOptions.style.display = elements.exportPptx.checked ? 'flex' : 'none';
+        });
+    }
+
     // Generate button
     if (elements.generateBtn) {
         elements.generateBtn.addEventListener('click', generateGantt);
     }
 
+    if (elements.cancelGenerationBtn) {
+        elements.cancelGenerationBtn.addEventListener('click', cancelGeneration);
+    }
+
     // Log thumbnail sizing for debug mode
     const thumb = document.getElementById('viewThumbDay');
     if (thumb) {
@@ -686,6 +745,20 @@ function setupEventListeners() {
     if (elements.viewPngBtn) {
         elements.viewPngBtn.addEventListener('click', viewPngFile);
     }
+    if (elements.watchInput) {
+        elements.watchInput.addEventListener('change', () => {
+            if (elements.watchInput.checked) {
+                startWatching();
+            } else {
+                stopWatching();
+            }
+        });
+    }
+
+    if (elements.copyTranscriptBtn) {
+        elements.copyTranscriptBtn.addEventListener('click', copyTranscript);
+    }
+
     if (elements.tryAgainBtn) {
         elements.tryAgainBtn.addEventListener('click', resetResults);
     }
@@ -768,6 +841,9 @@ function setupManualEntry() {
     if (elements.saveXlsxBtn) {
         elements.saveXlsxBtn.addEventListener('click', saveXlsxFile);
     }
+    if (elements.saveDocxBtn) {
+        elements.saveDocxBtn.addEventListener('click', saveDocxFile);
+    }
 
     // Initialize with empty state message
     renderTasks();
@@ -1498,7 +1574,6 @@ async function copyJson() {
 
 async function saveJsonFile() {
     const jsonData = getManualDataAsJson();
-    const jsonString = JSON.stringify(jsonData, null, 2);
 
     try {
         const filePath = await save({
@@ -1510,7 +1585,11 @@ async function saveJsonFile() {
         });
 
         if (filePath) {
-            await writeTextFile(filePath, jsonString);
+            // Canonical output keeps repeated saves byte-identical for version control
+            await invoke('save_project_json', {
+                project: jsonData,
+                outputPath: filePath
+            });
             // Use this saved file as input
             state.inputFile = filePath;
             updateGenerateButton();
@@ -1522,7 +1601,6 @@ async function saveJsonFile() {
 
 async function saveXlsxFile() {
     const jsonData = getManualDataAsJson();
-    const jsonString = JSON.stringify(jsonData, null, 2);
 
     try {
         // Prompt user to choose save location for XLSX
@@ -1535,17 +1613,9 @@ async function saveXlsxFile() {
         });
 
         if (filePath) {
-            // Create temp JSON file first
-            const tempDirPath = await tempDir();
-            const timestamp = Date.now();
-            const tempJsonPath = await pathJoin(tempDirPath, `ganttgen_temp_${timestamp}.json`);
-
-            // Write temp JSON file
-            await writeTextFile(tempJsonPath, jsonString);
-
-            // Call backend to convert JSON to Excel
+            // The backend writes the workbook natively, no Node runtime needed
             await invoke('export_to_excel', {
-                jsonPath: tempJsonPath,
+                project: jsonData,
                 excelPath: filePath
             });
 
@@ -1562,6 +1632,39 @@ async function saveXlsxFile() {
     }
 }
 
+async function saveDocxFile() {
+    const jsonData = getManualDataAsJson();
+
+    try {
+        const filePath = await save({
+            filters: [{
+                name: 'Word',
+                extensions: ['docx']
+            }],
+            defaultPath: 'gantt_project.docx'
+        });
+
+        if (filePath) {
+            // Chart and tables use the palette and view mode picked for generation
+            await invoke('export_docx', {
+                project: jsonData,
+                outputPath: filePath,
+                palette: state.selectedPalette,
+                viewMode: document.querySelector('input[name="viewMode"]:checked')?.value || 'day'
+            });
+
+            const originalText = elements.saveDocxBtn.textContent;
+            elements.saveDocxBtn.textContent = 'Saved!';
+            setTimeout(() => {
+                elements.saveDocxBtn.textContent = originalText;
+            }, 1500);
+        }
+    } catch (error) {
+        console.error('Failed to save DOCX file:', error);
+        alert(`Failed to save DOCX file: ${error}`);
+    }
+}
+
 function escapeHtml(text) {
     const div = document.createElement('div');
     div.textContent = text;
@@ -1602,9 +1705,68 @@ function setupDragAndDrop() {
     });
 }
 
+async function setupWatchListener() {
+    await listen('watch-result', (event) => {
+        const { job_id, result, error } = event.payload;
+        state.lastJobId = job_id;
+        if (!state.isGenerating) {
+            elements.progressSection.style.display = 'none';
+        }
+        if (result) {
+            state.lastResult = result;
+            showSuccess(result);
+        } else {
+            showError(error);
+        }
+    });
+}
+
+// Regenerate from the input file whenever it is saved, with the current form options
+async function startWatching() {
+    if (!state.inputFile) {
+        elements.watchInput.checked = false;
+        return;
+    }
+    try {
+        if (state.watchedFile && state.watchedFile !== state.inputFile) {
+            await stopWatching();
+        }
+        await invoke('watch_input', { path: state.inputFile, options: getGenerateOptions(state.inputFile) });
+        state.watchedFile = state.inputFile;
+        elements.watchInput.checked = true;
+    } catch (error) {
+        console.error('Failed to watch input file:', error);
+        elements.watchInput.checked = false;
+        showError(error);
+    }
+}
+
+async function stopWatching() {
+    const path = state.watchedFile;
+    state.watchedFile = null;
+    if (elements.watchInput) {
+        elements.watchInput.checked = false;
+    }
+    if (!path) return;
+    try {
+        await invoke('unwatch', { path });
+    } catch (error) {
+        console.error('Failed to stop watching input file:', error);
+    }
+}
+
 async function setupProgressListener() {
     await listen('generation-progress', (event) => {
-        const { step, progress } = event.payload;
+        const { job_id, state: jobState, step, progress } = event.payload;
+        const fromWatch = state.watchedFile && job_id && job_id.startsWith('watch-');
+        if (job_id && job_id !== state.jobId && !fromWatch) return;
+        if (fromWatch && !state.isGenerating) {
+            elements.progressSection.style.display = 'block';
+        }
+        if (jobState === 'cancelled') {
+            updateProgress(0, 'Cancelled');
+            return;
+        }
         updateProgress(progress, step);
     });
 }
@@ -1615,7 +1777,7 @@ async function openFileDialog() {
             multiple: false,
             filters: [{
                 name: 'GanttGen Input',
-                extensions: ['json', 'xlsx']
+                extensions: ['json', 'xlsx', 'xls', 'mmd', 'csv']
             }]
         });
 
@@ -1632,6 +1794,9 @@ async function handleFileSelection(filePath) {
         // Validate the file
         await invoke('validate_input_file', { path: filePath });
 
+        if (state.watchedFile && state.watchedFile !== filePath) {
+            await stopWatching();
+        }
         state.inputFile = filePath;
 
         // Extract filename from path
@@ -1735,6 +1900,7 @@ function populateUIFromManualData() {
 }
 
 function clearFile() {
+    stopWatching();
     state.inputFile = null;
     elements.selectedFile.style.display = 'none';
     elements.dropZone.style.display = 'block';
@@ -1773,6 +1939,57 @@ function updateProgress(percent, text) {
     elements.progressText.textContent = text;
 }
 
+async function cancelGeneration() {
+    if (!state.jobId) return;
+    elements.cancelGenerationBtn.disabled = true;
+    updateProgress(0, 'Cancelling...');
+    try {
+        await invoke('cancel_generation', { jobId: state.jobId });
+    } catch (error) {
+        console.error('Failed to cancel generation:', error);
+        elements.cancelGenerationBtn.disabled = false;
+    }
+}
+
+// Number typed into an input, or the fallback when the input is missing, blank or invalid
+function numberInput(input, fallback, parse = parseFloat) {
+    const value = input ? parse(input.value) : NaN;
+    return Number.isNaN(value) ? fallback : value;
+}
+
+// Generation options from the form, for a given input file
+function getGenerateOptions(inputPath) {
+    return {
+        input_path: inputPath,
+        output_path: state.outputDir ? `${state.outputDir}/output_gantt_chart.html` : null,
+        palette: state.selectedPalette,
+        self_contained_html: elements.selfContainedHtml ? elements.selfContainedHtml.checked : false,
+        export_png: elements.exportPng ? elements.exportPng.checked : false,
+        png_drop_shadow: elements.pngDropShadow ? elements.pngDropShadow.checked : false,
+        png_dpi: elements.pngDpi ? parseFloat(elements.pngDpi.value) || 192 : 192,
+        png_background: elements.pngBackground ? elements.pngBackground.value : null,
+        // A fixed width takes precedence over the DPI
+        png_width: elements.pngWidth ? parseInt(elements.pngWidth.value, 10) || null : null,
+        png_padding: elements.pngPadding ? parseFloat(elements.pngPadding.value) || 0 : 0,
+        export_jpeg: elements.exportJpeg ? elements.exportJpeg.checked : false,
+        jpeg_quality: elements.imageQuality ? parseInt(elements.imageQuality.value, 10) || 90 : 90,
+        export_webp: elements.exportWebp ? elements.exportWebp.checked : false,
+        webp_quality: elements.imageQuality ? parseInt(elements.imageQuality.value, 10) || 90 : 90,
+        export_thumbnail: elements.exportThumbnail ? elements.exportThumbnail.checked : false,
+        view_mode: document.querySelector('input[name="viewMode"]:checked')?.value || 'day',
+        export_svg: elements.exportSvg ? elements.exportSvg.checked : false,
+        export_pdf: elements.exportPdf ? elements.exportPdf.checked : false,
+        pdf_page_size: elements.pdfPageSize ? elements.pdfPageSize.value : 'a4',
+        pdf_orientation: elements.pdfOrientation ? elements.pdfOrientation.value : 'landscape',
+        pdf_margin_mm: numberInput(elements.pdfMargin, 10),
+        export_pptx: elements.exportPptx ? elements.exportPptx.checked : false,
+        pptx_weeks_per_slide: elements.pptxWeeksPerSlide ? parseInt(elements.pptxWeeksPerSlide.value, 10) || null : null,
+        // 0 turns a limit off, so only an explicit 0 does; blank keeps the default
+        timeout_secs: numberInput(elements.timeoutSecs, 600, (v) => parseInt(v, 10)),
+        stage_timeout_secs: numberInput(elements.stageTimeoutSecs, 180, (v) => parseInt(v, 10))
+    };
+}
+
 async function generateGantt() {
     if (state.isGenerating) return;
     
@@ -1790,6 +2007,8 @@ async function generateGantt() {
 
     // Show progress
     elements.progressSection.style.display = 'block';
+    state.jobId = crypto.randomUUID();
+    elements.cancelGenerationBtn.disabled = false;
     updateProgress(0, 'Initializing...');
 
     try {
@@ -1806,23 +2025,26 @@ async function generateGantt() {
         // Write temp file
         await writeTextFile(inputPath, jsonString);
         
-        const options = {
-            input_path: inputPath,
-            output_path: state.outputDir ? `${state.outputDir}/output_gantt_chart.html` : null,
-            palette: state.selectedPalette,
-            export_png: elements.exportPng ? elements.exportPng.checked : false,
-            png_drop_shadow: elements.pngDropShadow ? elements.pngDropShadow.checked : false,
-            view_mode: document.querySelector('input[name="viewMode"]:checked')?.value || 'day'
-        };
+        const options = { ...getGenerateOptions(inputPath), job_id: state.jobId };
 
         const result = await invoke('generate_gantt', { options });
 
         state.lastResult = result;
         showSuccess(result);
+
+        // Later saves regenerate with the options just used
+        if (state.watchedFile) {
+            await startWatching();
+        }
     } catch (error) {
-        showError(error);
+        // A cancelled run needs no error card; the user asked for it
+        if (error !== 'Generation cancelled') {
+            showError(error);
+        }
     } finally {
         state.isGenerating = false;
+        state.lastJobId = state.jobId;
+        state.jobId = null;
         updateGenerateButton();
         elements.progressSection.style.display = 'none';
     }
@@ -1851,7 +2073,48 @@ function showSuccess(result) {
                 <svg width="16" height="16" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2">
                     <polyline points="20 6 9 17 4 12"></polyline>
                 </svg>
-                <span>${result.png_path}</span>
+                <span>${result.png_path}${result.png_width ? ` (${result.png_width}×${result.png_height} px)` : ''}</span>
+            </div>
+        `;
+    }
+    for (const imagePath of [result.jpeg_path, result.webp_path, result.thumbnail_path]) {
+        if (!imagePath) continue;
+        filesHtml += `
+            <div class="result-file">
+                <svg width="16" height="16" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2">
+                    <polyline points="20 6 9 17 4 12"></polyline>
+                </svg>
+                <span>${imagePath}</span>
+            </div>
+        `;
+    }
+    if (result.svg_path) {
+        filesHtml += `
+            <div class="result-file">
+                <svg width="16" height="16" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2">
+                    <polyline points="20 6 9 17 4 12"></polyline>
+                </svg>
+                <span>${result.svg_path}</span>
+            </div>
+        `;
+    }
+    if (result.pdf_path) {
+        filesHtml += `
+            <div class="result-file">
+                <svg width="16" height="16" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2">
+                    <polyline points="20 6 9 17 4 12"></polyline>
+                </svg>
+                <span>${result.pdf_path}</span>
+            </div>
+        `;
+    }
+    if (result.pptx_path) {
+        filesHtml += `
+            <div class="result-file">
+                <svg width="16" height="16" viewBox="0 0 24 24" fill="none" stroke="currentColor" stroke-width="2">
+                    <polyline points="20 6 9 17 4 12"></polyline>
+                </svg>
+                <span>${result.pptx_path}</span>
             </div>
         `;
     }
@@ -1878,6 +2141,23 @@ function showError(error) {
     elements.errorMessage.textContent = typeof error === 'string' ? error : error.message || 'Unknown error';
 }
 
+async function copyTranscript() {
+    if (!state.lastJobId) return;
+    try {
+        const lines = await invoke('get_transcript', { jobId: state.lastJobId });
+        const text = lines.map(line => `${line.timestamp} [${line.stream}] ${line.text}`).join('\n');
+        await navigator.clipboard.writeText(text);
+        // Brief visual feedback
+        const originalText = elements.copyTranscriptBtn.textContent;
+        elements.copyTranscriptBtn.textContent = 'Copied!';
+        setTimeout(() => {
+            elements.copyTranscriptBtn.textContent = originalText;
+        }, 1500);
+    } catch (error) {
+        console.error('Failed to copy build output:', error);
+    }
+}
+
 function resetResults() {
     elements.resultSection.style.display = 'none';
     elements.resultSuccess.style.display = 'none';
@@ -2,6 +2,19 @@ const { build } = require('../../scripts/build');
 const { loadJSONFixture, createTempFile, cleanupTempFiles, createTempDir, cleanupTempDir } = require('../helpers/testHelpers');
 const path = require('path');
 const fs = require('fs');
+const { spawnSync } = require('child_process');
+
+const BUILD_SCRIPT = path.join(__dirname, '..', '..', 'scripts', 'build.js');
+
+// Run build.js as the desktop app does; returns the exit status and the JSON-lines events
+function runBuildScript(args, input) {
+    const result = spawnSync(process.execPath, [BUILD_SCRIPT, ...args], { input, encoding: 'utf8' });
+    const events = result.stdout
+        .split('\n')
+        .filter(line => line.startsWith('{'))
+        .map(line => JSON.parse(line));
+    return { status: result.status, events };
+}
 
 describe('Build Integration Tests', () => {
     let tempFiles = [];
@@ -159,3 +172,153 @@ describe('Build Integration Tests', () => {
     });
 });
 
+
+describe('Build Error Codes', () => {
+    let tempFiles = [];
+
+    afterEach(() => {
+        cleanupTempFiles(tempFiles);
+        tempFiles = [];
+    });
+
+    test('should reject unsupported input formats with UNSUPPORTED_FORMAT', async () => {
+        const inputPath = createTempFile('title,start', '.csv');
+        tempFiles.push(inputPath);
+
+        await expect(build(inputPath, null, { exportPng: false })).rejects.toMatchObject({ code: 'UNSUPPORTED_FORMAT' });
+    });
+
+    test('should reject a missing input file with INPUT_NOT_FOUND', async () => {
+        const inputPath = path.join(__dirname, 'missing-project.json');
+
+        await expect(build(inputPath, null, { exportPng: false })).rejects.toMatchObject({ code: 'INPUT_NOT_FOUND' });
+    });
+
+    test('should reject an invalid project with VALIDATION_FAILED', async () => {
+        const invalidProject = loadJSONFixture('invalid-project.json');
+        const inputPath = createTempFile(JSON.stringify(invalidProject, null, 2), '.json');
+        tempFiles.push(inputPath);
+
+        await expect(build(inputPath, null, { exportPng: false })).rejects.toMatchObject({ code: 'VALIDATION_FAILED' });
+    });
+});
+
+describe('Build Event Stream', () => {
+    let tempFiles = [];
+    let tempDirs = [];
+
+    afterEach(() => {
+        cleanupTempFiles(tempFiles);
+        tempDirs.forEach(dir => cleanupTempDir(dir));
+        tempFiles = [];
+        tempDirs = [];
+    });
+
+    function projectFile() {
+        const validProject = loadJSONFixture('valid-project.json');
+        const inputPath = createTempFile(JSON.stringify(validProject, null, 2), '.json');
+        tempFiles.push(inputPath);
+        return inputPath;
+    }
+
+    test('should emit stage and artifact events with --events', () => {
+        const outputDir = createTempDir();
+        tempDirs.push(outputDir);
+        const outputPath = path.join(outputDir, 'chart.html');
+
+        const { status, events } = runBuildScript(['--input', projectFile(), '--output', outputPath, '--no-png', '--events']);
+
+        expect(status).toBe(0);
+        expect(events.slice(0, 5)).toEqual([
+            { event: 'stage_started', stage: 'parse' },
+            { event: 'stage_finished', stage: 'parse' },
+            { event: 'stage_started', stage: 'html' },
+            { event: 'artifact', kind: 'html', path: outputPath, palette: null, viewMode: 'day' },
+            { event: 'stage_finished', stage: 'html' }
+        ]);
+        // The config copy is saved when config/ is writable and reported as a warning otherwise
+        expect(events).toHaveLength(6);
+        expect(['artifact', 'warning']).toContain(events[5].event);
+        expect(fs.existsSync(outputPath)).toBe(true);
+    });
+
+    test('should build every palette and view mode variant', () => {
+        const outputDir = createTempDir();
+        tempDirs.push(outputDir);
+        const outputPath = path.join(outputDir, 'chart.html');
+
+        const { status, events } = runBuildScript([
+            '--input', projectFile(), '--output', outputPath, '--no-png', '--events',
+            '--palette', 'reds,purples_a', '--view-mode', 'day,week'
+        ]);
+
+        expect(status).toBe(0);
+        const artifacts = events.filter(e => e.event === 'artifact' && e.kind === 'html');
+        expect(artifacts).toEqual([
+            { event: 'artifact', kind: 'html', path: path.join(outputDir, 'chart_reds.html'), palette: 'reds', viewMode: 'day' },
+            { event: 'artifact', kind: 'html', path: path.join(outputDir, 'chart_reds_week.html'), palette: 'reds', viewMode: 'week' },
+            { event: 'artifact', kind: 'html', path: path.join(outputDir, 'chart_purples_a.html'), palette: 'purples_a', viewMode: 'day' },
+            { event: 'artifact', kind: 'html', path: path.join(outputDir, 'chart_purples_a_week.html'), palette: 'purples_a', viewMode: 'week' }
+        ]);
+        artifacts.forEach(artifact => expect(fs.existsSync(artifact.path)).toBe(true));
+        expect(fs.existsSync(outputPath)).toBe(false);
+    });
+
+    test('should write no events without --events', () => {
+        const outputDir = createTempDir();
+        tempDirs.push(outputDir);
+
+        const { status, events } = runBuildScript(['--input', projectFile(), '--output', path.join(outputDir, 'chart.html'), '--no-png']);
+
+        expect(status).toBe(0);
+        expect(events).toEqual([]);
+    });
+
+    test('should end with a coded error event when the build fails', () => {
+        const { status, events } = runBuildScript(['--input', path.join(__dirname, 'missing-project.json'), '--events']);
+
+        expect(status).toBe(1);
+        expect(events).toHaveLength(1);
+        expect(events[0].event).toBe('error');
+        expect(events[0].code).toBe('INPUT_NOT_FOUND');
+        expect(events[0].message).toContain('missing-project.json');
+    });
+});
+
+describe('Build Worker Mode', () => {
+    let tempFiles = [];
+    let tempDirs = [];
+
+    afterEach(() => {
+        cleanupTempFiles(tempFiles);
+        tempDirs.forEach(dir => cleanupTempDir(dir));
+        tempFiles = [];
+        tempDirs = [];
+    });
+
+    test('should answer each request with a done event and exit when stdin closes', () => {
+        const validProject = loadJSONFixture('valid-project.json');
+        const inputPath = createTempFile(JSON.stringify(validProject, null, 2), '.json');
+        tempFiles.push(inputPath);
+        const outputDir = createTempDir();
+        tempDirs.push(outputDir);
+        const outputPath = path.join(outputDir, 'chart.html');
+
+        const requests = [
+            { id: '1', args: ['--input', inputPath, '--output', outputPath, '--no-png'] },
+            { id: '2', args: [] }
+        ];
+        const { status, events } = runBuildScript(['--worker'], requests.map(r => JSON.stringify(r)).join('\n') + '\n');
+
+        expect(status).toBe(0);
+        expect(events[0]).toEqual({ event: 'ready' });
+        const first = events.findIndex(e => e.event === 'done');
+        expect(events[first]).toEqual({ event: 'done', id: '1', ok: true });
+        expect(events.slice(1, first)).toContainEqual({ event: 'artifact', kind: 'html', path: outputPath, palette: null, viewMode: 'day' });
+        expect(events.slice(first + 1)).toEqual([
+            { event: 'error', code: 'BUILD_FAILED', message: 'Missing --input' },
+            { event: 'done', id: '2', ok: false }
+        ]);
+        expect(fs.existsSync(outputPath)).toBe(true);
+    });
+});
@@ -0,0 +1,59 @@
+const { parseArgs, variantOutputPath } = require('../../scripts/build');
+const path = require('path');
+
+describe('parseArgs', () => {
+    test('should return null without an input file', () => {
+        expect(parseArgs([])).toBeNull();
+        expect(parseArgs(['--palette', 'reds'])).toBeNull();
+        expect(parseArgs(['--input'])).toBeNull();
+    });
+
+    test('should resolve input and output paths', () => {
+        const parsed = parseArgs(['-i', 'config/project.json', '-o', 'out/chart.html']);
+
+        expect(parsed.inputPath).toBe(path.resolve('config/project.json'));
+        expect(parsed.outputPath).toBe(path.resolve('out/chart.html'));
+    });
+
+    test('should default to one day view build with the project palette', () => {
+        const parsed = parseArgs(['--input', 'project.json']);
+
+        expect(parsed.outputPath).toBeNull();
+        expect(parsed.palettes).toEqual([null]);
+        expect(parsed.viewModes).toEqual(['day']);
+        expect(parsed.options.exportPng).toBe(true);
+    });
+
+    test('should split comma-separated palettes and view modes', () => {
+        const parsed = parseArgs(['--input', 'project.json', '--palette', 'reds, purples_a,,', '--view-mode', 'day,week']);
+
+        expect(parsed.palettes).toEqual(['reds', 'purples_a']);
+        expect(parsed.viewModes).toEqual(['day', 'week']);
+    });
+
+    test('should fall back to day view for an empty view mode list', () => {
+        const parsed = parseArgs(['--input', 'project.json', '--view-mode', ',']);
+
+        expect(parsed.viewModes).toEqual(['day']);
+    });
+
+    test('should read the PNG and drop shadow flags', () => {
+        const parsed = parseArgs(['--input', 'project.json', '--no-png', '--drop-shadow']);
+
+        expect(parsed.options).toEqual({ exportPng: false, dropShadow: true });
+    });
+});
+
+describe('variantOutputPath', () => {
+    const outputPath = path.join('out', 'chart.html');
+
+    test('should add the lowercase palette and week suffixes before the extension', () => {
+        expect(variantOutputPath(outputPath, 'Reds', 'week')).toBe(path.join('out', 'chart_reds_week.html'));
+        expect(variantOutputPath(outputPath, 'purples_a', 'day')).toBe(path.join('out', 'chart_purples_a.html'));
+        expect(variantOutputPath(outputPath, null, 'week')).toBe(path.join('out', 'chart_week.html'));
+    });
+
+    test('should keep the path without a palette in day view', () => {
+        expect(variantOutputPath(outputPath, null, 'day')).toBe(outputPath);
+    });
+});
@@ -96,6 +96,13 @@ describe('parseExcel', () => {
         await expect(parseExcel(nonExistentPath)).rejects.toThrow();
     });
 
+    test('should reject legacy .xls workbooks with UNSUPPORTED_FORMAT', async () => {
+        const legacyPath = path.join(__dirname, 'legacy.xls');
+
+        await expect(parseExcel(legacyPath)).rejects.toMatchObject({ code: 'UNSUPPORTED_FORMAT' });
+        await expect(parseExcel(legacyPath)).rejects.toThrow('save it as .xlsx');
+    });
+
     test('should assign colors to tasks without colorIndex', async () => {
         const config = await parseExcel(fixturePath);
 